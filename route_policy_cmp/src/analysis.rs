@@ -0,0 +1,26 @@
+//! Analyses over parsed RPSL policies.
+use crate::parse::*;
+
+use super::*;
+
+pub mod bilateral;
+pub mod infer_rel;
+pub mod simulate;
+
+/// Entries in `versions` of the aut-num `asn`, grouped by each neighbor
+/// their peerings refer to by AS number.
+/// Other peerings, such as as-sets, peering-sets, and AS expressions,
+/// do not say which neighbor they refer to and are ignored.
+pub fn entries_by_neighbor(versions: &Versions, asn: Asn) -> BTreeMap<Asn, Vec<&Entry>> {
+    let mut entries: BTreeMap<Asn, Vec<&Entry>> = BTreeMap::new();
+    for entry in versions.entries() {
+        for peering_action in &entry.mp_peerings {
+            if let AsExpr::Single(AsName::Num(neighbor)) = &peering_action.mp_peering.remote_as {
+                if *neighbor != asn {
+                    entries.entry(*neighbor).or_default().push(entry);
+                }
+            }
+        }
+    }
+    entries
+}
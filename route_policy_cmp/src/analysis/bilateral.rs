@@ -0,0 +1,268 @@
+//! Static consistency check between the export policies of one AS and
+//! the import policies of its neighbor, without needing BGP routes.
+use hashbrown::HashSet;
+use ipnet::IpNet;
+
+use crate::bgp::{
+    cmp::RECURSION_LIMIT,
+    filter::member_op_or,
+    map::AsPathEntry,
+    report::{ToTruth, Truth},
+    Compare, QueryDump, Verbosity,
+};
+
+use super::*;
+
+/// Disagreement between `from`'s exports to `to` and `to`'s imports from
+/// `from`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Mismatch {
+    /// `from` announces `prefixes` to `to`, but `to` does not accept them.
+    NotAccepted {
+        from: Asn,
+        to: Asn,
+        prefixes: Vec<IpNet>,
+    },
+    /// The filters `from` announces to `to` with cannot be expanded to
+    /// prefixes, e.g., `ANY` or AS path regexes.
+    Unexpandable { from: Asn, to: Asn },
+    /// `from` exports to `to`, but `to` does not import from `from`.
+    ExportOnly { from: Asn, to: Asn },
+    /// `to` imports from `from`, but `from` does not export to `to`.
+    ImportOnly { from: Asn, to: Asn },
+}
+
+/// Check every pair of aut-nums in `dump` where one exports to the other
+/// by AS number, and report the [`Mismatch`]es.
+/// Pairs where either aut-num is unrecorded are skipped.
+///
+/// Announced prefixes are the routes the export filters expand to,
+/// ignoring range operators.
+/// Each of them is checked against all the imports of the receiving AS,
+/// as if it were originated by the announcing AS;
+/// a prefix only counts as not accepted if the import check definitely
+/// fails, with all errors reported, not if it is skipped or the import
+/// policies cannot be evaluated, e.g., because of invalid RPSL.
+pub fn check_bilateral(dump: &QueryDump) -> Vec<Mismatch> {
+    let exports: Vec<_> = dump
+        .aut_nums
+        .iter()
+        .par_bridge()
+        .map(|(from, from_an)| (*from, entries_by_neighbor(&from_an.exports, *from)))
+        .collect();
+    let imports: HashSet<(Asn, Asn)> = dump
+        .aut_nums
+        .iter()
+        .flat_map(|(to, to_an)| {
+            entries_by_neighbor(&to_an.imports, *to)
+                .into_keys()
+                .map(|from| (from, *to))
+        })
+        .collect();
+
+    let mut mismatches: Vec<_> = exports
+        .par_iter()
+        .flat_map_iter(|(from, neighbors)| {
+            neighbors.iter().filter_map(|(to, entries)| {
+                let to_an = dump.aut_nums.get(to)?;
+                match imports.contains(&(*from, *to)) {
+                    true => check_pair(dump, *from, *to, to_an, entries),
+                    false => Some(Mismatch::ExportOnly {
+                        from: *from,
+                        to: *to,
+                    }),
+                }
+            })
+        })
+        .collect();
+    let export_pairs: HashSet<(Asn, Asn)> = exports
+        .iter()
+        .flat_map(|(from, neighbors)| neighbors.keys().map(|to| (*from, *to)))
+        .collect();
+    mismatches.extend(
+        imports
+            .iter()
+            .filter(|(from, to)| {
+                dump.aut_nums.contains_key(from) && !export_pairs.contains(&(*from, *to))
+            })
+            .map(|(from, to)| Mismatch::ImportOnly {
+                from: *from,
+                to: *to,
+            }),
+    );
+    mismatches.par_sort_unstable();
+    mismatches
+}
+
+fn check_pair(
+    dump: &QueryDump,
+    from: Asn,
+    to: Asn,
+    to_an: &AutNum,
+    export_entries: &[&Entry],
+) -> Option<Mismatch> {
+    let mut announced = HashSet::new();
+    for entry in export_entries {
+        match expand_filter(dump, &entry.mp_filter, RECURSION_LIMIT) {
+            Some(prefixes) => announced.extend(prefixes),
+            None => return Some(Mismatch::Unexpandable { from, to }),
+        }
+    }
+    let as_path = vec![AsPathEntry::Seq(to), AsPathEntry::Seq(from)];
+    let verbosity = Verbosity::default().with_all_err();
+    let mut prefixes: Vec<_> = announced
+        .into_iter()
+        .filter(|prefix| {
+            let compare = Compare::new(*prefix, as_path.clone()).verbosity(verbosity);
+            compare
+                .check_import(dump, to_an, from, to)
+                .is_some_and(|report| report.truth() == Truth::False)
+        })
+        .collect();
+    if prefixes.is_empty() {
+        return None;
+    }
+    prefixes.sort_unstable();
+    Some(Mismatch::NotAccepted { from, to, prefixes })
+}
+
+/// Expand `filter` to the prefixes it matches, ignoring range operators,
+/// or `None` if it cannot be expanded.
+/// Unrecorded sets and ASes expand to nothing.
+pub fn expand_filter(dump: &QueryDump, filter: &Filter, depth: isize) -> Option<Vec<IpNet>> {
+    if depth <= 0 {
+        return None;
+    }
+    let mut prefixes = Vec::new();
+    match filter {
+        Filter::FilterSet(name) => {
+            if let Some(filter_set) = dump.filter_sets.get(name) {
+                for filter in &filter_set.filters {
+                    prefixes.extend(expand_filter(dump, filter, depth - 1)?);
+                }
+            }
+        }
+        Filter::AddrPrefixSet(ranges) => {
+            prefixes.extend(ranges.iter().map(|range| range.address_prefix))
+        }
+        Filter::RouteSet(name, _) => expand_route_set(dump, name, depth, &mut prefixes)?,
+        Filter::AsNum(num, _) => prefixes.extend(as_routes(dump, *num)),
+        Filter::AsSet(name, _) => expand_as_set(dump, name, &mut prefixes)?,
+        Filter::Or { left, right } => {
+            prefixes.extend(expand_filter(dump, left, depth - 1)?);
+            prefixes.extend(expand_filter(dump, right, depth - 1)?);
+        }
+        Filter::Group(filter) => prefixes.extend(expand_filter(dump, filter, depth - 1)?),
+        Filter::Any
+        | Filter::AsPathRE(_)
+        | Filter::And { .. }
+        | Filter::Not(_)
+        | Filter::Community(_)
+        | Filter::Invalid(_) => return None,
+    }
+    Some(prefixes)
+}
+
+fn as_routes(dump: &QueryDump, num: Asn) -> impl Iterator<Item = IpNet> + '_ {
+    dump.as_routes.get(&num).into_iter().flatten().copied()
+}
+
+fn expand_as_set(dump: &QueryDump, name: &str, prefixes: &mut Vec<IpNet>) -> Option<()> {
+    let mut visited = HashSet::new();
+    let mut to_visit = vec![name];
+    while let Some(name) = to_visit.pop() {
+        if is_any_as_set(name) {
+            return None;
+        }
+        if !visited.insert(name) {
+            continue;
+        }
+        if let Some(as_set_route) = dump.as_set_routes.get(name) {
+            prefixes.extend(&as_set_route.routes);
+            to_visit.extend(as_set_route.set_members.iter().map(SetName::as_str));
+        }
+    }
+    Some(())
+}
+
+/// Expand route-set `name` recursively to the sorted address prefix ranges
+/// it stands for, keeping the range operator of each member,
+/// or `None` if it cannot be expanded.
+/// Members without a range operator take the one they are referenced with,
+/// as when checking filters.
+/// Unrecorded sets and ASes expand to nothing.
+pub fn expand_route_set_ranges(
+    dump: &QueryDump,
+    name: &str,
+    depth: isize,
+) -> Option<Vec<AddrPfxRange>> {
+    let mut ranges = Vec::new();
+    route_set_ranges(dump, name, RangeOperator::NoOp, depth, &mut ranges)?;
+    ranges.sort_unstable();
+    ranges.dedup();
+    Some(ranges)
+}
+
+fn route_set_ranges(
+    dump: &QueryDump,
+    name: &str,
+    op: RangeOperator,
+    depth: isize,
+    ranges: &mut Vec<AddrPfxRange>,
+) -> Option<()> {
+    if depth <= 0 || is_any_route_set(name) {
+        return None;
+    }
+    let Some(route_set) = dump.route_sets.get(name) else {
+        return Some(());
+    };
+    let with_op = |member_op| {
+        let range_operator = member_op_or(member_op, op);
+        move |address_prefix| AddrPfxRange {
+            address_prefix,
+            range_operator,
+        }
+    };
+    for member in &route_set.members {
+        match member {
+            RouteSetMember::Range(range) => {
+                ranges.push(with_op(range.range_operator)(range.address_prefix))
+            }
+            RouteSetMember::NameOp(name, op) => {
+                route_set_ranges(dump, name, *op, depth - 1, ranges)?
+            }
+            RouteSetMember::AsNum(num, member_op) => {
+                ranges.extend(as_routes(dump, *num).map(with_op(*member_op)))
+            }
+            RouteSetMember::AsSet(name, member_op) => {
+                let mut prefixes = Vec::new();
+                expand_as_set(dump, name, &mut prefixes)?;
+                ranges.extend(prefixes.into_iter().map(with_op(*member_op)));
+            }
+        }
+    }
+    Some(())
+}
+
+fn expand_route_set(
+    dump: &QueryDump,
+    name: &str,
+    depth: isize,
+    prefixes: &mut Vec<IpNet>,
+) -> Option<()> {
+    if depth <= 0 || is_any_route_set(name) {
+        return None;
+    }
+    let Some(route_set) = dump.route_sets.get(name) else {
+        return Some(());
+    };
+    for member in &route_set.members {
+        match member {
+            RouteSetMember::Range(range) => prefixes.push(range.address_prefix),
+            RouteSetMember::NameOp(name, _) => expand_route_set(dump, name, depth - 1, prefixes)?,
+            RouteSetMember::AsNum(num, _) => prefixes.extend(as_routes(dump, *num)),
+            RouteSetMember::AsSet(name, _) => expand_as_set(dump, name, prefixes)?,
+        }
+    }
+    Some(())
+}
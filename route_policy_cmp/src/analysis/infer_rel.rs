@@ -0,0 +1,213 @@
+//! Infer AS relationships from the shape of `aut-num` policies.
+//!
+//! An AS that imports `ANY` from a neighbor likely buys transit from it,
+//! and an AS that exports `ANY` to a neighbor likely sells transit to it.
+//! Specific filters on both directions suggest peering.
+use std::{fmt::Display, io::Write};
+
+use crate::bgp::{AsRelDb, Relationship};
+
+use super::*;
+
+/// The role of a neighbor, as declared in an AS's policies.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum NeighborRole {
+    Provider,
+    Customer,
+    Peer,
+}
+
+/// What an AS's policies import from and export to one neighbor.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PolicyShape {
+    pub import_any: bool,
+    pub import_specific: bool,
+    pub export_any: bool,
+    pub export_specific: bool,
+}
+
+impl PolicyShape {
+    fn add(&mut self, filter: &Filter, is_import: bool) {
+        let is_any = matches!(filter, Filter::Any);
+        match (is_import, is_any) {
+            (true, true) => self.import_any = true,
+            (true, false) => self.import_specific = true,
+            (false, true) => self.export_any = true,
+            (false, false) => self.export_specific = true,
+        }
+    }
+
+    /// The role of the neighbor suggested by `self`, if clear.
+    /// Importing `ANY` suggests a provider, exporting `ANY` a customer,
+    /// and specific filters on both directions a peer.
+    pub fn role(&self) -> Option<NeighborRole> {
+        let Self {
+            import_any,
+            import_specific,
+            export_any,
+            export_specific,
+        } = *self;
+        if import_any && !import_specific && !export_any {
+            Some(NeighborRole::Provider)
+        } else if export_any && !export_specific && !import_any {
+            Some(NeighborRole::Customer)
+        } else if import_specific && export_specific && !import_any && !export_any {
+            Some(NeighborRole::Peer)
+        } else {
+            None
+        }
+    }
+}
+
+/// A relationship between two ASes, displayed in the CAIDA as-rel format.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AsRel {
+    ProviderCustomer { provider: Asn, customer: Asn },
+    Peers(Asn, Asn),
+}
+
+impl AsRel {
+    /// The relationship where `neighbor` has `role` from the perspective
+    /// of `asn`.
+    pub fn new(asn: Asn, neighbor: Asn, role: NeighborRole) -> Self {
+        match role {
+            NeighborRole::Provider => Self::ProviderCustomer {
+                provider: neighbor,
+                customer: asn,
+            },
+            NeighborRole::Customer => Self::ProviderCustomer {
+                provider: asn,
+                customer: neighbor,
+            },
+            NeighborRole::Peer => Self::Peers(asn.min(neighbor), asn.max(neighbor)),
+        }
+    }
+}
+
+impl AsRel {
+    /// Whether `self` agrees with the external dataset `db`,
+    /// or `None` if `db` does not have the pair.
+    pub fn agrees_with(&self, db: &AsRelDb) -> Option<bool> {
+        let (from, to, expected) = match *self {
+            AsRel::ProviderCustomer { provider, customer } => {
+                (provider, customer, Relationship::ProviderToCustomer)
+            }
+            AsRel::Peers(a, b) => (a, b, Relationship::PeerToPeer),
+        };
+        db.get(from, to).map(|rel| rel == expected)
+    }
+}
+
+impl Display for AsRel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsRel::ProviderCustomer { provider, customer } => {
+                write!(f, "{provider}|{customer}|-1")
+            }
+            AsRel::Peers(a, b) => write!(f, "{a}|{b}|0"),
+        }
+    }
+}
+
+/// Relationship inferred for a pair of neighbors.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum PairInference {
+    /// Both sides declare policies that agree.
+    Consistent(AsRel),
+    /// Only one side declares policies with a clear role for the other side.
+    OneSided { rel: AsRel, declared_by: Asn },
+    /// The two sides declare policies that suggest different relationships.
+    Contradictory {
+        a: Asn,
+        b: Asn,
+        /// The role of `b` according to `a`.
+        a_view: NeighborRole,
+        /// The role of `a` according to `b`.
+        b_view: NeighborRole,
+    },
+}
+
+impl PairInference {
+    /// The inferred relationship, unless contradictory.
+    pub fn rel(&self) -> Option<AsRel> {
+        match self {
+            PairInference::Consistent(rel) | PairInference::OneSided { rel, .. } => Some(*rel),
+            PairInference::Contradictory { .. } => None,
+        }
+    }
+}
+
+/// Collect the [`PolicyShape`] each AS in `dump` declares for each neighbor,
+/// see [`entries_by_neighbor`].
+pub fn policy_shapes(dump: &Dump) -> BTreeMap<(Asn, Asn), PolicyShape> {
+    let mut shapes: BTreeMap<(Asn, Asn), PolicyShape> = BTreeMap::new();
+    for (asn, aut_num) in &dump.aut_nums {
+        for (versions, is_import) in [(&aut_num.imports, true), (&aut_num.exports, false)] {
+            for (neighbor, entries) in entries_by_neighbor(versions, *asn) {
+                let shape = shapes.entry((*asn, neighbor)).or_default();
+                for entry in entries {
+                    shape.add(&entry.mp_filter, is_import);
+                }
+            }
+        }
+    }
+    shapes
+}
+
+/// Infer the relationship between each pair of neighbors in `dump`
+/// from their [`PolicyShape`]s, keyed by the pair with the smaller ASN first.
+/// Pairs where neither side has a clear role are left out.
+pub fn infer_relationships(dump: &Dump) -> BTreeMap<(Asn, Asn), PairInference> {
+    let roles: BTreeMap<(Asn, Asn), NeighborRole> = policy_shapes(dump)
+        .into_iter()
+        .filter_map(|(pair, shape)| Some((pair, shape.role()?)))
+        .collect();
+    let mut inferences = BTreeMap::new();
+    for (&(asn, neighbor), &role) in &roles {
+        let (a, b) = (asn.min(neighbor), asn.max(neighbor));
+        if inferences.contains_key(&(a, b)) {
+            continue;
+        }
+        let inference = match roles.get(&(neighbor, asn)) {
+            None => PairInference::OneSided {
+                rel: AsRel::new(asn, neighbor, role),
+                declared_by: asn,
+            },
+            Some(&other_role) => {
+                let rel = AsRel::new(asn, neighbor, role);
+                if rel == AsRel::new(neighbor, asn, other_role) {
+                    PairInference::Consistent(rel)
+                } else {
+                    let (a_view, b_view) = match asn == a {
+                        true => (role, other_role),
+                        false => (other_role, role),
+                    };
+                    PairInference::Contradictory {
+                        a,
+                        b,
+                        a_view,
+                        b_view,
+                    }
+                }
+            }
+        };
+        inferences.insert((a, b), inference);
+    }
+    inferences
+}
+
+/// Write the relationships in `inferences` that are not contradictory
+/// in the CAIDA as-rel format.
+pub fn write_caida<W: Write>(
+    inferences: &BTreeMap<(Asn, Asn), PairInference>,
+    mut writer: W,
+) -> Result<()> {
+    writeln!(
+        writer,
+        "# Inferred from RPSL aut-num policies. <provider-as>|<customer-as>|-1, <peer-as>|<peer-as>|0"
+    )?;
+    for rel in inferences.values().filter_map(PairInference::rel) {
+        writeln!(writer, "{rel}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! "What-if" simulation of route propagation through RPSL policies,
+//! without BGP data.
+use hashbrown::HashMap;
+use ipnet::IpNet;
+
+use crate::bgp::{map::AsPathEntry, Compare, QueryDump, Report, Verbosity};
+
+use super::*;
+
+/// Options for [`Simulation::propagate`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Simulation {
+    /// Maximum AS path length to propagate to, including the origin.
+    pub max_depth: usize,
+    /// Propagate over hops whose checks are skipped, e.g., because of
+    /// unrecorded sets, as if they were permitted.
+    pub allow_unknown: bool,
+    /// Stop expanding once this many paths are found.
+    pub max_paths: usize,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            allow_unknown: false,
+            max_paths: 0x10000,
+        }
+    }
+}
+
+/// AS paths RPSL permits a prefix to propagate along.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Propagation {
+    /// Permitted AS paths, each as seen by the AS that receives it,
+    /// i.e., the first AS is the receiver and the last is the origin.
+    pub paths: Vec<Vec<Asn>>,
+    /// Whether the search stopped early because of `max_paths`.
+    pub truncated: bool,
+}
+
+impl Propagation {
+    /// The shortest permitted AS path length to each AS reached.
+    pub fn reached(&self) -> BTreeMap<Asn, usize> {
+        let mut reached = BTreeMap::new();
+        for path in &self.paths {
+            let len = reached.entry(path[0]).or_insert(path.len());
+            *len = (*len).min(path.len());
+        }
+        reached
+    }
+}
+
+impl Simulation {
+    /// Propagate `prefix` originated by `origin` through `dump`,
+    /// breadth-first up to `self.max_depth`.
+    /// Each hop from AS X to AS Y is taken if X's exports and Y's imports
+    /// both permit the route with the AS path so far, using
+    /// [`Compare::check_export`] and [`Compare::check_import`].
+    ///
+    /// The neighbors of X are the ASes X exports to by AS number,
+    /// and the ASes that import from X by AS number;
+    /// peerings with sets such as `AS-ANY` are only followed if the other
+    /// side names the AS.
+    pub fn propagate(&self, dump: &QueryDump, prefix: IpNet, origin: Asn) -> Propagation {
+        let neighbors = neighbors(dump);
+        let mut propagation = Propagation::default();
+        let mut frontier = vec![vec![origin]];
+        for _ in 1..self.max_depth {
+            let next: Vec<Vec<Asn>> = frontier
+                .par_iter()
+                .flat_map_iter(|path| {
+                    let from = path[0];
+                    neighbors
+                        .get(&from)
+                        .into_iter()
+                        .flatten()
+                        .filter(|to| !path.contains(to))
+                        .filter(|to| self.permits(dump, prefix, path, **to))
+                        .map(|to| [&[*to], &path[..]].concat())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            let room = self.max_paths - propagation.paths.len();
+            if next.len() > room {
+                propagation.paths.extend(next.into_iter().take(room));
+                propagation.truncated = true;
+                break;
+            }
+            propagation.paths.extend(next.iter().cloned());
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        propagation
+    }
+
+    /// Whether the hop from `path[0]` to `to` is permitted.
+    fn permits(&self, dump: &QueryDump, prefix: IpNet, path: &[Asn], to: Asn) -> bool {
+        let from = path[0];
+        let (Some(from_an), Some(to_an)) = (dump.aut_nums.get(&from), dump.aut_nums.get(&to))
+        else {
+            return self.allow_unknown;
+        };
+        let as_path = [to]
+            .iter()
+            .chain(path)
+            .map(|asn| AsPathEntry::Seq(*asn))
+            .collect();
+        let compare = Compare::new(prefix, as_path).verbosity(Verbosity {
+            show_skips: true,
+            ..Verbosity::default()
+        });
+        [
+            compare.check_export(dump, from_an, from, Some(to)),
+            compare.check_import(dump, to_an, from, to),
+        ]
+        .into_iter()
+        .all(|report| match report {
+            None => true,
+            Some(Report::NeutralExport { .. } | Report::NeutralImport { .. }) => self.allow_unknown,
+            Some(_) => false,
+        })
+    }
+}
+
+/// The neighbors of each AS, named by AS number in its exports or in the
+/// imports of the neighbor.
+fn neighbors(dump: &QueryDump) -> HashMap<Asn, Vec<Asn>> {
+    let mut neighbors: HashMap<Asn, Vec<Asn>> = HashMap::new();
+    for (asn, aut_num) in &dump.aut_nums {
+        for to in entries_by_neighbor(&aut_num.exports, *asn).into_keys() {
+            neighbors.entry(*asn).or_default().push(to);
+        }
+        for from in entries_by_neighbor(&aut_num.imports, *asn).into_keys() {
+            neighbors.entry(from).or_default().push(*asn);
+        }
+    }
+    for tos in neighbors.values_mut() {
+        tos.sort_unstable();
+        tos.dedup();
+    }
+    neighbors
+}
@@ -0,0 +1,276 @@
+//! HTTP server with a JSON API over a [`QueryDump`] kept in memory,
+//! for services that call the checker without linking Rust.
+//!
+//! Endpoints:
+//! - `POST /check` with `{"prefix": "10.0.0.0/8", "as_path": "3 2 1"}`:
+//!   the [`Summary`] and [`Report`]s of [`Compare::check`] on the route,
+//!   with the AS path starting from the collector. An optional `verbosity`
+//!   object sets the [`Verbosity`] fields, missing ones taking their default.
+//! - `GET /aut-num/<AS>`, `GET /as-set/<name>`, `GET /route-set/<name>`:
+//!   the parsed object.
+//! - `GET /expand/<name>`: the AS numbers and unrecorded sets in an as-set,
+//!   or the prefix ranges in a route-set, recursively.
+//! - `GET /status`: the dump directory loaded and its object counts.
+//! - `POST /reload`: load the dump directory again, or the latest one
+//!   if watching.
+//!
+//! Errors are answered as `{"error": "<message>"}`.
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{
+    analysis::bilateral::expand_route_set_ranges,
+    bgp::{cmp::RECURSION_LIMIT, map::parse_as_path, Compare, QueryDump, Summary, Verbosity},
+    parse::{Asn, Dump},
+};
+
+use super::*;
+
+/// Body of `POST /check`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CheckRequest {
+    pub prefix: IpNet,
+    /// AS numbers separated by spaces, as shown by `bgpdump`.
+    pub as_path: String,
+    #[serde(default)]
+    pub verbosity: Verbosity,
+}
+
+/// A dump and the directory it is read from.
+struct Loaded {
+    dir: PathBuf,
+    dump: Arc<QueryDump>,
+}
+
+/// JSON API server, see the module documentation for the endpoints.
+pub struct Api {
+    loaded: RwLock<Loaded>,
+    /// Directory whose latest subdirectory, by name, is the dump to serve.
+    watch: Option<PathBuf>,
+}
+
+impl Api {
+    /// Serve the dump in `dir`.
+    pub fn new(dir: PathBuf, dump: QueryDump) -> Self {
+        let loaded = RwLock::new(Loaded {
+            dir,
+            dump: Arc::new(dump),
+        });
+        Self {
+            loaded,
+            watch: None,
+        }
+    }
+
+    /// Serve the latest dump directory in `parent`,
+    /// switching to newer ones once [`Api::reload`]ed.
+    ///
+    /// New dumps should be written elsewhere and then moved into `parent`,
+    /// so they are not read half-written.
+    pub fn watching(parent: PathBuf) -> Result<Self> {
+        let dir = latest_dump(&parent)?;
+        let dump = load(&dir)?;
+        Ok(Self {
+            watch: Some(parent),
+            ..Self::new(dir, dump)
+        })
+    }
+
+    /// The dump being served and its directory.
+    pub fn current(&self) -> (PathBuf, Arc<QueryDump>) {
+        let loaded = self.loaded.read().expect("Not poisoned");
+        (loaded.dir.clone(), loaded.dump.clone())
+    }
+
+    /// Load the latest dump directory if watching, or the same directory
+    /// otherwise, and switch to it. Unless `force`, skip loading if the
+    /// directory is the one being served. Requests being answered keep
+    /// using the previous dump.
+    /// Return the directory switched to, if any.
+    pub fn reload(&self, force: bool) -> Result<Option<PathBuf>> {
+        let (current, _) = self.current();
+        let dir = match &self.watch {
+            Some(parent) => latest_dump(parent)?,
+            None => current.clone(),
+        };
+        if !force && dir == current {
+            return Ok(None);
+        }
+        let dump = Arc::new(load(&dir)?);
+        *self.loaded.write().expect("Not poisoned") = Loaded {
+            dir: dir.clone(),
+            dump,
+        };
+        debug!("Switched to the dump in {}.", dir.display());
+        Ok(Some(dir))
+    }
+
+    /// Answer requests to `server` forever, each on its own thread.
+    /// If `poll`, check for a new dump directory at that interval.
+    pub fn serve(&self, server: Server, poll: Option<Duration>) {
+        thread::scope(|scope| {
+            if let Some(interval) = poll {
+                scope.spawn(move || loop {
+                    thread::sleep(interval);
+                    if let Err(err) = self.reload(false) {
+                        warn!("Failed to reload the dump: {err:#}.");
+                    }
+                });
+            }
+            for request in server.incoming_requests() {
+                scope.spawn(move || {
+                    if let Err(err) = self.respond(request) {
+                        debug!("Failed to respond: {err:#}.");
+                    }
+                });
+            }
+        });
+    }
+
+    fn respond(&self, mut request: Request) -> Result<()> {
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body)?;
+        let (status, value) = self.handle(request.method().as_str(), request.url(), &body);
+        let content_type =
+            Header::from_bytes("Content-Type", "application/json").expect("Valid header");
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        request.respond(response)?;
+        Ok(())
+    }
+
+    /// Answer a request with `method` on `url` with `body`,
+    /// as the status code and JSON value.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let result = match (method, segments.as_slice()) {
+            ("POST", ["check"]) => self.check(body),
+            ("GET", ["aut-num", num]) => self.aut_num(num),
+            ("GET", ["as-set", name]) => {
+                let (_, dump) = self.current();
+                let as_set = dump.as_sets.get(&name.to_uppercase());
+                as_set.map(to_value).transpose()
+            }
+            ("GET", ["route-set", name]) => {
+                let (_, dump) = self.current();
+                let route_set = dump.route_sets.get(&name.to_uppercase());
+                route_set.map(to_value).transpose()
+            }
+            ("GET", ["expand", name]) => self.expand(name),
+            ("GET", ["status"]) => Ok(Some(self.status())),
+            ("POST", ["reload"]) => self
+                .reload(true)
+                .map(|_| Some(self.status()))
+                .map_err(|err| (500, err)),
+            _ => return error(404, format!("No endpoint for {method} {path}")),
+        };
+        match result {
+            Ok(Some(value)) => (200, value),
+            Ok(None) => error(404, format!("{path} is not recorded")),
+            Err((status, err)) => error(status, format!("{err:#}")),
+        }
+    }
+
+    fn check(&self, body: &str) -> Answer {
+        let request: CheckRequest = serde_json::from_str(body).map_err(bad_request)?;
+        let as_path = parse_as_path(&request.as_path).map_err(|err| (400, err))?;
+        let (_, dump) = self.current();
+        let reports = Compare::new(request.prefix, as_path)
+            .verbosity(request.verbosity)
+            .check(&dump);
+        Ok(Some(json!({
+            "summary": Summary::of(&reports),
+            "reports": reports,
+        })))
+    }
+
+    fn aut_num(&self, num: &str) -> Answer {
+        let num: Asn = num.parse().map_err(|err| (400, err))?;
+        let (_, dump) = self.current();
+        let aut_num = dump.aut_nums.get(&num);
+        aut_num.map(to_value).transpose()
+    }
+
+    fn expand(&self, name: &str) -> Answer {
+        let name = name.to_uppercase();
+        let (_, dump) = self.current();
+        if let Some((nums, unrecorded)) = dump.expand_as_set(&name) {
+            return Ok(Some(json!({ "members": nums, "unrecorded": unrecorded })));
+        }
+        if !dump.route_sets.contains_key(&name) {
+            return Ok(None);
+        }
+        match expand_route_set_ranges(&dump, &name, RECURSION_LIMIT) {
+            Some(ranges) => {
+                let ranges: Vec<_> = ranges.iter().map(ToString::to_string).collect();
+                Ok(Some(json!({ "prefixes": ranges })))
+            }
+            None => Err((
+                422,
+                anyhow::anyhow!("{name} cannot be expanded to prefixes"),
+            )),
+        }
+    }
+
+    fn status(&self) -> Value {
+        let (dir, dump) = self.current();
+        json!({
+            "dir": dir,
+            "aut_nums": dump.aut_nums.len(),
+            "as_sets": dump.as_sets.len(),
+            "route_sets": dump.route_sets.len(),
+            "peering_sets": dump.peering_sets.len(),
+            "filter_sets": dump.filter_sets.len(),
+            "as_routes": dump.as_routes.len(),
+            "set_name_bytes": {
+                "before_interning": dump.name_memory.bytes_before,
+                "after_interning": dump.name_memory.bytes_after,
+            },
+        })
+    }
+}
+
+/// The JSON value found, `None` if not recorded, or the status and error.
+type Answer = Result<Option<Value>, (u16, anyhow::Error)>;
+
+fn error(status: u16, message: String) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn bad_request(err: serde_json::Error) -> (u16, anyhow::Error) {
+    (400, err.into())
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, (u16, anyhow::Error)> {
+    serde_json::to_value(value).map_err(|err| (500, err.into()))
+}
+
+/// Read the parsed dump in `dir` for querying.
+pub fn load(dir: &Path) -> Result<QueryDump> {
+    let parsed = Dump::pal_read(dir).with_context(|| format!("reading {}", dir.display()))?;
+    parsed.log_count();
+    Ok(QueryDump::from_dump(parsed))
+}
+
+/// Subdirectory of `parent` with the greatest name,
+/// such as the latest one when named by date.
+pub fn latest_dump(parent: &Path) -> Result<PathBuf> {
+    let mut latest = None;
+    for entry in read_dir(parent)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
+            latest = latest.max(Some(entry.path()));
+        }
+    }
+    latest.with_context(|| format!("no dump directory in {}", parent.display()))
+}
@@ -1,24 +1,46 @@
 use hashbrown::HashSet;
 
+use crate::parse::Asn;
+
 use super::*;
 
+pub mod aspa;
+pub mod bmp;
 pub mod cmp;
 pub mod filter;
+pub mod intern;
+pub mod json;
 pub mod map;
+pub mod memo;
+pub mod normalize;
 pub mod peering;
 pub mod query;
+pub mod record;
+pub mod relationship;
 pub mod report;
+pub mod rpki;
 pub mod stats;
+pub mod trace;
 pub mod verbosity;
 pub mod wrapper;
 
 pub use {
+    aspa::{AspaDirection, AspaHopCheck, AspaState, Aspas},
+    bmp::{BmpMessage, BmpReader, PeerHeader, Update},
     cmp::Compare,
+    intern::{Interner, NameMemory, SetKey, SetTable},
+    json::{stream_json_lines, JsonFormat, JsonRoute},
+    memo::{CacheStats, PairCache},
+    normalize::{Normalization, Normalizer},
     query::{AsSetRoute, QueryDump},
+    record::{RecordFormat, RecordWriter, ReportRecord, Summary},
+    relationship::{AsRelDb, LeakType, Relationship},
     report::{MatchProblem, Report, ReportItem, SkipReason},
-    stats::AsStats,
+    rpki::{RpkiState, Vrp, Vrps},
+    stats::{AsStats, Counts, DirCounts, IpVersion, Stats, StatsFormat, Summaries},
+    trace::TraceNode,
     verbosity::Verbosity,
-    wrapper::{parse_mrt, Line},
+    wrapper::{check_dedup, parse_mrt, read_mrt, skip_bad_lines, stream_lines, Line},
 };
 
 use filter::CheckFilter;
@@ -0,0 +1,176 @@
+//! AS_PATH verification using AS Provider Authorization (ASPA) objects.
+//! <https://datatracker.ietf.org/doc/draft-ietf-sidrops-aspa-verification/>.
+use std::{fs::File, io::BufReader, io::Read, path::Path, str::FromStr};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::parse::Asn;
+
+use super::{rpki::JsonAsn, *};
+
+/// Result of checking whether one AS attests another as its provider.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AspaHopCheck {
+    ProviderPlus,
+    NotProviderPlus,
+    NoAttestation,
+}
+
+/// Which verification procedure to use, depending on the relationship with
+/// the neighbor the route is received from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AspaDirection {
+    /// Received from a customer, a lateral peer, or a route server.
+    Upstream,
+    /// Received from a provider.
+    Downstream,
+}
+
+impl FromStr for AspaDirection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "upstream" => Self::Upstream,
+            "downstream" => Self::Downstream,
+            other => bail!("Unknown ASPA verification direction {other}"),
+        })
+    }
+}
+
+/// Outcome of ASPA AS_PATH verification.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AspaState {
+    Valid,
+    Invalid,
+    Unknown,
+}
+
+/// Customer ASes mapped to their sets of provider ASes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Aspas {
+    pub providers: HashMap<Asn, HashSet<Asn>>,
+}
+
+impl Aspas {
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Record that `customer` attests `providers` as its providers.
+    pub fn insert(&mut self, customer: Asn, providers: impl IntoIterator<Item = Asn>) {
+        self.providers
+            .entry(customer)
+            .or_default()
+            .extend(providers);
+    }
+
+    /// Whether `provider` is attested as a provider of `customer`.
+    pub fn hop(&self, customer: Asn, provider: Asn) -> AspaHopCheck {
+        match self.providers.get(&customer) {
+            Some(providers) if providers.contains(&provider) => AspaHopCheck::ProviderPlus,
+            Some(_) => AspaHopCheck::NotProviderPlus,
+            None => AspaHopCheck::NoAttestation,
+        }
+    }
+
+    /// Verify `path`, ordered from the origin to the neighbor, i.e.,
+    /// the reverse of the AS_PATH, with prepending removed.
+    pub fn verify(&self, path: &[Asn], direction: AspaDirection) -> AspaState {
+        let n = path.len();
+        let up = |i: usize| self.hop(path[i], path[i + 1]);
+        let down = |i: usize| self.hop(path[n - 1 - i], path[n - 2 - i]);
+        let (max_up, min_up) = ramps(n, up);
+        match direction {
+            AspaDirection::Upstream => match (max_up < n, min_up < n) {
+                (true, _) => AspaState::Invalid,
+                (_, true) => AspaState::Unknown,
+                _ => AspaState::Valid,
+            },
+            AspaDirection::Downstream => {
+                let (max_down, min_down) = ramps(n, down);
+                match (max_up + max_down < n, min_up + min_down < n) {
+                    (true, _) => AspaState::Invalid,
+                    (_, true) => AspaState::Unknown,
+                    _ => AspaState::Valid,
+                }
+            }
+        }
+    }
+
+    /// Read ASPA objects in the JSON format of Routinator or rpki-client:
+    /// `{"aspas": [{"customer": "AS64496", "providers": ["AS64497"]}]}`.
+    /// The customer may also be `customer_asid`, ASNs may be numbers,
+    /// and providers may be objects with an `asid`.
+    pub fn from_json<R: Read>(reader: R) -> Result<Self> {
+        let json: JsonAspas = serde_json::from_reader(reader)?;
+        let mut aspas = Self::default();
+        for aspa in json.aspas {
+            let providers = aspa
+                .providers
+                .iter()
+                .map(|provider| match provider {
+                    JsonProvider::Asn(asn) | JsonProvider::Object { asid: asn } => asn.asn(),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            aspas.insert(aspa.customer.asn()?, providers);
+        }
+        Ok(aspas)
+    }
+
+    /// Read ASPA objects from the JSON file at `path`.
+    pub fn from_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let aspas = Self::from_json(BufReader::new(File::open(path)?))
+            .with_context(|| format!("reading ASPAs from {}", path.display()))?;
+        debug!("Read {} ASPAs from {}.", aspas.len(), path.display());
+        Ok(aspas)
+    }
+}
+
+/// The maximum and minimum lengths of the ramp starting at one end of a path
+/// of `n` ASes, where `hop(i)` checks the `i`th hop from that end.
+/// The maximum ramp stops at the first "Not Provider+",
+/// and the minimum ramp stops at the first hop that is not "Provider+".
+fn ramps(n: usize, hop: impl Fn(usize) -> AspaHopCheck) -> (usize, usize) {
+    let mut max = n;
+    let mut min = n;
+    for i in 0..n.saturating_sub(1) {
+        let hop = hop(i);
+        if min == n && hop != AspaHopCheck::ProviderPlus {
+            min = i + 1;
+        }
+        if hop == AspaHopCheck::NotProviderPlus {
+            max = i + 1;
+            break;
+        }
+    }
+    (max, min)
+}
+
+#[derive(Deserialize)]
+struct JsonAspas {
+    aspas: Vec<JsonAspa>,
+}
+
+#[derive(Deserialize)]
+struct JsonAspa {
+    #[serde(alias = "customer_asid")]
+    customer: JsonAsn,
+    #[serde(alias = "provider_set")]
+    providers: Vec<JsonProvider>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonProvider {
+    Asn(JsonAsn),
+    Object { asid: JsonAsn },
+}
@@ -0,0 +1,345 @@
+//! Decoding of BGP Monitoring Protocol (BMP) streams,
+//! <https://www.rfc-editor.org/rfc/rfc7854>,
+//! into the routes announced in Route Monitoring messages.
+//!
+//! Only the parts needed to check routes are decoded: the per-peer header,
+//! and the announced and withdrawn unicast prefixes and AS path of the
+//! BGP UPDATE in Route Monitoring messages. ADD-PATH NLRI is not supported.
+use std::{
+    io::{ErrorKind, Read},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use ipnet::{Ipv4Net, Ipv6Net};
+
+use super::*;
+
+/// BMP version in the common header of every message.
+pub const BMP_VERSION: u8 = 3;
+const COMMON_HEADER_LEN: usize = 6;
+/// Length limit of one message, to reject garbage before allocating.
+const MAX_MESSAGE_LEN: usize = 0x100000;
+
+const ROUTE_MONITORING: u8 = 0;
+const PEER_DOWN: u8 = 2;
+const PEER_UP: u8 = 3;
+
+const BGP_UPDATE: u8 = 2;
+const AS_PATH: u8 = 2;
+const MP_REACH_NLRI: u8 = 14;
+const MP_UNREACH_NLRI: u8 = 15;
+const AS4_PATH: u8 = 17;
+
+/// Per-peer header of the BMP messages about one peer of the router.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PeerHeader {
+    pub peer_type: u8,
+    pub flags: u8,
+    pub address: IpAddr,
+    pub asn: Asn,
+    pub bgp_id: Ipv4Addr,
+    /// Seconds since the epoch when the message was generated.
+    pub timestamp: u32,
+}
+
+impl PeerHeader {
+    /// The A flag: the peer uses 2-byte AS numbers in AS_PATH.
+    pub fn legacy_as_path(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    /// The peer as the vantage point of the routes it sends.
+    pub fn collector(&self) -> CollectorPeer {
+        CollectorPeer {
+            asn: self.asn,
+            ip: self.address,
+        }
+    }
+}
+
+/// Prefixes and AS path in a BGP UPDATE message.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Update {
+    pub announced: Vec<IpNet>,
+    pub withdrawn: Vec<IpNet>,
+    /// AS path starting from the peer, empty if there is none.
+    pub as_path: Vec<AsPathEntry>,
+}
+
+impl Update {
+    /// A [`Compare`] for each announced prefix,
+    /// with the AS path normalized using `normalizer`.
+    pub fn compares(&self, normalizer: &Normalizer) -> Vec<Compare> {
+        self.announced
+            .iter()
+            .map(|prefix| Compare::with_normalizer(*prefix, self.as_path.clone(), normalizer))
+            .collect()
+    }
+}
+
+/// A BMP message, decoded as far as needed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BmpMessage {
+    RouteMonitoring {
+        peer: PeerHeader,
+        update: Update,
+    },
+    PeerUp(PeerHeader),
+    PeerDown(PeerHeader),
+    /// Other message types, such as Initiation and Statistics Report,
+    /// with the type number.
+    Other(u8),
+}
+
+impl BmpMessage {
+    /// Decode one whole message, including its common header.
+    pub fn decode(message: &[u8]) -> Result<Self> {
+        let mut bytes = Bytes(message);
+        let version = bytes.u8()?;
+        if version != BMP_VERSION {
+            bail!("Unsupported BMP version {version}");
+        }
+        let length = bytes.u32()? as usize;
+        if length != message.len() {
+            bail!("BMP length {length} but got {} bytes", message.len());
+        }
+        Ok(match bytes.u8()? {
+            ROUTE_MONITORING => {
+                let peer = bytes.peer_header()?;
+                let update = bytes.bgp_update(peer.legacy_as_path())?;
+                Self::RouteMonitoring { peer, update }
+            }
+            PEER_UP => Self::PeerUp(bytes.peer_header()?),
+            PEER_DOWN => Self::PeerDown(bytes.peer_header()?),
+            other => Self::Other(other),
+        })
+    }
+}
+
+/// Iterator of the [`BmpMessage`]s in a stream, such as a TCP connection
+/// from a router, or bytes captured from one.
+pub struct BmpReader<R> {
+    reader: R,
+}
+
+impl<R: Read> BmpReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// The bytes of the next whole message, `None` at the end of the stream.
+    pub fn next_bytes(&mut self) -> Result<Option<Vec<u8>>> {
+        let mut header = [0; COMMON_HEADER_LEN];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let length = u32::from_be_bytes(header[1..5].try_into().expect("4 bytes")) as usize;
+        if !(COMMON_HEADER_LEN..=MAX_MESSAGE_LEN).contains(&length) {
+            bail!("Invalid BMP message length {length}");
+        }
+        let mut message = header.to_vec();
+        message.resize(length, 0);
+        self.reader
+            .read_exact(&mut message[COMMON_HEADER_LEN..])
+            .context("Truncated BMP message")?;
+        Ok(Some(message))
+    }
+}
+
+impl<R: Read> Iterator for BmpReader<R> {
+    type Item = Result<BmpMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_bytes() {
+            Ok(message) => message.map(|message| BmpMessage::decode(&message)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Cursor over big-endian bytes, failing instead of reading past the end.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            bail!("Expected {n} more bytes, got {}", self.0.len());
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc7854#section-4.2>.
+    fn peer_header(&mut self) -> Result<PeerHeader> {
+        let peer_type = self.u8()?;
+        let flags = self.u8()?;
+        _ = self.take(8)?; // Peer Distinguisher.
+        let address: [u8; 16] = self.take(16)?.try_into()?;
+        let address = match flags & 0x80 != 0 {
+            true => IpAddr::V6(Ipv6Addr::from(address)),
+            false => IpAddr::V4(Ipv4Addr::new(
+                address[12],
+                address[13],
+                address[14],
+                address[15],
+            )),
+        };
+        let asn = Asn(self.u32()?);
+        let bgp_id = Ipv4Addr::from(self.u32()?);
+        let timestamp = self.u32()?;
+        _ = self.u32()?; // Timestamp microseconds.
+        Ok(PeerHeader {
+            peer_type,
+            flags,
+            address,
+            asn,
+            bgp_id,
+            timestamp,
+        })
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc4271#section-4.3>.
+    fn bgp_update(&mut self, legacy_as_path: bool) -> Result<Update> {
+        _ = self.take(16)?; // Marker.
+        let length = self.u16()? as usize;
+        let message_type = self.u8()?;
+        if message_type != BGP_UPDATE {
+            bail!("Route Monitoring with BGP message type {message_type}");
+        }
+        let mut body = Bytes(self.take(length.saturating_sub(19))?);
+
+        let mut update = Update::default();
+        let withdrawn_len = body.u16()? as usize;
+        update.withdrawn = Bytes(body.take(withdrawn_len)?).prefixes(false)?;
+
+        let attributes_len = body.u16()? as usize;
+        let mut attributes = Bytes(body.take(attributes_len)?);
+        let mut as4_path = None;
+        while !attributes.is_empty() {
+            let flags = attributes.u8()?;
+            let type_code = attributes.u8()?;
+            let length = match flags & 0x10 != 0 {
+                true => attributes.u16()? as usize,
+                false => attributes.u8()? as usize,
+            };
+            let mut value = Bytes(attributes.take(length)?);
+            match type_code {
+                AS_PATH => update.as_path = value.as_path(legacy_as_path)?,
+                // Only meaningful from 2-byte speakers, RFC 6793 section 4.1.
+                AS4_PATH if legacy_as_path => as4_path = Some(value.as_path(false)?),
+                MP_REACH_NLRI => update.announced.extend(value.mp_reach()?),
+                MP_UNREACH_NLRI => update.withdrawn.extend(value.mp_unreach()?),
+                _ => {}
+            }
+        }
+        if let Some(as4_path) = as4_path {
+            merge_as4_path(&mut update.as_path, as4_path);
+        }
+
+        update.announced.extend(body.prefixes(false)?);
+        Ok(update)
+    }
+
+    /// NLRI encoded as prefix length in bits and the significant bytes.
+    fn prefixes(&mut self, v6: bool) -> Result<Vec<IpNet>> {
+        let mut prefixes = Vec::new();
+        while !self.is_empty() {
+            let length = self.u8()?;
+            let significant = self.take((length as usize).div_ceil(8))?;
+            let prefix = match v6 {
+                true => {
+                    let mut address = [0; 16];
+                    address
+                        .get_mut(..significant.len())
+                        .context("IPv6 prefix too long")?
+                        .copy_from_slice(significant);
+                    IpNet::V6(Ipv6Net::new(Ipv6Addr::from(address), length)?)
+                }
+                false => {
+                    let mut address = [0; 4];
+                    address
+                        .get_mut(..significant.len())
+                        .context("IPv4 prefix too long")?
+                        .copy_from_slice(significant);
+                    IpNet::V4(Ipv4Net::new(Ipv4Addr::from(address), length)?)
+                }
+            };
+            prefixes.push(prefix);
+        }
+        Ok(prefixes)
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc4271#section-4.3>, path segments.
+    fn as_path(&mut self, legacy: bool) -> Result<Vec<AsPathEntry>> {
+        let mut as_path = Vec::new();
+        while !self.is_empty() {
+            let segment_type = self.u8()?;
+            let count = self.u8()?;
+            let nums = (0..count)
+                .map(|_| match legacy {
+                    true => self.u16().map(|num| Asn(num.into())),
+                    false => self.u32().map(Asn),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            match segment_type {
+                1 => as_path.push(AsPathEntry::Set(nums)),
+                2 => as_path.extend(nums.into_iter().map(AsPathEntry::Seq)),
+                3 => as_path.push(AsPathEntry::ConfedSeq(nums)),
+                4 => as_path.push(AsPathEntry::ConfedSet(nums)),
+                other => bail!("Unknown AS path segment type {other}"),
+            }
+        }
+        Ok(as_path)
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc4760#section-3>, unicast only.
+    fn mp_reach(&mut self) -> Result<Vec<IpNet>> {
+        let (afi, safi) = (self.u16()?, self.u8()?);
+        let next_hop_len = self.u8()? as usize;
+        _ = self.take(next_hop_len)?;
+        _ = self.u8()?; // Reserved.
+        self.mp_prefixes(afi, safi)
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc4760#section-4>, unicast only.
+    fn mp_unreach(&mut self) -> Result<Vec<IpNet>> {
+        let (afi, safi) = (self.u16()?, self.u8()?);
+        self.mp_prefixes(afi, safi)
+    }
+
+    fn mp_prefixes(&mut self, afi: u16, safi: u8) -> Result<Vec<IpNet>> {
+        match (afi, safi) {
+            (1, 1) => self.prefixes(false),
+            (2, 1) => self.prefixes(true),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Replace the trailing entries of the 2-byte `as_path` with `as4_path`.
+/// <https://www.rfc-editor.org/rfc/rfc6793#section-4.2.3>.
+fn merge_as4_path(as_path: &mut Vec<AsPathEntry>, as4_path: Vec<AsPathEntry>) {
+    if as4_path.len() <= as_path.len() {
+        as_path.truncate(as_path.len() - as4_path.len());
+        as_path.extend(as4_path);
+    }
+}
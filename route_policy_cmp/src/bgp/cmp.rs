@@ -1,10 +1,13 @@
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{
+    cell::Cell,
+    net::{Ipv4Addr, Ipv6Addr},
+};
 
 use ipnet::*;
 
 use crate::parse::*;
 
-use super::*;
+use super::{memo::Memo, *};
 
 use {AsPathEntry::*, MatchProblem::*, Report::*, ReportItem::*, SkipReason::*};
 
@@ -23,17 +26,31 @@ pub struct Compare {
     pub recursion_limit: isize,
     /// [`Verbosity`] level when generating report.
     pub verbosity: Verbosity,
+    /// [`Normalization`]s applied to `as_path`.
+    pub normalizations: Vec<Normalization>,
 }
 
 impl Compare {
-    pub fn new(prefix: IpNet, mut as_path: Vec<AsPathEntry>) -> Self {
-        as_path.dedup();
-        as_path.shrink_to_fit();
+    /// Create [`Compare`] with `as_path` normalized using the default
+    /// [`Normalizer`], which removes prepending and expands AS_SETs with a
+    /// single member.
+    pub fn new(prefix: IpNet, as_path: Vec<AsPathEntry>) -> Self {
+        Self::with_normalizer(prefix, as_path, &Normalizer::default())
+    }
+
+    /// Create [`Compare`] with `as_path` normalized using `normalizer`.
+    pub fn with_normalizer(
+        prefix: IpNet,
+        as_path: Vec<AsPathEntry>,
+        normalizer: &Normalizer,
+    ) -> Self {
+        let (as_path, normalizations) = normalizer.normalize(as_path);
         Self {
             prefix,
             as_path,
             recursion_limit: RECURSION_LIMIT,
             verbosity: Verbosity::default(),
+            normalizations,
         }
     }
 
@@ -45,25 +62,89 @@ impl Compare {
     /// Create [`Compare`] from a line of table dump generated by `bgpdump`
     /// on a MRT file.
     pub fn with_line_dump(line: &str) -> Result<Self> {
+        Self::with_line_dump_normalizer(line, &Normalizer::default())
+    }
+
+    /// Like [`with_line_dump`](#method.with_line_dump),
+    /// but normalize the AS path using `normalizer`.
+    pub fn with_line_dump_normalizer(line: &str, normalizer: &Normalizer) -> Result<Self> {
         let (prefix, as_path, _, _) = parse_table_dump(line)?;
-        Ok(Self::new(prefix, as_path))
+        Ok(Self::with_normalizer(prefix, as_path, normalizer))
+    }
+
+    /// The origin AS, if the AS path ends with a single AS.
+    pub fn origin(&self) -> Option<Asn> {
+        match self.as_path.last()? {
+            Seq(origin) => Some(*origin),
+            _ => None,
+        }
+    }
+
+    /// Validate the origin of `self.prefix` against `vrps`.
+    /// <https://www.rfc-editor.org/rfc/rfc6811#section-2>.
+    pub fn rpki_state(&self, vrps: &Vrps) -> RpkiState {
+        vrps.validate(&self.prefix, self.origin())
     }
 
     /// Check `self` against RPSL policy `dump` and generate reports.
     /// Depending on which [`Verbosity`] `self.verbose` is set to,
     /// the reports have different levels of details.
     /// If `verbosity.stop_at_err`, stops at the first erroneous AS pair.
+    /// If `verbosity.trace`, also reports all errors, see
+    /// [`Verbosity::with_all_err`], and appends a [`Trace`] report.
+    /// The trace's root is [`Truth::False`] if some AS pair definitely fails,
+    /// [`Truth::Unknown`] if some pair cannot be determined,
+    /// and [`Truth::True`] otherwise.
     pub fn check(&self, dump: &QueryDump) -> Vec<Report> {
+        if !self.verbosity.trace {
+            return self.check_pairs(dump, None);
+        }
+        if !self.verbosity.all_err {
+            // Failures only carry the items that tell definite ones from
+            // unknown ones in the trace with all errors reported.
+            let verbosity = self.verbosity.with_all_err();
+            return self.clone().verbosity(verbosity).check(dump);
+        }
+        let as_path: Vec<_> = self.as_path.iter().map(ToString::to_string).collect();
+        let rule = format!("route {} with AS path {}", self.prefix, as_path.join(" "));
+        let (mut reports, mut root) = trace::collect(rule, || self.check_pairs(dump, None));
+        let outcomes: Vec<_> = root.children.iter().map(|child| child.outcome).collect();
+        root.outcome = if outcomes.contains(&Truth::False) {
+            Truth::False
+        } else if outcomes.contains(&Truth::Unknown) || reports.iter().any(Report::is_neutral) {
+            Truth::Unknown
+        } else {
+            Truth::True
+        };
+        reports.push(Trace { root });
+        reports
+    }
+
+    /// Like [`check`](#method.check) against the [`QueryDump`] of `cache`,
+    /// but reuse the results of AS pairs and filters checked before from
+    /// `cache`, if it [`accepts`](PairCache::accepts) `self`.
+    pub fn check_cached(&self, cache: &PairCache) -> Vec<Report> {
+        let dump = cache.dump();
+        match cache.accepts(self) {
+            true => self.check_pairs(dump, Some(cache)),
+            false => self.check(dump),
+        }
+    }
+
+    fn check_pairs(&self, dump: &QueryDump, cache: Option<&PairCache>) -> Vec<Report> {
         let mut reports = Vec::with_capacity(self.as_path.len() * 2);
+        if self.verbosity.show_skips && !self.normalizations.is_empty() {
+            reports.push(NormalizedAsPath {
+                items: self.normalizations.clone(),
+            });
+        }
         if self.as_path.len() == 1 {
-            reports.extend(self.check_last_export(dump));
+            reports.extend(self.check_last_export_memo(dump, cache));
         }
 
-        let reverse_as_path = self.as_path.iter().rev();
-        // Iterate the pairs in `as_path` from right to left, with overlaps.
-        for (from, to) in reverse_as_path.clone().zip(reverse_as_path.skip(1)) {
+        for (from, to) in self.reverse_pairs() {
             if let (Seq(from), Seq(to)) = (from, to) {
-                let r = self.check_pair(dump, *from, *to);
+                let r = self.check_pair_memo(dump, *from, *to, cache);
                 if !r.is_empty() {
                     reports.extend(r);
                     if self.verbosity.stop_at_first {
@@ -81,25 +162,148 @@ impl Compare {
         reports
     }
 
+    /// Iterate the pairs in `as_path` from right to left, with overlaps.
+    pub fn reverse_pairs(&self) -> impl Iterator<Item = (&AsPathEntry, &AsPathEntry)> {
+        let reverse_as_path = self.as_path.iter().rev();
+        reverse_as_path.clone().zip(reverse_as_path.skip(1))
+    }
+
+    /// Verify `self.as_path` against ASPA objects `aspas` and generate
+    /// an [`AspaHop`](Report::AspaHop) report for each AS pair,
+    /// followed by an [`AspaPath`] report.
+    /// Valid paths are only reported if `verbosity.show_success`,
+    /// and unknown paths only if `verbosity.show_skips`.
+    /// AS paths containing AS_SETs are invalid.
+    pub fn check_aspa(&self, aspas: &Aspas, direction: AspaDirection) -> Vec<Report> {
+        let path: Option<Vec<Asn>> = self
+            .as_path
+            .iter()
+            .rev()
+            .map(|entry| match entry {
+                Seq(asn) => Some(*asn),
+                _ => None,
+            })
+            .collect();
+        let state = match &path {
+            Some(path) => aspas.verify(path, direction),
+            None => AspaState::Invalid,
+        };
+        let show = match state {
+            AspaState::Valid => self.verbosity.show_success,
+            AspaState::Unknown => self.verbosity.show_skips,
+            AspaState::Invalid => true,
+        };
+        if !show {
+            return vec![];
+        }
+
+        let mut reports = Vec::with_capacity(self.as_path.len());
+        for (from, to) in self.reverse_pairs() {
+            if let (Seq(from), Seq(to)) = (from, to) {
+                reports.push(Report::AspaHop {
+                    from: *from,
+                    to: *to,
+                    up: aspas.hop(*from, *to),
+                    down: aspas.hop(*to, *from),
+                });
+            }
+        }
+        reports.push(AspaPath { direction, state });
+        reports
+    }
+
+    /// Check `self.as_path` against the valley-free property using AS
+    /// relationships `rels`, and classify the route leaks found.
+    /// Unknown relationships are only reported if `verbosity.show_skips`.
+    pub fn check_leaks(&self, rels: &AsRelDb) -> Vec<Report> {
+        let mut reports = Vec::new();
+        let mut turned = false;
+        // The previous hop and its relationship, if adjacent and known.
+        let mut prev: Option<(Asn, Relationship)> = None;
+        for (from, to) in self.reverse_pairs() {
+            let (Seq(from), Seq(to)) = (from, to) else {
+                prev = None;
+                continue;
+            };
+            let Some(rel) = rels.get(*from, *to) else {
+                if self.verbosity.show_skips {
+                    reports.push(RelationshipUnknown {
+                        from: *from,
+                        to: *to,
+                    });
+                }
+                prev = None;
+                continue;
+            };
+            if turned && rel != Relationship::ProviderToCustomer {
+                let leak = prev.and_then(|(prev_from, prev_rel)| {
+                    LeakType::classify(prev_rel, rel).map(|leak_type| (prev_from, leak_type))
+                });
+                reports.push(match leak {
+                    Some((prev_from, leak_type)) => RouteLeak {
+                        from: prev_from,
+                        leaker: *from,
+                        to: *to,
+                        leak_type,
+                    },
+                    None => ValleyFreeViolation {
+                        from: *from,
+                        to: *to,
+                    },
+                });
+                // Only report further leaks after this one.
+                turned = rel == Relationship::PeerToPeer;
+            } else {
+                turned |= rel.is_turn();
+            }
+            prev = Some((*from, rel));
+        }
+        reports
+    }
+
     pub fn check_last_export(&self, dump: &QueryDump) -> Option<Report> {
+        self.check_last_export_memo(dump, None)
+    }
+
+    fn check_last_export_memo(
+        &self,
+        dump: &QueryDump,
+        cache: Option<&PairCache>,
+    ) -> Option<Report> {
         match self.as_path.last()? {
             Seq(from) => match dump.aut_nums.get(from) {
-                Some(from_an) => self.check_export(dump, from_an, *from, None),
+                Some(from_an) => {
+                    let memo = memo(cache, *from, false);
+                    self.check_export_memo(dump, from_an, *from, None, memo)
+                }
                 None => self.verbosity.show_skips.then(|| {
                     let items = aut_num_unrecorded_items(*from);
                     NeutralSingleExport { from: *from, items }
                 }),
             },
-            Set(from) => self
+            Set(from) | ConfedSeq(from) | ConfedSet(from) => self
                 .verbosity
                 .show_skips
                 .then(|| SetSingleExport { from: from.clone() }),
         }
     }
 
-    pub fn check_pair(&self, dump: &QueryDump, from: usize, to: usize) -> Vec<Report> {
+    pub fn check_pair(&self, dump: &QueryDump, from: Asn, to: Asn) -> Vec<Report> {
+        self.check_pair_memo(dump, from, to, None)
+    }
+
+    fn check_pair_memo(
+        &self,
+        dump: &QueryDump,
+        from: Asn,
+        to: Asn,
+        cache: Option<&PairCache>,
+    ) -> Vec<Report> {
         let from_report = match dump.aut_nums.get(&from) {
-            Some(from_an) => self.check_export(dump, from_an, from, Some(to)),
+            Some(from_an) => {
+                let memo = memo(cache, from, false);
+                self.check_export_memo(dump, from_an, from, Some(to), memo)
+            }
             None => self.verbosity.show_skips.then(|| {
                 let items = aut_num_unrecorded_items(from);
                 NeutralExport { from, to, items }
@@ -110,7 +314,7 @@ impl Compare {
             (from_report, _) => from_report,
         };
         let to_report = match dump.aut_nums.get(&to) {
-            Some(to_an) => self.check_import(dump, to_an, from, to),
+            Some(to_an) => self.check_import_memo(dump, to_an, from, to, memo(cache, to, true)),
             None => self.verbosity.show_skips.then(|| {
                 let items = aut_num_unrecorded_items(to);
                 NeutralImport { from, to, items }
@@ -123,8 +327,19 @@ impl Compare {
         &self,
         dump: &QueryDump,
         from_an: &AutNum,
-        from: usize,
-        to: Option<usize>,
+        from: Asn,
+        to: Option<Asn>,
+    ) -> Option<Report> {
+        self.check_export_memo(dump, from_an, from, to, None)
+    }
+
+    fn check_export_memo(
+        &self,
+        dump: &QueryDump,
+        from_an: &AutNum,
+        from: Asn,
+        to: Option<Asn>,
+        memo: Option<Memo>,
     ) -> Option<Report> {
         if from_an.exports.is_default() {
             return self.verbosity.show_skips.then(|| {
@@ -135,7 +350,15 @@ impl Compare {
                 }
             });
         }
-        let (items, fail) = match self.check_compliant(dump, &from_an.exports, to) {
+        let report = trace::traced(
+            self.verbosity.trace,
+            || match to {
+                Some(to) => format!("AS{from} export to AS{to}"),
+                None => format!("AS{from} export"),
+            },
+            || self.check_compliant_memo(dump, &from_an.exports, to, memo),
+        );
+        let (items, fail) = match report {
             None => {
                 return self.verbosity.show_success.then_some(match to {
                     Some(to) => GoodExport { from, to },
@@ -161,8 +384,19 @@ impl Compare {
         &self,
         dump: &QueryDump,
         to_an: &AutNum,
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
+    ) -> Option<Report> {
+        self.check_import_memo(dump, to_an, from, to, None)
+    }
+
+    fn check_import_memo(
+        &self,
+        dump: &QueryDump,
+        to_an: &AutNum,
+        from: Asn,
+        to: Asn,
+        memo: Option<Memo>,
     ) -> Option<Report> {
         if to_an.imports.is_default() {
             return self.verbosity.show_skips.then(|| NeutralImport {
@@ -171,7 +405,12 @@ impl Compare {
                 items: vec![Skip(ImportEmpty)],
             });
         }
-        let (items, fail) = match self.check_compliant(dump, &to_an.imports, Some(from)) {
+        let report = trace::traced(
+            self.verbosity.trace,
+            || format!("AS{to} import from AS{from}"),
+            || self.check_compliant_memo(dump, &to_an.imports, Some(from), memo),
+        );
+        let (items, fail) = match report {
             None => {
                 return self
                     .verbosity
@@ -193,14 +432,24 @@ impl Compare {
         &self,
         dump: &QueryDump,
         policy: &Versions,
-        accept_num: Option<usize>,
+        accept_num: Option<Asn>,
+    ) -> AnyReport {
+        self.check_compliant_memo(dump, policy, accept_num, None)
+    }
+
+    fn check_compliant_memo(
+        &self,
+        dump: &QueryDump,
+        policy: &Versions,
+        accept_num: Option<Asn>,
+        memo: Option<Memo>,
     ) -> AnyReport {
         let mut aggregator: AnyReportAggregator = match self.prefix {
-            IpNet::V4(_) => self.check_casts(dump, &policy.ipv4, accept_num),
-            IpNet::V6(_) => self.check_casts(dump, &policy.ipv6, accept_num),
+            IpNet::V4(_) => self.check_casts_memo(dump, &policy.ipv4, accept_num, memo),
+            IpNet::V6(_) => self.check_casts_memo(dump, &policy.ipv6, accept_num, memo),
         }?
         .into();
-        aggregator.join(self.check_casts(dump, &policy.any, accept_num)?);
+        aggregator.join(self.check_casts_memo(dump, &policy.any, accept_num, memo)?);
         aggregator.to_any()
     }
 
@@ -208,15 +457,37 @@ impl Compare {
         &self,
         dump: &QueryDump,
         casts: &Casts,
-        accept_num: Option<usize>,
+        accept_num: Option<Asn>,
+    ) -> AnyReport {
+        self.check_casts_memo(dump, casts, accept_num, None)
+    }
+
+    fn check_casts_memo(
+        &self,
+        dump: &QueryDump,
+        casts: &Casts,
+        accept_num: Option<Asn>,
+        memo: Option<Memo>,
     ) -> AnyReport {
         let mut aggregator = AnyReportAggregator::new();
         let specific_cast = match is_multicast(&self.prefix) {
             true => &casts.multicast,
             false => &casts.unicast,
         };
-        for entry in [specific_cast, &casts.any].into_iter().flatten() {
-            aggregator.join(self.check_entry(dump, entry, accept_num).to_any()?);
+        for (index, entry) in [specific_cast, &casts.any]
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let report = trace::traced(
+                self.verbosity.trace,
+                || match entry.index {
+                    Some(attribute) => format!("entry #{index} (attribute #{})", attribute + 1),
+                    None => format!("entry #{index}"),
+                },
+                || self.check_entry_memo(dump, entry, accept_num, memo),
+            );
+            aggregator.join(report.to_any()?);
         }
         aggregator.to_any()
     }
@@ -225,41 +496,79 @@ impl Compare {
         &self,
         dump: &QueryDump,
         entry: &Entry,
-        accept_num: Option<usize>,
+        accept_num: Option<Asn>,
+    ) -> AllReport {
+        self.check_entry_memo(dump, entry, accept_num, None)
+    }
+
+    /// Like [`check_entry`](#method.check_entry), but look up the peering
+    /// and filter results in `memo` first.
+    fn check_entry_memo(
+        &self,
+        dump: &QueryDump,
+        entry: &Entry,
+        accept_num: Option<Asn>,
+        memo: Option<Memo>,
     ) -> AllReport {
         let peering_report = match accept_num {
-            Some(accept_num) => self
-                .check_peering_actions(dump, &entry.mp_peerings, accept_num)
-                .to_all()
-                .map_err(|mut report| {
-                    if self.verbosity.per_entry_err {
-                        report.push(NoMatch(Peering));
-                    }
-                    report
-                })?,
+            Some(accept_num) => {
+                let check = || {
+                    self.check_peering_actions(dump, &entry.mp_peerings, accept_num)
+                        .to_all()
+                };
+                match memo {
+                    Some(memo) => memo.peering(entry, accept_num, check),
+                    None => check(),
+                }
+                .map_err(|report| self.entry_err(report, entry, Peering))?
+            }
             None => None,
         };
-        let filter_report = CheckFilter {
+        let filter_report = match memo {
+            Some(memo) => memo.filter(entry, self.prefix, || self.check_filter(dump, entry)),
+            None => self.check_filter(dump, entry).0,
+        }
+        .map_err(|report| self.entry_err(report, entry, Filter))?;
+        peering_report.join(filter_report).to_all()
+    }
+
+    /// Check the filter of `entry`, and whether the result depends on
+    /// `self.as_path` besides `self.prefix`.
+    fn check_filter(&self, dump: &QueryDump, entry: &Entry) -> (AllReport, bool) {
+        let path_dependent = Cell::new(false);
+        let report = CheckFilter {
             dump,
             compare: self,
             verbosity: self.verbosity,
+            path_dependent: &path_dependent,
         }
         .check(&entry.mp_filter, self.recursion_limit)
-        .to_all()
-        .map_err(|mut report| {
-            if self.verbosity.per_entry_err {
-                report.push(NoMatch(Filter));
+        .to_all();
+        (report, path_dependent.get())
+    }
+
+    /// Add `problem` and the attribute `entry` comes from to the failed
+    /// `report` if [`Verbosity::per_entry_err`].
+    fn entry_err(
+        &self,
+        mut report: ReportItems,
+        entry: &Entry,
+        problem: MatchProblem,
+    ) -> ReportItems {
+        if self.verbosity.per_entry_err {
+            report.push(NoMatch(problem));
+            if let Some(index) = entry.index {
+                report.push(NoMatch(Attribute(index)));
             }
-            report
-        })?;
-        peering_report.join(filter_report).to_all()
+        }
+        report
     }
 
     pub fn check_peering_actions<'a, I>(
         &self,
         dump: &QueryDump,
         peerings: I,
-        accept_num: usize,
+        accept_num: Asn,
     ) -> AnyReport
     where
         I: IntoIterator<Item = &'a PeeringAction>,
@@ -276,7 +585,7 @@ impl Compare {
         &self,
         dump: &QueryDump,
         peering_actions: &PeeringAction,
-        accept_num: usize,
+        accept_num: Asn,
     ) -> AllReport {
         CheckPeering {
             dump,
@@ -298,7 +607,7 @@ impl Compare {
         Ok(None)
     }
 
-    pub fn goes_through_num(&self, num: usize) -> bool {
+    pub fn goes_through_num(&self, num: Asn) -> bool {
         self.as_path.iter().any(|p| p.contains_num(num))
     }
 }
@@ -326,6 +635,10 @@ pub fn is_multicast(prefix: &IpNet) -> bool {
     }
 }
 
-fn aut_num_unrecorded_items(aut_num: usize) -> Vec<ReportItem> {
+fn memo<'a>(cache: Option<&'a PairCache<'a>>, asn: Asn, import: bool) -> Option<Memo<'a>> {
+    cache.map(|cache| Memo { cache, asn, import })
+}
+
+fn aut_num_unrecorded_items(aut_num: Asn) -> Vec<ReportItem> {
     vec![Skip(AutNumUnrecorded(aut_num))]
 }
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use crate::{
     lex::Call,
     parse::{Filter::*, *},
@@ -9,10 +11,21 @@ pub struct CheckFilter<'a> {
     pub dump: &'a QueryDump,
     pub compare: &'a Compare,
     pub verbosity: Verbosity,
+    /// Set if the result depends on `compare.as_path`, not only on
+    /// `compare.prefix`.
+    pub path_dependent: &'a Cell<bool>,
 }
 
 impl<'a> CheckFilter<'a> {
     pub fn check(&self, filter: &'a Filter, depth: isize) -> AnyReport {
+        trace::traced(
+            self.verbosity.trace,
+            || format!("filter {}", trace::describe_filter(filter)),
+            || self.check_untraced(filter, depth),
+        )
+    }
+
+    fn check_untraced(&self, filter: &'a Filter, depth: isize) -> AnyReport {
         if depth <= 0 {
             return recursion_any_report(RecurSrc::CheckFilter);
         }
@@ -35,10 +48,12 @@ impl<'a> CheckFilter<'a> {
         }
     }
 
-    fn filter_set(&self, name: &str, depth: isize) -> AnyReport {
+    fn filter_set(&self, name: &SetName, depth: isize) -> AnyReport {
         let filter_set = match self.dump.filter_sets.get(name) {
             Some(f) => f,
-            None => return self.skip_any_report(|| SkipReason::FilterSetUnrecorded(name.into())),
+            None => {
+                return self.skip_any_report(|| SkipReason::FilterSetUnrecorded(name.to_string()))
+            }
         };
         let mut aggregator = AnyReportAggregator::new();
         for filter in &filter_set.filters {
@@ -47,14 +62,15 @@ impl<'a> CheckFilter<'a> {
         aggregator.to_any()
     }
 
-    fn filter_as_num(&self, num: usize, op: RangeOperator) -> AnyReport {
+    fn filter_as_num(&self, num: Asn, op: RangeOperator) -> AnyReport {
         let routes = match self.dump.as_routes.get(&num) {
             Some(r) => r,
             None => {
+                self.path_dependent.set(true);
                 return match self.compare.goes_through_num(num) {
                     true => self.skip_any_report(|| SkipReason::AsRoutesUnrecorded(num)),
                     false => empty_skip_any_report(),
-                }
+                };
             }
         };
         if match_ips(&self.compare.prefix, routes, op) {
@@ -78,17 +94,30 @@ impl<'a> CheckFilter<'a> {
         }
     }
 
-    fn filter_route_set(&self, name: &str, op: RangeOperator, depth: isize) -> AnyReport {
+    fn filter_route_set<K>(&self, name: &K, op: RangeOperator, depth: isize) -> AnyReport
+    where
+        K: SetKey + ?Sized,
+    {
+        let route_set = self.dump.route_sets.get(name);
+        let name = name.name();
+        if is_any_route_set(name) {
+            return None;
+        }
         if depth <= 0 {
             return recursion_any_report(RecurSrc::FilterRouteSet(name.into()));
         }
-        let route_set = match self.dump.route_sets.get(name) {
+        let route_set = match route_set {
             Some(r) => r,
             None => return self.skip_any_report(|| SkipReason::RouteSetUnrecorded(name.into())),
         };
         let mut aggregator = AnyReportAggregator::new();
         for member in &route_set.members {
-            aggregator.join(self.filter_route_set_member(member, op, depth - 1)?);
+            let report = trace::traced(
+                self.verbosity.trace,
+                || format!("member {member:?}"),
+                || self.filter_route_set_member(member, op, depth - 1),
+            );
+            aggregator.join(report?);
         }
         if aggregator.all_fail {
             self.no_match_any_report(|| MatchProblem::FilterRouteSet(name.into()))
@@ -99,7 +128,7 @@ impl<'a> CheckFilter<'a> {
 
     fn filter_route_set_member(
         &self,
-        member: &RouteSetMember,
+        member: &'a RouteSetMember,
         op: RangeOperator,
         depth: isize,
     ) -> AnyReport {
@@ -116,16 +145,33 @@ impl<'a> CheckFilter<'a> {
                 _ => self.filter_prefixes([prefix]),
             },
             RouteSetMember::NameOp(name, op) => self.filter_route_set(name, *op, depth - 1),
+            RouteSetMember::AsNum(num, member_op) => {
+                self.filter_as_num(*num, member_op_or(*member_op, op))
+            }
+            RouteSetMember::AsSet(name, member_op) => self.filter_as_set(
+                name,
+                member_op_or(*member_op, op),
+                depth - 1,
+                &mut HashSet::with_capacity(16384),
+            ),
         }
     }
 
-    fn filter_as_set(
+    fn filter_as_set<K>(
         &self,
-        name: &'a str,
+        name: &'a K,
         op: RangeOperator,
         depth: isize,
         visited: &mut HashSet<&'a str>,
-    ) -> AnyReport {
+    ) -> AnyReport
+    where
+        K: SetKey + ?Sized,
+    {
+        let as_set_route = self.dump.as_set_routes.get(name);
+        let name = name.name();
+        if is_any_as_set(name) {
+            return None;
+        }
         if visited.contains(&name) {
             return failed_any_report();
         }
@@ -133,7 +179,7 @@ impl<'a> CheckFilter<'a> {
         if depth <= 0 {
             return recursion_any_report(RecurSrc::FilterAsSet(name.into()));
         }
-        let as_set_route = match self.dump.as_set_routes.get(name) {
+        let as_set_route = match as_set_route {
             Some(r) => r,
             None => return self.skip_any_report(|| SkipReason::AsSetRouteUnrecorded(name.into())),
         };
@@ -157,15 +203,22 @@ impl<'a> CheckFilter<'a> {
 
         let mut aggregator = AnyReportAggregator::new();
         for set in &as_set_route.set_members {
-            aggregator.join(self.filter_as_set(set, op, depth - 1, visited)?);
+            let report = trace::traced(
+                self.verbosity.trace,
+                || format!("member as-set {set}{op}"),
+                || self.filter_as_set(set, op, depth - 1, visited),
+            );
+            aggregator.join(report?);
         }
 
-        aggregator.join(self.skip_any_reports(|| {
-            as_set_route
-                .unrecorded_nums
-                .iter()
-                .map(|num| SkipReason::AsRoutesUnrecorded(*num))
-        })?);
+        if !as_set_route.unrecorded_nums.is_empty() {
+            aggregator.join(self.skip_any_reports(|| {
+                as_set_route
+                    .unrecorded_nums
+                    .iter()
+                    .map(|num| SkipReason::AsRoutesUnrecorded(*num))
+            })?);
+        }
 
         if aggregator.all_fail {
             self.no_match_any_report(|| MatchProblem::FilterAsSet(name.into(), op))
@@ -202,15 +255,11 @@ impl<'a> CheckFilter<'a> {
         if depth <= 0 {
             return recursion_any_report(RecurSrc::FilterNot);
         }
-        match self.check(filter, depth) {
-            Some((_errors, true)) => None,
-            Some(report @ (_, false)) => {
-                let mut aggregator: AnyReportAggregator = report.into();
-                aggregator.join(self.no_match_any_report(|| MatchProblem::Filter)?);
-                aggregator.to_any()
-            }
-            None => self.no_match_any_report(|| MatchProblem::Filter),
-        }
+        let inner = CheckFilter {
+            verbosity: self.verbosity.with_all_err(),
+            ..*self
+        };
+        self.not_any_report(inner.check(filter, depth), || MatchProblem::Filter)
     }
 
     /// We skip community checks, but this could be an enhancement.
@@ -224,6 +273,15 @@ impl<'a> CheckFilter<'a> {
     }
 }
 
+/// The range operator of a route set member,
+/// or the one applied to the route set if the member has none.
+pub(crate) fn member_op_or(member_op: RangeOperator, op: RangeOperator) -> RangeOperator {
+    match member_op {
+        RangeOperator::NoOp => op,
+        member_op => member_op,
+    }
+}
+
 impl<'a> VerbosityReport for CheckFilter<'a> {
     fn get_verbosity(&self) -> Verbosity {
         self.verbosity
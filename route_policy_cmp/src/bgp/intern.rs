@@ -0,0 +1,323 @@
+//! Interning of the set names in a [`QueryDump`],
+//! so each name is stored once and looked up by vector indexing.
+use std::{fmt, mem::size_of, sync::Arc};
+
+use hashbrown::HashMap;
+
+use crate::parse::*;
+
+/// Slot of symbols without a value in a [`SetTable`].
+const NO_SLOT: u32 = u32::MAX;
+
+/// Interner of set names, assigning each distinct name a [`Symbol`].
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    names: Vec<Arc<str>>,
+    symbols: HashMap<Arc<str>, Symbol>,
+    memory: NameMemory,
+}
+
+impl Interner {
+    /// The shared text and the [`Symbol`] of `name`, interning it if new.
+    pub fn intern(&mut self, name: &str) -> (Arc<str>, Symbol) {
+        if let Some((shared, symbol)) = self.symbols.get_key_value(name) {
+            return (shared.clone(), *symbol);
+        }
+        let shared: Arc<str> = name.into();
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(shared.clone());
+        self.symbols.insert(shared.clone(), symbol);
+        (shared, symbol)
+    }
+
+    /// Point `name` to the shared text and set its [`Symbol`].
+    pub fn resolve(&mut self, name: &mut SetName) {
+        self.memory.references += 1;
+        self.memory.bytes_before += name_bytes(name);
+        let (shared, symbol) = self.intern(name);
+        *name = SetName::new(shared, symbol);
+    }
+
+    pub fn resolve_filter(&mut self, filter: &mut Filter) {
+        match filter {
+            Filter::FilterSet(name) | Filter::RouteSet(name, _) | Filter::AsSet(name, _) => {
+                self.resolve(name)
+            }
+            Filter::And { left, right } | Filter::Or { left, right } => {
+                self.resolve_filter(left);
+                self.resolve_filter(right);
+            }
+            Filter::Not(filter) | Filter::Group(filter) => self.resolve_filter(filter),
+            Filter::Any
+            | Filter::AddrPrefixSet(_)
+            | Filter::AsNum(..)
+            | Filter::AsPathRE(_)
+            | Filter::Community(_)
+            | Filter::Invalid(_) => {}
+        }
+    }
+
+    pub fn resolve_as_expr(&mut self, as_expr: &mut AsExpr) {
+        match as_expr {
+            AsExpr::PeeringSet(name) => self.resolve(name),
+            AsExpr::And { left, right }
+            | AsExpr::Or { left, right }
+            | AsExpr::Except { left, right } => {
+                self.resolve_as_expr(left);
+                self.resolve_as_expr(right);
+            }
+            AsExpr::Group(as_expr) => self.resolve_as_expr(as_expr),
+            AsExpr::Single(_) => {}
+        }
+    }
+
+    pub fn resolve_entry(&mut self, entry: &mut Entry) {
+        for peering_action in &mut entry.mp_peerings {
+            self.resolve_as_expr(&mut peering_action.mp_peering.remote_as);
+        }
+        self.resolve_filter(&mut entry.mp_filter);
+    }
+
+    pub fn resolve_policy(&mut self, versions: &mut Versions) {
+        for casts in [&mut versions.any, &mut versions.ipv4, &mut versions.ipv6] {
+            for entries in [&mut casts.any, &mut casts.unicast, &mut casts.multicast] {
+                entries
+                    .iter_mut()
+                    .for_each(|entry| self.resolve_entry(entry));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Estimated memory used by the names resolved so far, before and after,
+    /// with the interned texts and the [`Self::names`] shared by
+    /// [`SetTable`]s, but not the lookup map of `self`, dropped once the
+    /// tables are built.
+    /// Add the keys of the tables with [`NameMemory::add_table`].
+    pub fn memory(&self) -> NameMemory {
+        let unique: usize = self.names.iter().map(|name| ARC_HEADER + name.len()).sum();
+        let names = ARC_HEADER + self.names.len() * size_of::<Arc<str>>();
+        NameMemory {
+            unique: self.names.len(),
+            bytes_after: self.memory.references * size_of::<SetName>() + unique + names,
+            ..self.memory
+        }
+    }
+
+    /// The names in [`Symbol`] order, for [`SetTable::new`].
+    pub fn names(&self) -> Arc<[Arc<str>]> {
+        self.names.as_slice().into()
+    }
+}
+
+/// Reference counts before the text of an [`Arc<str>`].
+const ARC_HEADER: usize = 2 * size_of::<usize>();
+
+/// Bytes `name` took as a [`String`] before interning.
+fn name_bytes(name: &str) -> usize {
+    size_of::<String>() + name.len()
+}
+
+/// Estimated memory used by set names, in references and table keys,
+/// from the sizes of the types and the lengths of the names,
+/// ignoring allocator overhead and spare capacity.
+///
+/// Before interning, each reference has its own [`String`] and the tables
+/// are `HashMap<String, T>`s.
+/// After, references share interned texts, looked up through [`SetTable`]s.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NameMemory {
+    pub references: usize,
+    /// Number of distinct names.
+    pub unique: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl NameMemory {
+    /// Add the keys of `table`, without its values.
+    pub fn add_table<T>(&mut self, table: &SetTable<T>) {
+        // One control byte per bucket in `hashbrown` maps.
+        let before: usize = table.keys().map(|name| name_bytes(name) + 1).sum();
+        self.bytes_before += before;
+        self.bytes_after += table.key_bytes();
+    }
+}
+
+impl fmt::Display for NameMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} set name references to {} names and the set tables take about {} bytes, {} before interning",
+            self.references, self.unique, self.bytes_after, self.bytes_before
+        )
+    }
+}
+
+/// A set name to look up in a [`SetTable`].
+pub trait SetKey {
+    fn name(&self) -> &str;
+
+    /// The [`Symbol`] to look up by, if any.
+    fn symbol_in<T>(&self, _table: &SetTable<T>) -> Option<Symbol> {
+        None
+    }
+}
+
+impl SetKey for str {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl SetKey for String {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl SetKey for SetName {
+    fn name(&self) -> &str {
+        self
+    }
+
+    fn symbol_in<T>(&self, table: &SetTable<T>) -> Option<Symbol> {
+        table.symbol_of(self)
+    }
+}
+
+/// Objects keyed by set name, looked up by the [`Symbol`] of a resolved
+/// [`SetName`], or by hashing the name otherwise.
+#[derive(Clone, Debug)]
+pub struct SetTable<T> {
+    /// The interned names, to check a [`SetName`] comes from the same
+    /// [`Interner`] before trusting its [`Symbol`].
+    names: Arc<[Arc<str>]>,
+    /// Index into `entries` for each [`Symbol`], or [`NO_SLOT`].
+    slots: Vec<u32>,
+    entries: Vec<(Arc<str>, T)>,
+    index: HashMap<Arc<str>, u32>,
+}
+
+impl<T> Default for SetTable<T> {
+    fn default() -> Self {
+        Self {
+            names: Arc::new([]),
+            slots: Vec::new(),
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for SetTable<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(name, value)| other.get(name) == Some(value))
+    }
+}
+
+impl<T: Eq> Eq for SetTable<T> {}
+
+impl<T> SetTable<T> {
+    /// Table of `map`, whose keys should be interned in `interner`,
+    /// sharing `names` from [`Interner::names`].
+    pub fn new<I>(interner: &Interner, names: &Arc<[Arc<str>]>, map: I) -> Self
+    where
+        I: IntoIterator<Item = (String, T)>,
+    {
+        let names = names.clone();
+        let mut slots = vec![NO_SLOT; names.len()];
+        let mut entries = Vec::new();
+        let mut index = HashMap::new();
+        for (name, value) in map {
+            let slot = entries.len() as u32;
+            let (shared, symbol) = match interner.symbols.get_key_value(name.as_str()) {
+                Some((shared, symbol)) => (shared.clone(), Some(*symbol)),
+                None => (name.into(), None),
+            };
+            if let Some(symbol) = symbol {
+                slots[symbol.0 as usize] = slot;
+            }
+            index.insert(shared.clone(), slot);
+            entries.push((shared, value));
+        }
+        Self {
+            names,
+            slots,
+            entries,
+            index,
+        }
+    }
+
+    /// Look up `name`, by its [`Symbol`] if it is a [`SetName`] resolved
+    /// by the [`Interner`] of `self`, or by hashing it otherwise.
+    pub fn get<K: SetKey + ?Sized>(&self, name: &K) -> Option<&T> {
+        let slot = match name.symbol_in(self) {
+            Some(symbol) => self.slots[symbol.0 as usize],
+            None => *self.index.get(name.name())?,
+        };
+        (slot != NO_SLOT).then(|| &self.entries[slot as usize].1)
+    }
+
+    /// The [`Symbol`] of `name` in `self`, if `name` is resolved by the
+    /// same [`Interner`].
+    fn symbol_of(&self, name: &SetName) -> Option<Symbol> {
+        let shared = self.names.get(name.symbol.index()?)?;
+        Arc::ptr_eq(shared, name.shared()).then_some(name.symbol)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.entries.iter().map(|(name, value)| (&**name, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| &**name)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Estimated bytes of the keys of `self` and the slots and index to look
+    /// them up, without the values and the shared [`Interner::names`].
+    /// Key texts not interned are counted, interned ones are shared.
+    fn key_bytes(&self) -> usize {
+        let mut interned = vec![false; self.entries.len()];
+        for slot in self.slots.iter().filter(|slot| **slot != NO_SLOT) {
+            interned[*slot as usize] = true;
+        }
+        let unshared: usize = self
+            .entries
+            .iter()
+            .zip(interned)
+            .filter(|(_, interned)| !interned)
+            .map(|((name, _), _)| ARC_HEADER + name.len())
+            .sum();
+        self.slots.len() * size_of::<u32>()
+            + self.entries.len() * size_of::<Arc<str>>()
+            + self.index.len() * (size_of::<(Arc<str>, u32)>() + 1)
+            + unshared
+    }
+}
@@ -0,0 +1,355 @@
+//! Reading of BGP updates archived as JSON, one message per line,
+//! from RIPE RIS Live (<https://ris-live.ripe.net/manual/>) or exabgp,
+//! into [`Line`]s in the same form as the output of `bgpdump -m`,
+//! so they go through the same checks and reports as MRT files.
+//!
+//! Only announcements to unicast prefixes become [`Line`]s;
+//! withdrawals and other messages are skipped.
+use std::{io::BufRead, net::IpAddr, str::FromStr};
+
+use serde_json::Value;
+
+use super::*;
+
+/// Format of JSON update messages.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum JsonFormat {
+    /// RIS Live `ris_message`s, or only their `data`.
+    RisLive,
+    /// exabgp `update` messages from its JSON API encoder.
+    ExaBgp,
+}
+
+impl FromStr for JsonFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "ris-live" | "ris" => Self::RisLive,
+            "exabgp" => Self::ExaBgp,
+            other => bail!("Unknown JSON update format {other}"),
+        })
+    }
+}
+
+impl JsonFormat {
+    /// Routes announced in one JSON `message`.
+    pub fn routes(self, message: &str) -> Result<Vec<JsonRoute>> {
+        match self {
+            Self::RisLive => ris_live_routes(message),
+            Self::ExaBgp => exabgp_routes(message),
+        }
+    }
+}
+
+/// One route announced in a JSON update message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JsonRoute {
+    /// Seconds since the epoch.
+    pub timestamp: u64,
+    pub peer: CollectorPeer,
+    pub prefix: IpNet,
+    pub as_path: Vec<AsPathEntry>,
+    /// `IGP`, `EGP` or `INCOMPLETE`.
+    pub origin: String,
+    pub next_hop: String,
+    /// Communities as `<AS>:<value>`.
+    pub communities: Vec<String>,
+}
+
+impl JsonRoute {
+    /// `self` as a BGP4MP announcement line of `bgpdump -m`.
+    pub fn to_line_dump(&self) -> String {
+        let path: Vec<_> = self.as_path.iter().map(bgpdump_entry).collect();
+        format!(
+            "BGP4MP|{}|A|{}|{}|{}|{}|{}|{}|0|0|{}|NAG||",
+            self.timestamp,
+            self.peer.ip,
+            self.peer.asn,
+            self.prefix,
+            path.join(" "),
+            self.origin,
+            self.next_hop,
+            self.communities.join(" "),
+        )
+    }
+
+    pub fn into_line(self, normalizer: &Normalizer) -> Result<Line> {
+        Line::from_raw_normalizer(self.to_line_dump(), normalizer)
+    }
+}
+
+/// Lazily read the [`Line`]s from the JSON messages in `reader`,
+/// one message per line, normalizing their AS paths using `normalizer`.
+/// Malformed messages become errors that [`skip_bad_lines`] skips,
+/// unlike errors reading `reader`.
+pub fn stream_json_lines<R: BufRead>(
+    reader: R,
+    format: JsonFormat,
+    normalizer: Normalizer,
+) -> impl Iterator<Item = Result<Line>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .flat_map(move |line| {
+            let routes = line
+                .map_err(anyhow::Error::from)
+                .and_then(|line| format.routes(&line));
+            match routes {
+                Ok(routes) => routes
+                    .into_iter()
+                    .map(|route| route.into_line(&normalizer))
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            }
+        })
+}
+
+/// AS path element in JSON: an AS number, or an AS_SET as an array.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum PathElement {
+    Num(u32),
+    Set(Vec<u32>),
+}
+
+fn flat_as_path(path: Vec<PathElement>) -> Vec<AsPathEntry> {
+    path.into_iter()
+        .map(|element| match element {
+            PathElement::Num(num) => AsPathEntry::Seq(Asn(num)),
+            PathElement::Set(nums) => AsPathEntry::Set(nums.into_iter().map(Asn).collect()),
+        })
+        .collect()
+}
+
+fn communities(pairs: Vec<(u32, u32)>) -> Vec<String> {
+    pairs
+        .into_iter()
+        .map(|(num, value)| format!("{num}:{value}"))
+        .collect()
+}
+
+/// AS number as a JSON number or string.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonAsn {
+    Num(u32),
+    Str(String),
+}
+
+impl TryFrom<JsonAsn> for Asn {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonAsn) -> Result<Self> {
+        match value {
+            JsonAsn::Num(num) => Ok(Asn(num)),
+            JsonAsn::Str(s) => s.parse(),
+        }
+    }
+}
+
+/// `data` of a RIS Live `ris_message`.
+#[derive(Clone, Debug, Deserialize)]
+struct RisMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    timestamp: f64,
+    peer: IpAddr,
+    peer_asn: JsonAsn,
+    #[serde(default)]
+    path: Vec<PathElement>,
+    #[serde(default)]
+    community: Vec<(u32, u32)>,
+    #[serde(default)]
+    origin: Option<String>,
+    #[serde(default)]
+    announcements: Vec<RisAnnouncement>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct RisAnnouncement {
+    next_hop: String,
+    prefixes: Vec<IpNet>,
+}
+
+fn ris_live_routes(message: &str) -> Result<Vec<JsonRoute>> {
+    let mut value: Value = serde_json::from_str(message)?;
+    if value.get("data").is_some() {
+        if value["type"] != "ris_message" {
+            return Ok(Vec::new());
+        }
+        value = value["data"].take();
+    }
+    let message: RisMessage = serde_json::from_value(value)?;
+    if message.kind != "UPDATE" {
+        return Ok(Vec::new());
+    }
+    let peer = CollectorPeer {
+        asn: message.peer_asn.try_into()?,
+        ip: message.peer,
+    };
+    let as_path = flat_as_path(message.path);
+    let communities = communities(message.community);
+    let origin = message.origin.unwrap_or_default().to_uppercase();
+    let routes = message
+        .announcements
+        .into_iter()
+        .flat_map(|announcement| {
+            let next_hop = announcement.next_hop;
+            announcement
+                .prefixes
+                .into_iter()
+                .map(move |prefix| (prefix, next_hop.clone()))
+        })
+        .map(|(prefix, next_hop)| JsonRoute {
+            timestamp: message.timestamp as u64,
+            peer,
+            prefix,
+            as_path: as_path.clone(),
+            origin: origin.clone(),
+            next_hop,
+            communities: communities.clone(),
+        })
+        .collect();
+    Ok(routes)
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    time: f64,
+    neighbor: Option<ExaNeighbor>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaNeighbor {
+    address: ExaPeer<IpAddr>,
+    asn: ExaPeer<JsonAsn>,
+    message: Option<ExaBody>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaPeer<T> {
+    peer: T,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaBody {
+    update: Option<ExaUpdate>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaUpdate {
+    #[serde(default)]
+    attribute: ExaAttributes,
+    /// Next hops of each address family, keyed by family, e.g., `ipv4 unicast`.
+    #[serde(default)]
+    announce: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct ExaAttributes {
+    origin: Option<String>,
+    #[serde(rename = "as-path")]
+    as_path: Option<ExaAsPath>,
+    #[serde(default)]
+    community: Vec<(u32, u32)>,
+}
+
+/// exabgp AS path, flat, or in segments keyed by their position.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum ExaAsPath {
+    Flat(Vec<PathElement>),
+    Segments(BTreeMap<String, ExaSegment>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaSegment {
+    element: String,
+    value: Vec<u32>,
+}
+
+impl ExaAsPath {
+    fn entries(self) -> Result<Vec<AsPathEntry>> {
+        let segments = match self {
+            Self::Flat(path) => return Ok(flat_as_path(path)),
+            Self::Segments(segments) => segments,
+        };
+        let mut segments: Vec<_> = segments.into_iter().collect();
+        segments.sort_by_cached_key(|(position, _)| position.parse::<usize>().ok());
+        let mut as_path = Vec::new();
+        for (_, segment) in segments {
+            let nums: Vec<_> = segment.value.into_iter().map(Asn).collect();
+            match segment.element.as_str() {
+                "as-sequence" => as_path.extend(nums.into_iter().map(AsPathEntry::Seq)),
+                "as-set" => as_path.push(AsPathEntry::Set(nums)),
+                "confed-sequence" => as_path.push(AsPathEntry::ConfedSeq(nums)),
+                "confed-set" => as_path.push(AsPathEntry::ConfedSet(nums)),
+                other => bail!("Unknown exabgp AS path segment {other}"),
+            }
+        }
+        Ok(as_path)
+    }
+}
+
+/// NLRI of one next hop, as a list in exabgp 4, or an object keyed by
+/// prefix in exabgp 3.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum ExaNlris {
+    List(Vec<ExaNlri>),
+    Map(BTreeMap<IpNet, Value>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ExaNlri {
+    nlri: IpNet,
+}
+
+fn exabgp_routes(message: &str) -> Result<Vec<JsonRoute>> {
+    let message: ExaMessage = serde_json::from_str(message)?;
+    let Some(neighbor) = message.neighbor.filter(|_| message.kind == "update") else {
+        return Ok(Vec::new());
+    };
+    let Some(update) = neighbor.message.and_then(|body| body.update) else {
+        return Ok(Vec::new());
+    };
+    let peer = CollectorPeer {
+        asn: neighbor.asn.peer.try_into()?,
+        ip: neighbor.address.peer,
+    };
+    let ExaAttributes {
+        origin,
+        as_path,
+        community,
+    } = update.attribute;
+    let as_path = as_path.map(ExaAsPath::entries).transpose()?;
+    let as_path = as_path.unwrap_or_default();
+    let communities = communities(community);
+    let origin = origin.unwrap_or_default().to_uppercase();
+
+    let mut routes = Vec::new();
+    for (family, next_hops) in update.announce {
+        if !family.ends_with(" unicast") {
+            continue;
+        }
+        for (next_hop, nlris) in next_hops {
+            let prefixes: Vec<IpNet> = match serde_json::from_value(nlris)? {
+                ExaNlris::List(nlris) => nlris.into_iter().map(|nlri| nlri.nlri).collect(),
+                ExaNlris::Map(nlris) => nlris.into_keys().collect(),
+            };
+            routes.extend(prefixes.into_iter().map(|prefix| JsonRoute {
+                timestamp: message.time as u64,
+                peer,
+                prefix,
+                as_path: as_path.clone(),
+                origin: origin.clone(),
+                next_hop: next_hop.clone(),
+                communities: communities.clone(),
+            }));
+        }
+    }
+    Ok(routes)
+}
@@ -7,52 +7,112 @@ use ipnet::IpNet;
 use lazy_regex::regex_captures;
 use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+use crate::parse::Asn;
+
+/// Vantage point of a route: the peer of the route collector that sent it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct CollectorPeer {
-    pub asn: usize,
+    pub asn: Asn,
     pub ip: IpAddr,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(from = "SerdeAsPathEntry", into = "SerdeAsPathEntry")]
 pub enum AsPathEntry {
-    Seq(usize),
-    Set(Vec<usize>),
+    Seq(Asn),
+    Set(Vec<Asn>),
+    /// AS_CONFED_SEQUENCE, shown as `(1 2)` by `bgpdump`.
+    /// <https://www.rfc-editor.org/rfc/rfc5065#section-3>.
+    ConfedSeq(Vec<Asn>),
+    /// AS_CONFED_SET, shown as `[1,2]` by `bgpdump`.
+    ConfedSet(Vec<Asn>),
+}
+
+/// Serialized form of [`AsPathEntry`],
+/// tagging confederation segments so they are not read back as AS_SETs.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SerdeAsPathEntry {
+    Seq(Asn),
+    Set(Vec<Asn>),
+    ConfedSeq { confed_seq: Vec<Asn> },
+    ConfedSet { confed_set: Vec<Asn> },
+}
+
+impl From<SerdeAsPathEntry> for AsPathEntry {
+    fn from(entry: SerdeAsPathEntry) -> Self {
+        match entry {
+            SerdeAsPathEntry::Seq(asn) => Self::Seq(asn),
+            SerdeAsPathEntry::Set(set) => Self::Set(set),
+            SerdeAsPathEntry::ConfedSeq { confed_seq } => Self::ConfedSeq(confed_seq),
+            SerdeAsPathEntry::ConfedSet { confed_set } => Self::ConfedSet(confed_set),
+        }
+    }
+}
+
+impl From<AsPathEntry> for SerdeAsPathEntry {
+    fn from(entry: AsPathEntry) -> Self {
+        match entry {
+            AsPathEntry::Seq(asn) => Self::Seq(asn),
+            AsPathEntry::Set(set) => Self::Set(set),
+            AsPathEntry::ConfedSeq(confed_seq) => Self::ConfedSeq { confed_seq },
+            AsPathEntry::ConfedSet(confed_set) => Self::ConfedSet { confed_set },
+        }
+    }
 }
 
 impl AsPathEntry {
-    pub fn contains_num(&self, num: usize) -> bool {
+    pub fn contains_num(&self, num: Asn) -> bool {
         match self {
             AsPathEntry::Seq(n) => num == *n,
-            AsPathEntry::Set(ns) => ns.contains(&num),
+            AsPathEntry::Set(ns) | AsPathEntry::ConfedSeq(ns) | AsPathEntry::ConfedSet(ns) => {
+                ns.contains(&num)
+            }
         }
     }
+
+    /// Whether `self` is an AS_CONFED_SEQUENCE or AS_CONFED_SET.
+    pub fn is_confed(&self) -> bool {
+        matches!(self, AsPathEntry::ConfedSeq(_) | AsPathEntry::ConfedSet(_))
+    }
+}
+
+fn join_nums(nums: &[Asn], separator: &str) -> String {
+    nums.iter()
+        .map(|asn| format!("{asn}"))
+        .collect::<Vec<String>>()
+        .join(separator)
 }
 
 impl Display for AsPathEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AsPathEntry::Seq(asn) => write!(f, "{asn}"),
-            AsPathEntry::Set(sset) => write!(
-                f,
-                "{}",
-                sset.iter()
-                    .map(|asn| format!("{asn}"))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ),
+            AsPathEntry::Set(sset) => write!(f, "{}", join_nums(sset, ",")),
+            AsPathEntry::ConfedSeq(confed_seq) => write!(f, "({})", join_nums(confed_seq, " ")),
+            AsPathEntry::ConfedSet(confed_set) => write!(f, "[{}]", join_nums(confed_set, ",")),
         }
     }
 }
 
+/// `entry` as shown by `bgpdump`, with AS_SETs in braces.
+pub fn bgpdump_entry(entry: &AsPathEntry) -> String {
+    match entry {
+        AsPathEntry::Set(nums) => format!("{{{}}}", join_nums(nums, ",")),
+        entry => entry.to_string(),
+    }
+}
+
 impl IntoIterator for AsPathEntry {
-    type Item = usize;
+    type Item = Asn;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
         match self {
             AsPathEntry::Seq(asn) => vec![asn].into_iter(),
-            AsPathEntry::Set(sset) => sset.into_iter(),
+            AsPathEntry::Set(sset)
+            | AsPathEntry::ConfedSeq(sset)
+            | AsPathEntry::ConfedSet(sset) => sset.into_iter(),
         }
     }
 }
@@ -65,22 +125,62 @@ impl FromStr for AsPathEntry {
         if let Ok(n) = asn {
             return Ok(AsPathEntry::Seq(n));
         }
+        if let Some((_, cap)) = regex_captures!(r"^\[(\d+(?:,\d+)*)\]$", s) {
+            let confed_set = cap.split(',').map(str::parse).collect::<Result<_>>()?;
+            return Ok(AsPathEntry::ConfedSet(confed_set));
+        }
         let (_, cap) =
             regex_captures!(r"\{(\d+(?:,\d+)*)\}", s).context("as-path-entry-no-match")?;
-        // Regex should guarantee the unwraps never fail:
-        let asset = cap.split(',').map(|n| n.parse().unwrap()).collect();
+        let asset = cap.split(',').map(str::parse).collect::<Result<_>>()?;
         Ok(AsPathEntry::Set(asset))
     }
 }
 
+/// Parse the AS path field of a table dump, where AS_CONFED_SEQUENCEs
+/// span multiple space-separated tokens, e.g., `1 (2 3) {4,5}`.
+pub fn parse_as_path(field: &str) -> Result<Vec<AsPathEntry>> {
+    let mut as_path = Vec::new();
+    let mut tokens = field.split(' ');
+    while let Some(token) = tokens.next() {
+        let Some(mut token) = token.strip_prefix('(') else {
+            as_path.push(token.parse()?);
+            continue;
+        };
+        let mut confed_seq = Vec::new();
+        loop {
+            let (num, end) = match token.strip_suffix(')') {
+                Some(num) => (num, true),
+                None => (token, false),
+            };
+            confed_seq.push(num.parse().context("as-path-entry-no-match")?);
+            if end {
+                break;
+            }
+            token = tokens.next().context("as-path-entry-no-match")?;
+        }
+        as_path.push(AsPathEntry::ConfedSeq(confed_seq));
+    }
+    Ok(as_path)
+}
+
+/// Seconds since the epoch when the route in a `bgpdump -m` line was
+/// dumped or announced, from its second field.
+pub fn parse_timestamp(line: &str) -> Result<u64> {
+    let timestamp = line.split('|').nth(1).context("no-timestamp")?;
+    timestamp.parse().context("bad-timestamp")
+}
+
 /// Return (IP prefix, AS-path, BGP collector, communities).
 pub fn parse_table_dump(line: &str) -> Result<(IpNet, Vec<AsPathEntry>, CollectorPeer, Vec<&str>)> {
     // TABLE_DUMP2|1619481601|B|94.156.252.18|34224|6.132.0.0/14|34224 6939 8003|IGP|94.156.252.18|0|0|34224:333 34224:334 34224:2040|NAG|||
     // TABLE_DUMP2|1661040000|B|94.177.122.251|58057|2001:410::/32|58057 174 1299 1299 1299 2603 2603 2603 6509 {271,7860,8111,10972,53904}|IGP|::ffff:94.177.122.251|0|0|174:21100 58057:65010 174:22005|AG|6509 205.189.32.101|
-    if !(line.starts_with("TABLE_DUMP2")) {
-        bail!("{line} does not start with TABLE_DUMP2");
-    }
+    // BGP4MP|1687212000|A|192.0.2.1|64496|10.0.0.0/8|64496 64497|IGP|192.0.2.1|0|0|64496:1|NAG||
     let fields: Vec<_> = line.split('|').collect();
+    match fields[..] {
+        ["TABLE_DUMP2", ..] => {}
+        [kind, _, "A", ..] if kind.starts_with("BGP4MP") => {}
+        _ => bail!("{line} is neither a TABLE_DUMP2 route nor a BGP4MP announcement"),
+    }
     let n_fields = fields.len();
     if n_fields < 15 {
         bail!("{line} breaks down to {n_fields} fields instead of 15");
@@ -91,10 +191,7 @@ pub fn parse_table_dump(line: &str) -> Result<(IpNet, Vec<AsPathEntry>, Collecto
     };
     let prefix = fields[5].parse().context("bad-prefix")?;
 
-    let aspath: Vec<AsPathEntry> = fields[6]
-        .split(' ')
-        .map(|e| e.parse())
-        .collect::<Result<_>>()?;
+    let aspath = parse_as_path(fields[6])?;
 
     let communities = fields[11].split_whitespace().collect();
     Ok((prefix, aspath, vp, communities))
@@ -0,0 +1,196 @@
+//! Memoisation of AS pair checks across routes.
+//!
+//! Routes through the same AS pair are checked against the same [`Entry`]s.
+//! Whether the peerings of an entry match the AS on the other side of the
+//! pair does not depend on the route, and whether its filter matches mostly
+//! depends only on the prefix, so [`PairCache`] keeps both.
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+};
+
+use dashmap::DashMap;
+
+use crate::parse::*;
+
+use super::{cmp::RECURSION_LIMIT, *};
+
+/// Default of [`PairCache::filter_limit`].
+pub const FILTER_CACHE_LIMIT: usize = 1 << 22;
+
+/// Identity of an [`Entry`] by its address in the [`QueryDump`] of the
+/// [`PairCache`], which the cache borrows so the address stays valid.
+type EntryId = usize;
+
+fn entry_id(entry: &Entry) -> EntryId {
+    entry as *const Entry as EntryId
+}
+
+/// Peering results of an [`Entry`] of the AS pair `(from, to)`,
+/// from the exports of `from` or the imports of `to`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct PeeringKey {
+    from: Asn,
+    to: Asn,
+    import: bool,
+    entry: EntryId,
+}
+
+/// Concurrent cache of the peering part of the checks of each AS pair and
+/// direction, and of the filter part of the checks of each [`Entry`] and
+/// prefix, shared by threads checking routes.
+///
+/// Entries are keyed by their address, so a [`PairCache`] borrows the
+/// [`QueryDump`] it is created for, and checks through it are always against
+/// that dump.
+/// Only checks with the [`Verbosity`] it is created with use it,
+/// see [`Self::accepts`].
+#[derive(Debug)]
+pub struct PairCache<'a> {
+    dump: &'a QueryDump,
+    verbosity: Verbosity,
+    peerings: DashMap<PeeringKey, AllReport>,
+    filters: DashMap<(EntryId, IpNet), AllReport>,
+    /// Number of entries in `filters`, tracked separately because
+    /// [`DashMap::len`] locks every shard.
+    n_filters: AtomicUsize,
+    /// Filter results are no longer cached once there are this many.
+    pub filter_limit: usize,
+    peering_hits: AtomicU64,
+    peering_misses: AtomicU64,
+    filter_hits: AtomicU64,
+    filter_misses: AtomicU64,
+}
+
+impl<'a> PairCache<'a> {
+    /// Cache for checks against `dump` with `verbosity`.
+    pub fn new(dump: &'a QueryDump, verbosity: Verbosity) -> Self {
+        Self {
+            dump,
+            verbosity,
+            peerings: DashMap::new(),
+            filters: DashMap::new(),
+            n_filters: AtomicUsize::new(0),
+            filter_limit: FILTER_CACHE_LIMIT,
+            peering_hits: AtomicU64::new(0),
+            peering_misses: AtomicU64::new(0),
+            filter_hits: AtomicU64::new(0),
+            filter_misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether checks of `compare` can use `self`.
+    /// Traced checks cannot, because cached results have no trace.
+    pub fn accepts(&self, compare: &Compare) -> bool {
+        compare.verbosity == self.verbosity
+            && !compare.verbosity.trace
+            && compare.recursion_limit == RECURSION_LIMIT
+    }
+
+    /// The [`QueryDump`] checks through `self` are against.
+    pub fn dump(&self) -> &'a QueryDump {
+        self.dump
+    }
+
+    /// Hit and miss counts so far.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            peering_hits: self.peering_hits.load(Relaxed),
+            peering_misses: self.peering_misses.load(Relaxed),
+            filter_hits: self.filter_hits.load(Relaxed),
+            filter_misses: self.filter_misses.load(Relaxed),
+        }
+    }
+
+    fn peering<F>(&self, key: PeeringKey, check: F) -> AllReport
+    where
+        F: FnOnce() -> AllReport,
+    {
+        if let Some(report) = self.peerings.get(&key) {
+            self.peering_hits.fetch_add(1, Relaxed);
+            return report.clone();
+        }
+        self.peering_misses.fetch_add(1, Relaxed);
+        let report = check();
+        self.peerings.insert(key, report.clone());
+        report
+    }
+
+    /// Filter result of `entry` on `prefix`, from `check` if not cached.
+    /// `check` also returns whether the result depends on the AS path,
+    /// in which case it is not cached.
+    fn filter<F>(&self, entry: &Entry, prefix: IpNet, check: F) -> AllReport
+    where
+        F: FnOnce() -> (AllReport, bool),
+    {
+        let key = (entry_id(entry), prefix);
+        if let Some(report) = self.filters.get(&key) {
+            self.filter_hits.fetch_add(1, Relaxed);
+            return report.clone();
+        }
+        self.filter_misses.fetch_add(1, Relaxed);
+        let (report, path_dependent) = check();
+        if !path_dependent
+            && self.n_filters.load(Relaxed) < self.filter_limit
+            && self.filters.insert(key, report.clone()).is_none()
+        {
+            self.n_filters.fetch_add(1, Relaxed);
+        }
+        report
+    }
+}
+
+/// Hit and miss counts of a [`PairCache`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub peering_hits: u64,
+    pub peering_misses: u64,
+    pub filter_hits: u64,
+    pub filter_misses: u64,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peering {} hits {} misses, filter {} hits {} misses",
+            self.peering_hits, self.peering_misses, self.filter_hits, self.filter_misses
+        )
+    }
+}
+
+/// The [`PairCache`] used while checking the policy of `asn`,
+/// its imports if `import`, otherwise its exports.
+#[derive(Clone, Copy)]
+pub(crate) struct Memo<'a> {
+    pub cache: &'a PairCache<'a>,
+    pub asn: Asn,
+    pub import: bool,
+}
+
+impl Memo<'_> {
+    /// Peering result of `entry` with `accept_num` on the other side.
+    pub fn peering<F>(self, entry: &Entry, accept_num: Asn, check: F) -> AllReport
+    where
+        F: FnOnce() -> AllReport,
+    {
+        let (from, to) = match self.import {
+            true => (accept_num, self.asn),
+            false => (self.asn, accept_num),
+        };
+        let key = PeeringKey {
+            from,
+            to,
+            import: self.import,
+            entry: entry_id(entry),
+        };
+        self.cache.peering(key, check)
+    }
+
+    pub fn filter<F>(self, entry: &Entry, prefix: IpNet, check: F) -> AllReport
+    where
+        F: FnOnce() -> (AllReport, bool),
+    {
+        self.cache.filter(entry, prefix, check)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::parse::Asn;
+
+use super::*;
+
+use AsPathEntry::*;
+
+/// Options for normalizing AS paths before checking them.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Normalizer {
+    /// Remove consecutive duplicate entries caused by AS path prepending.
+    pub remove_prepending: bool,
+    /// Remove private ASNs, see [`Asn::is_private`].
+    pub remove_private: bool,
+    /// Remove reserved ASNs and [`AS_TRANS`](crate::parse::AS_TRANS), see [`Asn::is_reserved`].
+    pub remove_reserved: bool,
+    /// Remove AS_CONFED_SEQUENCE and AS_CONFED_SET segments,
+    /// which should not have left the confederation.
+    /// <https://www.rfc-editor.org/rfc/rfc5065#section-5.3>.
+    pub remove_confed: bool,
+    /// Replace AS_SETs with a single member with that member.
+    pub expand_single_sets: bool,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self {
+            remove_prepending: true,
+            remove_private: false,
+            remove_reserved: false,
+            remove_confed: false,
+            expand_single_sets: true,
+        }
+    }
+}
+
+impl Normalizer {
+    /// Apply all normalizations enabled to `as_path`.
+    /// Return the normalized AS path and the normalizations applied.
+    pub fn normalize(&self, as_path: Vec<AsPathEntry>) -> (Vec<AsPathEntry>, Vec<Normalization>) {
+        let mut normalizations = Vec::new();
+        let mut result: Vec<AsPathEntry> = Vec::with_capacity(as_path.len());
+        for entry in as_path {
+            if let Some(entry) = self.normalize_entry(entry, &mut normalizations) {
+                match (self.remove_prepending, result.last()) {
+                    (true, Some(last)) if *last == entry => match normalizations.last_mut() {
+                        Some(Normalization::Prepending { entry: e, removed }) if *e == entry => {
+                            *removed += 1
+                        }
+                        _ => normalizations.push(Normalization::Prepending { entry, removed: 1 }),
+                    },
+                    _ => result.push(entry),
+                }
+            }
+        }
+        result.shrink_to_fit();
+        (result, normalizations)
+    }
+
+    fn normalize_entry(
+        &self,
+        entry: AsPathEntry,
+        normalizations: &mut Vec<Normalization>,
+    ) -> Option<AsPathEntry> {
+        match entry {
+            Seq(asn) => self.keep_asn(asn, normalizations).then_some(Seq(asn)),
+            Set(set) => {
+                let mut set: Vec<_> = set
+                    .into_iter()
+                    .filter(|asn| self.keep_asn(*asn, normalizations))
+                    .collect();
+                match (set.len(), self.expand_single_sets) {
+                    (0, _) => None,
+                    (1, true) => {
+                        let asn = set.pop().expect("`set` has one member");
+                        normalizations.push(Normalization::SingleSet(asn));
+                        Some(Seq(asn))
+                    }
+                    _ => Some(Set(set)),
+                }
+            }
+            ConfedSeq(confed_seq) if self.remove_confed => {
+                normalizations.push(Normalization::ConfedSeq(confed_seq));
+                None
+            }
+            ConfedSet(confed_set) if self.remove_confed => {
+                normalizations.push(Normalization::ConfedSet(confed_set));
+                None
+            }
+            entry => Some(entry),
+        }
+    }
+
+    fn keep_asn(&self, asn: Asn, normalizations: &mut Vec<Normalization>) -> bool {
+        let normalization = if self.remove_private && asn.is_private() {
+            Normalization::PrivateAsn(asn)
+        } else if self.remove_reserved && asn.is_as_trans() {
+            Normalization::AsTrans
+        } else if self.remove_reserved && asn.is_reserved() {
+            Normalization::ReservedAsn(asn)
+        } else {
+            return true;
+        };
+        normalizations.push(normalization);
+        false
+    }
+}
+
+/// A change [`Normalizer`] made to an AS path.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Normalization {
+    /// `removed` consecutive duplicates of `entry` were removed.
+    Prepending { entry: AsPathEntry, removed: usize },
+    /// A private ASN was removed.
+    PrivateAsn(Asn),
+    /// A reserved ASN was removed.
+    ReservedAsn(Asn),
+    /// [`AS_TRANS`](crate::parse::AS_TRANS) was removed.
+    AsTrans,
+    /// An AS_CONFED_SEQUENCE was removed.
+    ConfedSeq(Vec<Asn>),
+    /// An AS_CONFED_SET was removed.
+    ConfedSet(Vec<Asn>),
+    /// An AS_SET with a single member was replaced with the member.
+    SingleSet(Asn),
+}
@@ -5,7 +5,7 @@ use super::*;
 pub struct CheckPeering<'a> {
     pub dump: &'a QueryDump,
     pub compare: &'a Compare,
-    pub accept_num: usize,
+    pub accept_num: Asn,
     pub verbosity: Verbosity,
 }
 
@@ -25,6 +25,14 @@ impl<'a> CheckPeering<'a> {
     }
 
     fn check_remote_as(&self, remote_as: &AsExpr, depth: isize) -> AnyReport {
+        trace::traced(
+            self.verbosity.trace,
+            || format!("peering {}", trace::describe_as_expr(remote_as)),
+            || self.check_remote_as_untraced(remote_as, depth),
+        )
+    }
+
+    fn check_remote_as_untraced(&self, remote_as: &AsExpr, depth: isize) -> AnyReport {
         if depth <= 0 {
             return recursion_any_report(RecurSrc::CheckRemoteAs);
         }
@@ -53,7 +61,7 @@ impl<'a> CheckPeering<'a> {
         }
     }
 
-    fn check_remote_as_num(&self, num: usize) -> AnyReport {
+    fn check_remote_as_num(&self, num: Asn) -> AnyReport {
         if self.accept_num == num {
             None
         } else {
@@ -67,6 +75,9 @@ impl<'a> CheckPeering<'a> {
         depth: isize,
         visited: &mut HashSet<&'a str>,
     ) -> AnyReport {
+        if is_any_as_set(name) {
+            return None;
+        }
         if visited.contains(&name) {
             return failed_any_report();
         }
@@ -97,7 +108,12 @@ impl<'a> CheckPeering<'a> {
 
         let mut aggregator = AnyReportAggregator::new();
         for set in &as_set.set_members {
-            aggregator.join(self.check_remote_as_set(set, depth - 1, visited)?);
+            let report = trace::traced(
+                self.verbosity.trace,
+                || format!("member as-set {set}"),
+                || self.check_remote_as_set(set, depth - 1, visited),
+            );
+            aggregator.join(report?);
         }
         if aggregator.all_fail {
             self.no_match_any_report(|| MatchProblem::RemoteAsSet(name.into()))
@@ -105,13 +121,15 @@ impl<'a> CheckPeering<'a> {
             aggregator.to_any()
         }
     }
-    fn check_remote_peering_set(&self, name: &str, depth: isize) -> AnyReport {
+    fn check_remote_peering_set(&self, name: &SetName, depth: isize) -> AnyReport {
         if depth <= 0 {
-            return recursion_any_report(RecurSrc::RemotePeeringSet(name.into()));
+            return recursion_any_report(RecurSrc::RemotePeeringSet(name.to_string()));
         }
         let peering_set = match self.dump.peering_sets.get(name) {
             Some(r) => r,
-            None => return self.skip_any_report(|| SkipReason::PeeringSetUnrecorded(name.into())),
+            None => {
+                return self.skip_any_report(|| SkipReason::PeeringSetUnrecorded(name.to_string()))
+            }
         };
         let mut aggregator = AnyReportAggregator::new();
         for peering in &peering_set.peerings {
@@ -144,15 +162,16 @@ impl<'a> CheckPeering<'a> {
             return recursion_all_report(RecurSrc::PeeringExcept);
         }
         let left_report = self.check_remote_as(left, depth - 1).to_all()?;
-        let right_report = match self.check_remote_as(right, depth) {
-            Some((_, true)) => Ok(None),
-            report @ Some((_, false)) => report
-                .to_all()?
-                .join(self.skip_all_report(|| SkipReason::SkippedExceptPeeringResult)?)
-                .to_all(),
-            None => self.no_match_all_report(|| MatchProblem::ExceptPeeringRightMatch),
+        let inner = CheckPeering {
+            verbosity: self.verbosity.with_all_err(),
+            ..*self
         };
-        left_report.join(right_report?).to_all()
+        let right_report = self
+            .not_any_report(inner.check_remote_as(right, depth), || {
+                MatchProblem::ExceptPeeringRightMatch
+            })
+            .to_all()?;
+        left_report.join(right_report).to_all()
     }
 }
 
@@ -1,4 +1,4 @@
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 use crate::parse::*;
 
@@ -9,8 +9,8 @@ use super::*;
 pub struct AsSetRoute {
     /// Should always be sorted.
     pub routes: Vec<IpNet>,
-    pub unrecorded_nums: Vec<usize>,
-    pub set_members: Vec<String>,
+    pub unrecorded_nums: Vec<Asn>,
+    pub set_members: Vec<SetName>,
 }
 
 impl AsSetRoute {
@@ -27,7 +27,7 @@ impl AsSetRoute {
 
     /// Fill in routes for the AS with `as_set` with routes in `as_routes`.
     /// The process is done only once, and the result [`AsSetRoute`] is cleaned.
-    pub fn from_as_set(as_set: &AsSet, as_routes: &BTreeMap<usize, Vec<IpNet>>) -> Self {
+    pub fn from_as_set(as_set: &AsSet, as_routes: &BTreeMap<Asn, Vec<IpNet>>) -> Self {
         let mut routes = Vec::with_capacity(as_set.members.len() << 2);
         let mut unrecorded_nums = Vec::new();
         for member in &as_set.members {
@@ -39,7 +39,7 @@ impl AsSetRoute {
         let mut result = Self {
             routes,
             unrecorded_nums,
-            set_members: as_set.set_members.clone(),
+            set_members: as_set.set_members.iter().map(SetName::from).collect(),
         };
         result.clean_up();
         result
@@ -47,28 +47,33 @@ impl AsSetRoute {
 }
 
 /// Cleaned RPSL dump ready for query.
+/// Set names referenced in it are interned, see [`SetName`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct QueryDump {
-    pub aut_nums: HashMap<usize, AutNum>,
-    pub as_sets: HashMap<String, AsSet>,
-    pub route_sets: HashMap<String, RouteSet>,
-    pub peering_sets: HashMap<String, PeeringSet>,
-    pub filter_sets: HashMap<String, FilterSet>,
+    pub aut_nums: HashMap<Asn, AutNum>,
+    pub as_sets: SetTable<AsSet>,
+    pub route_sets: SetTable<RouteSet>,
+    pub peering_sets: SetTable<PeeringSet>,
+    pub filter_sets: SetTable<FilterSet>,
     /// Each value should always be sorted.
-    pub as_routes: HashMap<usize, Vec<IpNet>>,
+    pub as_routes: HashMap<Asn, Vec<IpNet>>,
     /// Each value should always be sorted.
-    pub as_set_routes: HashMap<String, AsSetRoute>,
+    pub as_set_routes: SetTable<AsSetRoute>,
+    /// Estimated memory used by the set names, in references and in the keys
+    /// of the set tables, see [`NameMemory`].
+    pub name_memory: NameMemory,
 }
 
 impl QueryDump {
-    /// Clean `dump` and use it to create a [`QueryDump`].
+    /// Clean `dump` and use it to create a [`QueryDump`],
+    /// interning the names of the sets and resolving references to them.
     pub fn from_dump(dump: Dump) -> Self {
         let Dump {
-            aut_nums,
+            mut aut_nums,
             as_sets,
             route_sets,
-            peering_sets,
-            filter_sets,
+            mut peering_sets,
+            mut filter_sets,
             mut as_routes,
         } = dump;
         as_routes.par_iter_mut().for_each(|(_, routes)| {
@@ -80,23 +85,107 @@ impl QueryDump {
             .par_iter()
             .map(|(name, set)| (name.clone(), AsSetRoute::from_as_set(set, &as_routes)))
             .collect();
-        let as_set_routes = flatten_as_set_routes(&as_set_routes);
-        let as_set_routes = HashMap::from_iter(as_set_routes);
-        let aut_nums = HashMap::from_iter(aut_nums);
-        let as_sets = HashMap::from_iter(as_sets);
-        let route_sets = HashMap::from_iter(route_sets);
-        let peering_sets = HashMap::from_iter(peering_sets);
-        let filter_sets = HashMap::from_iter(filter_sets);
-        let as_routes = HashMap::from_iter(as_routes);
-        Self {
-            aut_nums,
-            as_sets,
-            route_sets,
-            peering_sets,
-            filter_sets,
-            as_routes,
-            as_set_routes,
+        let mut as_set_routes = flatten_as_set_routes(&as_set_routes);
+
+        let mut interner = Interner::default();
+        for name in as_sets
+            .keys()
+            .chain(route_sets.keys())
+            .chain(peering_sets.keys())
+            .chain(filter_sets.keys())
+        {
+            interner.intern(name);
+        }
+        for aut_num in aut_nums.values_mut() {
+            interner.resolve_policy(&mut aut_num.imports);
+            interner.resolve_policy(&mut aut_num.exports);
         }
+        for peering_set in peering_sets.values_mut() {
+            for peering in &mut peering_set.peerings {
+                interner.resolve_as_expr(&mut peering.remote_as);
+            }
+        }
+        for filter_set in filter_sets.values_mut() {
+            for filter in &mut filter_set.filters {
+                interner.resolve_filter(filter);
+            }
+        }
+        for as_set_route in as_set_routes.values_mut() {
+            for member in &mut as_set_route.set_members {
+                interner.resolve(member);
+            }
+        }
+        let names = interner.names();
+        let mut query = Self {
+            aut_nums: HashMap::from_iter(aut_nums),
+            as_sets: SetTable::new(&interner, &names, as_sets),
+            route_sets: SetTable::new(&interner, &names, route_sets),
+            peering_sets: SetTable::new(&interner, &names, peering_sets),
+            filter_sets: SetTable::new(&interner, &names, filter_sets),
+            as_routes: HashMap::from_iter(as_routes),
+            as_set_routes: SetTable::new(&interner, &names, as_set_routes),
+            name_memory: interner.memory(),
+        };
+        let memory = &mut query.name_memory;
+        memory.add_table(&query.as_sets);
+        memory.add_table(&query.route_sets);
+        memory.add_table(&query.peering_sets);
+        memory.add_table(&query.filter_sets);
+        memory.add_table(&query.as_set_routes);
+        debug!("{memory}.");
+        query
+    }
+}
+
+impl QueryDump {
+    /// All AS numbers in as-set `name` and its member sets, recursively,
+    /// sorted, and the member sets that are not recorded.
+    /// `None` if `name` itself is not recorded.
+    pub fn expand_as_set(&self, name: &str) -> Option<(Vec<Asn>, Vec<String>)> {
+        self.as_sets.get(name)?;
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![name];
+        let (mut nums, mut unrecorded) = (Vec::new(), Vec::new());
+        while let Some(name) = to_visit.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            match self.as_sets.get(name) {
+                Some(as_set) => {
+                    nums.extend(&as_set.members);
+                    to_visit.extend(as_set.set_members.iter().map(String::as_str));
+                }
+                None => unrecorded.push(name.to_owned()),
+            }
+        }
+        nums.sort_unstable();
+        nums.dedup();
+        unrecorded.sort_unstable();
+        Some((nums, unrecorded))
+    }
+
+    /// Names of the as-sets that directly contain `num`, sorted.
+    pub fn as_sets_containing_num(&self, num: Asn) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .as_sets
+            .iter()
+            .filter(|(_, as_set)| as_set.members.binary_search(&num).is_ok())
+            .map(|(name, _)| name)
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Names of the as-sets that directly contain the set `member`, sorted.
+    pub fn as_sets_containing_set(&self, member: &str) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .as_sets
+            .iter()
+            .filter(|(_, as_set)| as_set.set_members.iter().any(|m| m == member))
+            .map(|(name, _)| name)
+            .collect();
+        names.sort_unstable();
+        names
     }
 }
 
@@ -107,7 +196,7 @@ fn flatten_as_set_routes(
     result.par_iter_mut().for_each(|(_, v)| {
         let members = mem::take(&mut v.set_members);
         for member in members {
-            match as_set_routes.get(&member) {
+            match as_set_routes.get(member.as_str()) {
                 Some(as_set_route) => {
                     v.routes.extend(&as_set_route.routes);
                     v.unrecorded_nums.extend(&as_set_route.unrecorded_nums);
@@ -0,0 +1,244 @@
+//! Per-route records of the reports on [`Line`]s,
+//! written in batches as JSON Lines, CSV or Parquet.
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use polars::{
+    io::parquet::BatchedWriter,
+    prelude::{CsvWriter, DataFrame, NamedFrom, ParquetWriter, PolarsResult, SerWriter, Series},
+};
+
+use super::*;
+
+/// Overall result of the RPSL checks on a route.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Summary {
+    /// No check is bad or neutral.
+    Good,
+    /// Some checks are neutral, none is bad.
+    Neutral,
+    /// Some checks are bad.
+    Bad,
+}
+
+impl Summary {
+    pub fn of(reports: &[Report]) -> Self {
+        if reports.iter().any(Report::is_bad_rpsl) {
+            Self::Bad
+        } else if reports.iter().any(Report::is_neutral) {
+            Self::Neutral
+        } else {
+            Self::Good
+        }
+    }
+}
+
+/// One route and the reports generated on it.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReportRecord {
+    pub prefix: IpNet,
+    /// AS path as shown by `bgpdump`, before normalization.
+    pub path: String,
+    pub collector_asn: Asn,
+    pub collector_ip: String,
+    /// Seconds since the epoch when the route was dumped or announced.
+    pub timestamp: u64,
+    /// Communities as `<AS>:<value>`, not decoded from BMP.
+    pub communities: Vec<String>,
+    pub summary: Summary,
+    pub rpki: Option<RpkiState>,
+    pub reports: Vec<Report>,
+    pub leaks: Option<Vec<Report>>,
+    pub aspa: Option<Vec<Report>>,
+}
+
+impl ReportRecord {
+    /// Record `line`, after [`Line::check`] is called on it.
+    pub fn from_line(line: &Line) -> Result<Self> {
+        let (prefix, as_path, collector, _) = parse_table_dump(&line.raw)?;
+        let reports = line.report.clone().unwrap_or_default();
+        Ok(Self {
+            timestamp: line.timestamp,
+            communities: line.communities.clone(),
+            rpki: line.rpki,
+            leaks: line.leaks.clone(),
+            aspa: line.aspa.clone(),
+            ..Self::new(prefix, &as_path, collector, reports)
+        })
+    }
+
+    /// Record `reports` on the route to `prefix` via `as_path`,
+    /// received from `collector`, without timestamp, communities,
+    /// or RPKI, leak or ASPA checks.
+    pub fn new(
+        prefix: IpNet,
+        as_path: &[AsPathEntry],
+        collector: CollectorPeer,
+        reports: Vec<Report>,
+    ) -> Self {
+        let path: Vec<_> = as_path.iter().map(bgpdump_entry).collect();
+        Self {
+            prefix,
+            path: path.join(" "),
+            collector_asn: collector.asn,
+            collector_ip: collector.ip.to_string(),
+            timestamp: 0,
+            communities: Vec::new(),
+            summary: Summary::of(&reports),
+            rpki: None,
+            reports,
+            leaks: None,
+            aspa: None,
+        }
+    }
+}
+
+/// Output format of [`RecordWriter`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RecordFormat {
+    JsonLines,
+    Csv,
+    Parquet,
+}
+
+impl RecordFormat {
+    /// Guess the format from the extension of `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) => extension.parse(),
+            None => bail!("{} has no extension to tell the format", path.display()),
+        }
+    }
+}
+
+impl FromStr for RecordFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "jsonl" | "json" => Self::JsonLines,
+            "csv" => Self::Csv,
+            "parquet" => Self::Parquet,
+            other => bail!("Unknown record format {other}"),
+        })
+    }
+}
+
+/// Writer of [`ReportRecord`]s, one batch at a time,
+/// so only the current batch needs to be in memory.
+///
+/// In CSV and Parquet, `communities` are joined by spaces as in `bgpdump`,
+/// and `reports`, `leaks` and `aspa` are serialized as JSON strings.
+pub enum RecordWriter {
+    JsonLines(BufWriter<File>),
+    Csv { file: BufWriter<File>, header: bool },
+    Parquet(Box<BatchedWriter<File>>),
+}
+
+impl RecordWriter {
+    pub fn create<P: AsRef<Path>>(path: P, format: RecordFormat) -> Result<Self> {
+        let file = File::create(path)?;
+        Ok(match format {
+            RecordFormat::JsonLines => Self::JsonLines(BufWriter::new(file)),
+            RecordFormat::Csv => Self::Csv {
+                file: BufWriter::new(file),
+                header: true,
+            },
+            RecordFormat::Parquet => {
+                let schema = records_df(&[])?.schema();
+                Self::Parquet(Box::new(ParquetWriter::new(file).batched(&schema)?))
+            }
+        })
+    }
+
+    pub fn write_batch(&mut self, records: &[ReportRecord]) -> Result<()> {
+        match self {
+            Self::JsonLines(file) => {
+                for record in records {
+                    serde_json::to_writer(&mut *file, record)?;
+                    writeln!(file)?;
+                }
+            }
+            Self::Csv { file, header } => {
+                CsvWriter::new(file)
+                    .has_header(mem::take(header))
+                    .finish(&mut records_df(records)?)?;
+            }
+            Self::Parquet(writer) => writer.write_batch(&records_df(records)?)?,
+        }
+        Ok(())
+    }
+
+    /// Flush the records written so far, for readers following the file.
+    /// Parquet files are only readable after [`RecordWriter::finish`].
+    pub fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::JsonLines(file) | Self::Csv { file, .. } => file.flush()?,
+            Self::Parquet(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Flush all the records written.
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Self::JsonLines(mut file) | Self::Csv { mut file, .. } => file.flush()?,
+            Self::Parquet(mut writer) => _ = writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+fn records_df(records: &[ReportRecord]) -> PolarsResult<DataFrame> {
+    let to_json =
+        |reports: &Vec<Report>| serde_json::to_string(reports).expect("Reports serialize");
+    let column = |name: &str, f: &dyn Fn(&ReportRecord) -> String| {
+        Series::new(name, records.iter().map(f).collect::<Vec<_>>())
+    };
+    DataFrame::new(vec![
+        column("prefix", &|r| r.prefix.to_string()),
+        column("path", &|r| r.path.clone()),
+        Series::new(
+            "collector_asn",
+            records
+                .iter()
+                .map(|r| r.collector_asn.0)
+                .collect::<Vec<_>>(),
+        ),
+        column("collector_ip", &|r| r.collector_ip.clone()),
+        Series::new(
+            "timestamp",
+            records.iter().map(|r| r.timestamp).collect::<Vec<_>>(),
+        ),
+        column("communities", &|r| r.communities.join(" ")),
+        column("summary", &|r| format!("{:?}", r.summary)),
+        Series::new(
+            "rpki",
+            records
+                .iter()
+                .map(|r| r.rpki.map(|state| format!("{state:?}")))
+                .collect::<Vec<_>>(),
+        ),
+        column("reports", &|r| to_json(&r.reports)),
+        Series::new(
+            "leaks",
+            records
+                .iter()
+                .map(|r| r.leaks.as_ref().map(to_json))
+                .collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "aspa",
+            records
+                .iter()
+                .map(|r| r.aspa.as_ref().map(to_json))
+                .collect::<Vec<_>>(),
+        ),
+    ])
+}
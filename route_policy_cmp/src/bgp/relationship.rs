@@ -0,0 +1,141 @@
+//! AS relationships from CAIDA's AS Relationships dataset,
+//! used for valley-free and route leak analysis.
+//! <https://publicdata.caida.org/datasets/as-relationships/>.
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use hashbrown::HashMap;
+
+use crate::parse::Asn;
+
+use super::*;
+
+/// Relationship of a hop where a route propagates from one AS to another.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Relationship {
+    /// Uphill, from a customer to its provider.
+    CustomerToProvider,
+    /// Lateral, between peers.
+    PeerToPeer,
+    /// Downhill, from a provider to its customer.
+    ProviderToCustomer,
+}
+
+impl Relationship {
+    /// Whether a route may only propagate downhill after this hop,
+    /// according to the valley-free property.
+    pub fn is_turn(self) -> bool {
+        self != Relationship::CustomerToProvider
+    }
+}
+
+/// Route leak types that can be seen from the AS path.
+/// <https://www.rfc-editor.org/rfc/rfc7908#section-3>.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum LeakType {
+    /// Type 1: Hairpin turn, from a provider to another provider.
+    HairpinTurn,
+    /// Type 2: Lateral ISP-ISP-ISP leak, from a peer to another peer.
+    Lateral,
+    /// Type 3: Leak of transit-provider prefixes to peers.
+    ProviderToPeer,
+    /// Type 4: Leak of peer prefixes to transit providers.
+    PeerToProvider,
+}
+
+impl LeakType {
+    /// Classify the leak by the leaker that receives the route over
+    /// the `received` hop and propagates it over the `sent` hop.
+    pub fn classify(received: Relationship, sent: Relationship) -> Option<Self> {
+        use Relationship::*;
+        match (received, sent) {
+            (ProviderToCustomer, CustomerToProvider) => Some(Self::HairpinTurn),
+            (PeerToPeer, PeerToPeer) => Some(Self::Lateral),
+            (ProviderToCustomer, PeerToPeer) => Some(Self::ProviderToPeer),
+            (PeerToPeer, CustomerToProvider) => Some(Self::PeerToProvider),
+            _ => None,
+        }
+    }
+}
+
+/// AS relationships keyed by the AS pairs in both directions.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AsRelDb {
+    pub relationships: HashMap<(Asn, Asn), Relationship>,
+}
+
+impl AsRelDb {
+    pub fn len(&self) -> usize {
+        self.relationships.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relationships.is_empty()
+    }
+
+    /// Record that `provider` provides transit to `customer`.
+    pub fn insert_provider_customer(&mut self, provider: Asn, customer: Asn) {
+        use Relationship::*;
+        self.relationships
+            .insert((provider, customer), ProviderToCustomer);
+        self.relationships
+            .insert((customer, provider), CustomerToProvider);
+    }
+
+    /// Record that `a` and `b` peer.
+    pub fn insert_peers(&mut self, a: Asn, b: Asn) {
+        self.relationships.insert((a, b), Relationship::PeerToPeer);
+        self.relationships.insert((b, a), Relationship::PeerToPeer);
+    }
+
+    /// Relationship of the hop from `from` to `to`, if known.
+    pub fn get(&self, from: Asn, to: Asn) -> Option<Relationship> {
+        self.relationships.get(&(from, to)).copied()
+    }
+
+    /// Read the CAIDA `as-rel` or `as-rel2` serial format:
+    /// `<provider>|<customer>|-1` or `<peer>|<peer>|0`,
+    /// optionally followed by `|<source>`. Lines starting with `#` are
+    /// comments.
+    pub fn from_caida<R: BufRead>(reader: R) -> Result<Self> {
+        let mut db = Self::default();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split('|').collect();
+            if fields.len() < 3 {
+                bail!("{line} breaks down to {} fields instead of 3", fields.len());
+            }
+            let a = fields[0].parse()?;
+            let b = fields[1].parse()?;
+            match fields[2] {
+                "-1" => db.insert_provider_customer(a, b),
+                "0" => db.insert_peers(a, b),
+                other => bail!("Unknown relationship `{other}` in {line}"),
+            }
+        }
+        Ok(db)
+    }
+
+    /// Read the CAIDA relationship file at `path`, uncompressed.
+    pub fn from_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let db = Self::from_caida(BufReader::new(File::open(path)?))
+            .with_context(|| format!("reading AS relationships from {}", path.display()))?;
+        debug!(
+            "Read {} AS relationships from {}.",
+            db.len(),
+            path.display()
+        );
+        Ok(db)
+    }
+}
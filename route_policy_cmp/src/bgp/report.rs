@@ -11,59 +11,154 @@ use super::*;
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum Report {
     GoodImport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
     },
     GoodExport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
     },
     GoodSingleExport {
-        from: usize,
+        from: Asn,
     },
     NeutralImport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
         items: Vec<ReportItem>,
     },
     NeutralExport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
         items: Vec<ReportItem>,
     },
     NeutralSingleExport {
-        from: usize,
+        from: Asn,
         items: Vec<ReportItem>,
     },
     AsPathPairWithSet {
         from: AsPathEntry,
         to: AsPathEntry,
     },
+    NormalizedAsPath {
+        items: Vec<Normalization>,
+    },
     SetImport {
-        from: usize,
-        to: Vec<usize>,
+        from: Asn,
+        to: Vec<Asn>,
     },
     SetExport {
-        from: Vec<usize>,
-        to: usize,
+        from: Vec<Asn>,
+        to: Asn,
     },
     SetSingleExport {
-        from: Vec<usize>,
+        from: Vec<Asn>,
     },
     BadImport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
         items: Vec<ReportItem>,
     },
     BadExport {
-        from: usize,
-        to: usize,
+        from: Asn,
+        to: Asn,
         items: Vec<ReportItem>,
     },
     BadSingeExport {
-        from: usize,
+        from: Asn,
         items: Vec<ReportItem>,
     },
+    /// ASPA checks on the hop between `from` and `to`,
+    /// where `from` is closer to the origin.
+    AspaHop {
+        from: Asn,
+        to: Asn,
+        /// Whether `to` is attested as a provider of `from`.
+        up: AspaHopCheck,
+        /// Whether `from` is attested as a provider of `to`.
+        down: AspaHopCheck,
+    },
+    /// ASPA verification result of the whole AS path.
+    AspaPath {
+        direction: AspaDirection,
+        state: AspaState,
+    },
+    /// `leaker` received the route from `from` and leaked it to `to`.
+    RouteLeak {
+        from: Asn,
+        leaker: Asn,
+        to: Asn,
+        leak_type: LeakType,
+    },
+    /// The hop from `from` to `to` goes uphill or laterally after the route
+    /// has already gone downhill or laterally, but the leak cannot be
+    /// classified because the previous hop is unknown.
+    ValleyFreeViolation {
+        from: Asn,
+        to: Asn,
+    },
+    /// The relationship between `from` and `to` is unknown.
+    RelationshipUnknown {
+        from: Asn,
+        to: Asn,
+    },
+    /// Every rule visited when checking the route, see [`Verbosity::trace`].
+    Trace {
+        root: TraceNode,
+    },
+}
+
+impl Report {
+    /// Whether `self` is a bad report of the RPSL checks.
+    pub fn is_bad_rpsl(&self) -> bool {
+        matches!(
+            self,
+            Self::BadImport { .. } | Self::BadExport { .. } | Self::BadSingeExport { .. }
+        )
+    }
+
+    /// Whether `self` is a neutral report of the RPSL checks.
+    pub fn is_neutral(&self) -> bool {
+        matches!(
+            self,
+            Self::NeutralImport { .. }
+                | Self::NeutralExport { .. }
+                | Self::NeutralSingleExport { .. }
+        )
+    }
+
+    /// Cite the policy attributes of the entries that did not match,
+    /// recorded if [`Verbosity::per_entry_err`], e.g.,
+    /// "AS2914 mp-export attribute #7: `to AS-ANY announce AS2914:AS-GLOBAL`".
+    pub fn citations(&self, dump: &QueryDump) -> Vec<String> {
+        let (num, items) = match self {
+            Self::BadImport { to, items, .. } | Self::NeutralImport { to, items, .. } => {
+                (to, items)
+            }
+            Self::BadExport { from, items, .. }
+            | Self::NeutralExport { from, items, .. }
+            | Self::BadSingeExport { from, items }
+            | Self::NeutralSingleExport { from, items } => (from, items),
+            _ => return Vec::new(),
+        };
+        let Some(aut_num) = dump.aut_nums.get(num) else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter_map(|item| match item {
+                NoMatch(MatchProblem::Attribute(index)) => aut_num.cite(*num, *index),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether `self` reports a route leak or valley-free violation.
+    pub fn is_leak(&self) -> bool {
+        matches!(
+            self,
+            Self::RouteLeak { .. } | Self::ValleyFreeViolation { .. }
+        )
+    }
 }
 
 /// Single item in [`Report`] to signal some status.
@@ -75,35 +170,129 @@ pub enum ReportItem {
     Recursion(RecurSrc),
 }
 
+impl ReportItem {
+    /// Names of all the [`SkipReason`], [`MatchProblem`] and [`RpslError`]
+    /// variants, and `Recursion` for all [`RecurSrc`]es, as in [`Self::name`].
+    pub const NAMES: [&'static str; 24] = [
+        "FilterSetUnrecorded",
+        "AsRoutesUnrecorded",
+        "RouteSetUnrecorded",
+        "AsSetUnrecorded",
+        "AsSetRouteUnrecorded",
+        "AsRegexUnimplemented",
+        "CommunityCheckUnimplemented",
+        "PeeringSetUnrecorded",
+        "AutNumUnrecorded",
+        "ImportEmpty",
+        "ExportEmpty",
+        "Filter",
+        "FilterAsNum",
+        "FilterAsSet",
+        "FilterPrefixes",
+        "FilterRouteSet",
+        "RemoteAsNum",
+        "RemoteAsSet",
+        "ExceptPeeringRightMatch",
+        "Peering",
+        "Attribute",
+        "InvalidAsName",
+        "InvalidFilter",
+        "Recursion",
+    ];
+
+    /// Whether `self` is a definite result of the evaluation,
+    /// rather than caused by failing to evaluate.
+    pub fn is_definite(&self) -> bool {
+        matches!(self, NoMatch(_))
+    }
+
+    /// Name of the variant of `self`, one of [`Self::NAMES`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skip(reason) => reason.name(),
+            NoMatch(problem) => problem.name(),
+            BadRpsl(error) => error.name(),
+            Recursion(_) => "Recursion",
+        }
+    }
+
+    /// Name of the as-set `self` is about, if any.
+    pub fn as_set(&self) -> Option<&str> {
+        match self {
+            Skip(SkipReason::AsSetUnrecorded(name) | SkipReason::AsSetRouteUnrecorded(name))
+            | NoMatch(MatchProblem::FilterAsSet(name, _) | MatchProblem::RemoteAsSet(name)) => {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum SkipReason {
     FilterSetUnrecorded(String),
-    AsRoutesUnrecorded(usize),
+    AsRoutesUnrecorded(Asn),
     RouteSetUnrecorded(String),
     AsSetUnrecorded(String),
     AsSetRouteUnrecorded(String),
     // TODO: Remove once implemented.
     AsRegexUnimplemented(String),
-    SkippedNotFilterResult,
     CommunityCheckUnimplemented(Call),
     PeeringSetUnrecorded(String),
-    SkippedExceptPeeringResult,
-    AutNumUnrecorded(usize),
+    AutNumUnrecorded(Asn),
     ImportEmpty,
     ExportEmpty,
 }
 
+impl SkipReason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FilterSetUnrecorded(_) => "FilterSetUnrecorded",
+            Self::AsRoutesUnrecorded(_) => "AsRoutesUnrecorded",
+            Self::RouteSetUnrecorded(_) => "RouteSetUnrecorded",
+            Self::AsSetUnrecorded(_) => "AsSetUnrecorded",
+            Self::AsSetRouteUnrecorded(_) => "AsSetRouteUnrecorded",
+            Self::AsRegexUnimplemented(_) => "AsRegexUnimplemented",
+            Self::CommunityCheckUnimplemented(_) => "CommunityCheckUnimplemented",
+            Self::PeeringSetUnrecorded(_) => "PeeringSetUnrecorded",
+            Self::AutNumUnrecorded(_) => "AutNumUnrecorded",
+            Self::ImportEmpty => "ImportEmpty",
+            Self::ExportEmpty => "ExportEmpty",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum MatchProblem {
     Filter,
-    FilterAsNum(usize, RangeOperator),
+    FilterAsNum(Asn, RangeOperator),
     FilterAsSet(String, RangeOperator),
     FilterPrefixes,
     FilterRouteSet(String),
-    RemoteAsNum(usize),
+    RemoteAsNum(Asn),
     RemoteAsSet(String),
     ExceptPeeringRightMatch,
     Peering,
+    /// The entry from the attribute at this index in the aut-num body did
+    /// not match, see [`AutNum::cite`].
+    Attribute(usize),
+}
+
+impl MatchProblem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Filter => "Filter",
+            Self::FilterAsNum(..) => "FilterAsNum",
+            Self::FilterAsSet(..) => "FilterAsSet",
+            Self::FilterPrefixes => "FilterPrefixes",
+            Self::FilterRouteSet(_) => "FilterRouteSet",
+            Self::RemoteAsNum(_) => "RemoteAsNum",
+            Self::RemoteAsSet(_) => "RemoteAsSet",
+            Self::ExceptPeeringRightMatch => "ExceptPeeringRightMatch",
+            Self::Peering => "Peering",
+            Self::Attribute(_) => "Attribute",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -112,6 +301,15 @@ pub enum RpslError {
     InvalidFilter(String),
 }
 
+impl RpslError {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InvalidAsName(_) => "InvalidAsName",
+            Self::InvalidFilter(_) => "InvalidFilter",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum RecurSrc {
     CheckFilter,
@@ -229,6 +427,53 @@ pub const fn failed_any_report() -> AnyReport {
     Some((vec![], true))
 }
 
+/// Value of a report under three-valued logic.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Truth {
+    /// Definitely matches.
+    True,
+    /// Definitely does not match.
+    False,
+    /// Cannot be determined, e.g., because some objects are unrecorded,
+    /// the RPSL is invalid, or the recursion limit is reached.
+    Unknown,
+}
+
+pub trait ToTruth {
+    /// Only classifies failures accurately if the report is generated with
+    /// [`Verbosity::all_err`], otherwise, all failures are [`Truth::False`].
+    fn truth(&self) -> Truth;
+}
+
+impl ToTruth for AnyReport {
+    fn truth(&self) -> Truth {
+        match self {
+            None => Truth::True,
+            Some((items, true)) if items.iter().all(ReportItem::is_definite) => Truth::False,
+            Some(_) => Truth::Unknown,
+        }
+    }
+}
+
+/// Bad RPSL reports are [`Truth::False`] if all their items are definite,
+/// bad and neutral ones are otherwise [`Truth::Unknown`],
+/// and all other reports are [`Truth::True`].
+impl ToTruth for Report {
+    fn truth(&self) -> Truth {
+        match self {
+            Self::BadImport { items, .. }
+            | Self::BadExport { items, .. }
+            | Self::BadSingeExport { items, .. }
+                if items.iter().all(ReportItem::is_definite) =>
+            {
+                Truth::False
+            }
+            _ if self.is_bad_rpsl() || self.is_neutral() => Truth::Unknown,
+            _ => Truth::True,
+        }
+    }
+}
+
 pub trait ToAnyReport {
     fn to_any(self) -> AnyReport;
 }
@@ -0,0 +1,184 @@
+//! Route origin validation against RPKI Validated ROA Payloads (VRPs).
+//! <https://www.rfc-editor.org/rfc/rfc6811>.
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use hashbrown::HashMap;
+
+use crate::parse::Asn;
+
+use super::*;
+
+/// A Validated ROA Payload.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Vrp {
+    pub prefix: IpNet,
+    pub max_length: u8,
+    pub asn: Asn,
+}
+
+impl Vrp {
+    /// Create a [`Vrp`], checking that `max_length` is between the length of
+    /// `prefix` and the maximum length of its address family.
+    pub fn new(prefix: IpNet, max_length: u8, asn: Asn) -> Result<Self> {
+        if max_length < prefix.prefix_len() || max_length > prefix.max_prefix_len() {
+            bail!("Max length {max_length} is invalid for {prefix}");
+        }
+        Ok(Self {
+            prefix: prefix.trunc(),
+            max_length,
+            asn,
+        })
+    }
+
+    /// Whether `self` matches a route to `prefix` originated by `origin`.
+    pub fn matches(&self, prefix: &IpNet, origin: Asn) -> bool {
+        self.asn == origin && prefix.prefix_len() <= self.max_length
+    }
+}
+
+/// Route origin validation state.
+/// <https://www.rfc-editor.org/rfc/rfc6811#section-2>.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum RpkiState {
+    Valid,
+    Invalid,
+    NotFound,
+}
+
+/// VRPs indexed by prefix for route origin validation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Vrps {
+    /// VRPs keyed by their truncated prefixes.
+    pub by_prefix: HashMap<IpNet, Vec<Vrp>>,
+}
+
+impl Vrps {
+    pub fn len(&self) -> usize {
+        self.by_prefix.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_prefix.is_empty()
+    }
+
+    pub fn insert(&mut self, vrp: Vrp) {
+        self.by_prefix.entry(vrp.prefix).or_default().push(vrp);
+    }
+
+    /// Validate a route to `prefix` originated by `origin`.
+    /// `origin` is `None` if it cannot be determined, e.g., if the AS path
+    /// ends with an AS_SET; such routes are never valid.
+    pub fn validate(&self, prefix: &IpNet, origin: Option<Asn>) -> RpkiState {
+        let mut covered = false;
+        for vrp in self.covering(prefix) {
+            match origin {
+                Some(origin) if vrp.matches(prefix, origin) => return RpkiState::Valid,
+                _ => covered = true,
+            }
+        }
+        match covered {
+            true => RpkiState::Invalid,
+            false => RpkiState::NotFound,
+        }
+    }
+
+    /// All VRPs whose prefixes cover `prefix`.
+    pub fn covering<'a>(&'a self, prefix: &'a IpNet) -> impl Iterator<Item = &'a Vrp> + 'a {
+        (0..=prefix.prefix_len())
+            .filter_map(|len| IpNet::new(prefix.addr(), len).ok())
+            .filter_map(|net| self.by_prefix.get(&net.trunc()))
+            .flatten()
+    }
+
+    /// Read VRPs from the file at `path`, either JSON or CSV,
+    /// as exported by Routinator or rpki-client.
+    pub fn from_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+        let is_json = reader
+            .fill_buf()?
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .is_some_and(|byte| *byte == b'{');
+        let vrps = match is_json {
+            true => Self::from_json(reader),
+            false => Self::from_csv(reader),
+        }
+        .with_context(|| format!("reading VRPs from {}", path.display()))?;
+        debug!("Read {} VRPs from {}.", vrps.len(), path.display());
+        Ok(vrps)
+    }
+
+    /// Read VRPs in the JSON format of Routinator or rpki-client:
+    /// `{"roas": [{"asn": "AS13335", "prefix": "1.0.0.0/24", "maxLength": 24, ...}]}`.
+    /// `asn` may be a string or a number.
+    pub fn from_json<R: Read>(reader: R) -> Result<Self> {
+        let json: JsonVrps = serde_json::from_reader(reader)?;
+        let mut vrps = Self::default();
+        for roa in json.roas {
+            vrps.insert(Vrp::new(roa.prefix, roa.max_length, roa.asn.asn()?)?);
+        }
+        Ok(vrps)
+    }
+
+    /// Read VRPs in the CSV format of Routinator or rpki-client:
+    /// `ASN,IP Prefix,Max Length,Trust Anchor`, with an optional header.
+    pub fn from_csv<R: BufRead>(reader: R) -> Result<Self> {
+        let mut vrps = Self::default();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("ASN") {
+                continue;
+            }
+            let fields: Vec<_> = line.split(',').map(str::trim).collect();
+            if fields.len() < 3 {
+                bail!("{line} breaks down to {} fields instead of 3", fields.len());
+            }
+            let asn = fields[0].parse()?;
+            let prefix = fields[1].parse().context(format!("bad prefix in {line}"))?;
+            let max_length = fields[2]
+                .parse()
+                .context(format!("bad max length in {line}"))?;
+            vrps.insert(Vrp::new(prefix, max_length, asn)?);
+        }
+        Ok(vrps)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonVrps {
+    roas: Vec<JsonRoa>,
+}
+
+#[derive(Deserialize)]
+struct JsonRoa {
+    asn: JsonAsn,
+    prefix: IpNet,
+    #[serde(rename = "maxLength")]
+    max_length: u8,
+}
+
+/// ASN in JSON exports, either a number or a string like `AS13335`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum JsonAsn {
+    Num(u32),
+    Str(String),
+}
+
+impl JsonAsn {
+    pub(crate) fn asn(&self) -> Result<Asn> {
+        match self {
+            JsonAsn::Num(num) => Ok(Asn(*num)),
+            JsonAsn::Str(name) => name.parse(),
+        }
+    }
+}
@@ -1,11 +1,17 @@
+use std::{fs::File, hash::Hash, path::Path, str::FromStr};
+
 use dashmap::DashMap;
+use hashbrown::HashMap;
+use polars::prelude::{
+    CsvWriter, DataFrame, NamedFrom, ParquetWriter, PolarsResult, SerWriter, Series,
+};
 
 use super::*;
 
 use Report::*;
 
 impl Compare {
-    pub fn as_stats(&mut self, dump: &QueryDump, map: &DashMap<usize, AsStats>) {
+    pub fn as_stats(&mut self, dump: &QueryDump, map: &DashMap<Asn, AsStats>) {
         self.verbosity = Verbosity {
             stop_at_first: false,
             show_skips: true,
@@ -59,3 +65,437 @@ pub struct AsStats {
     pub import_err: u32,
     pub export_err: u32,
 }
+
+/// IP version of the prefix of a route.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum IpVersion {
+    Ipv4,
+    Ipv6,
+}
+
+impl IpVersion {
+    pub fn of(prefix: &IpNet) -> Self {
+        match prefix {
+            IpNet::V4(_) => Self::Ipv4,
+            IpNet::V6(_) => Self::Ipv6,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ipv4 => "ipv4",
+            Self::Ipv6 => "ipv6",
+        }
+    }
+}
+
+/// Outcome of one import or export check.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Outcome {
+    Ok,
+    Skip,
+    Err,
+}
+
+/// Counters of import or export checks, and of the [`ReportItem`]s in
+/// them by [`ReportItem::name`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Counts {
+    pub ok: u32,
+    pub skip: u32,
+    pub err: u32,
+    pub items: BTreeMap<&'static str, u32>,
+}
+
+impl Counts {
+    fn add<'a, I>(&mut self, outcome: Outcome, items: I)
+    where
+        I: IntoIterator<Item = &'a ReportItem>,
+    {
+        match outcome {
+            Outcome::Ok => self.ok += 1,
+            Outcome::Skip => self.skip += 1,
+            Outcome::Err => self.err += 1,
+        }
+        for item in items {
+            *self.items.entry(item.name()).or_default() += 1;
+        }
+    }
+}
+
+/// [`Counts`] of import checks and of export checks.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DirCounts {
+    pub import: Counts,
+    pub export: Counts,
+}
+
+impl DirCounts {
+    fn side(&mut self, import: bool) -> &mut Counts {
+        match import {
+            true => &mut self.import,
+            false => &mut self.export,
+        }
+    }
+}
+
+/// Number of routes of each [`Summary`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Summaries {
+    pub good: u32,
+    pub neutral: u32,
+    pub bad: u32,
+}
+
+impl Summaries {
+    fn add(&mut self, summary: Summary) {
+        match summary {
+            Summary::Good => self.good += 1,
+            Summary::Neutral => self.neutral += 1,
+            Summary::Bad => self.bad += 1,
+        }
+    }
+}
+
+/// One import or export check in a [`Report`].
+struct Checked<'a> {
+    import: bool,
+    /// The AS whose policy is checked.
+    asn: Asn,
+    /// The hop checked, `(from, to)`.
+    pair: Option<(Asn, Asn)>,
+    outcome: Outcome,
+    items: &'a [ReportItem],
+}
+
+impl<'a> Checked<'a> {
+    fn of(report: &'a Report) -> Option<Self> {
+        let (import, asn, pair, outcome, items): (_, _, _, _, &[ReportItem]) = match report {
+            GoodImport { from, to } => (true, *to, Some((*from, *to)), Outcome::Ok, &[]),
+            GoodExport { from, to } => (false, *from, Some((*from, *to)), Outcome::Ok, &[]),
+            GoodSingleExport { from } => (false, *from, None, Outcome::Ok, &[]),
+            NeutralImport { from, to, items } => {
+                (true, *to, Some((*from, *to)), Outcome::Skip, items)
+            }
+            NeutralExport { from, to, items } => {
+                (false, *from, Some((*from, *to)), Outcome::Skip, items)
+            }
+            NeutralSingleExport { from, items } => (false, *from, None, Outcome::Skip, items),
+            BadImport { from, to, items } => (true, *to, Some((*from, *to)), Outcome::Err, items),
+            BadExport { from, to, items } => {
+                (false, *from, Some((*from, *to)), Outcome::Err, items)
+            }
+            BadSingeExport { from, items } => (false, *from, None, Outcome::Err, items),
+            _ => return None,
+        };
+        Some(Self {
+            import,
+            asn,
+            pair,
+            outcome,
+            items,
+        })
+    }
+}
+
+/// Compliance statistics of routes, aggregated per AS, per AS pair,
+/// per as-set, per registry, and per vantage point,
+/// each also by [`IpVersion`].
+/// Reports can be [`record`](Self::record)ed from multiple threads.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Checks of the imports and exports of each AS.
+    pub per_as: DashMap<(Asn, IpVersion), DirCounts>,
+    /// Checks of each hop `(from, to)`,
+    /// where the export side checks `from`'s exports
+    /// and the import side checks `to`'s imports.
+    pub per_pair: DashMap<(Asn, Asn, IpVersion), DirCounts>,
+    /// Checks with items about each as-set, counting only those items.
+    pub per_as_set: DashMap<(String, IpVersion), Counts>,
+    /// Checks of the aut-nums from each registry.
+    /// Checks of unrecorded aut-nums are not counted.
+    pub per_registry: DashMap<(String, IpVersion), DirCounts>,
+    /// The registry each aut-num comes from, see [`AutNum::source`].
+    pub registries: HashMap<Asn, String>,
+    /// Routes seen by each collector peer AS, over all its sessions.
+    pub per_collector: DashMap<(Asn, IpVersion), Summaries>,
+    /// Routes seen by each collector peer.
+    pub per_peer: DashMap<(CollectorPeer, IpVersion), Summaries>,
+}
+
+impl Stats {
+    pub fn new(dump: &QueryDump) -> Self {
+        let registries = dump
+            .aut_nums
+            .iter()
+            .filter_map(|(num, aut_num)| Some((*num, aut_num.source()?)))
+            .collect();
+        Self {
+            registries,
+            ..Self::default()
+        }
+    }
+
+    /// `verbosity` to generate reports to [`record`](Self::record) with:
+    /// with all errors reported, so failures are broken down by reason.
+    /// Fail if `verbosity` stops at the first error or hides skips or
+    /// successes, which would distort the counts.
+    pub fn verbosity(verbosity: Verbosity) -> Result<Verbosity> {
+        if verbosity.stop_at_first || !verbosity.show_skips || !verbosity.show_success {
+            bail!("Statistics need every check reported, without stopping at the first error or hiding skips or successes");
+        }
+        Ok(verbosity.with_all_err())
+    }
+
+    /// Record `reports` generated on `compare`,
+    /// with a verbosity from [`Self::verbosity`].
+    pub fn record(&self, compare: &Compare, reports: &[Report]) {
+        let version = IpVersion::of(&compare.prefix);
+        for check in reports.iter().filter_map(Checked::of) {
+            let Checked {
+                import,
+                asn,
+                pair,
+                outcome,
+                items,
+            } = check;
+            self.per_as
+                .entry((asn, version))
+                .or_default()
+                .side(import)
+                .add(outcome, items);
+            if let Some((from, to)) = pair {
+                self.per_pair
+                    .entry((from, to, version))
+                    .or_default()
+                    .side(import)
+                    .add(outcome, items);
+            }
+            if let Some(registry) = self.registries.get(&asn) {
+                self.per_registry
+                    .entry((registry.clone(), version))
+                    .or_default()
+                    .side(import)
+                    .add(outcome, items);
+            }
+            let mut as_sets: Vec<_> = items.iter().filter_map(ReportItem::as_set).collect();
+            as_sets.sort_unstable();
+            as_sets.dedup();
+            for as_set in as_sets {
+                let set_items = items.iter().filter(|item| item.as_set() == Some(as_set));
+                self.per_as_set
+                    .entry((as_set.into(), version))
+                    .or_default()
+                    .add(outcome, set_items);
+            }
+        }
+    }
+
+    /// Record the route to `prefix` checked with `reports` as seen by
+    /// `collector`, once for each vantage point that saw it.
+    pub fn record_vantage_point(
+        &self,
+        collector: CollectorPeer,
+        prefix: &IpNet,
+        reports: &[Report],
+    ) {
+        let version = IpVersion::of(prefix);
+        let summary = Summary::of(reports);
+        self.per_collector
+            .entry((collector.asn, version))
+            .or_default()
+            .add(summary);
+        self.per_peer
+            .entry((collector, version))
+            .or_default()
+            .add(summary);
+    }
+
+    /// Per-AS table, with columns `aut_num`, `ip_version`, `direction`,
+    /// `ok`, `skip`, `err`, and one for each of [`ReportItem::NAMES`].
+    pub fn as_df(&self) -> PolarsResult<DataFrame> {
+        let rows = dir_rows(&self.per_as);
+        let keys = vec![
+            Series::new("aut_num", map_rows(&rows, |((num, _), ..)| num.0)),
+            Series::new("ip_version", map_rows(&rows, |((_, v), ..)| v.as_str())),
+            Series::new("direction", map_rows(&rows, |(_, d, _)| *d)),
+        ];
+        counts_df(keys, rows.iter().map(|(.., counts)| counts))
+    }
+
+    /// Per-AS-pair table, with columns `from`, `to`, `ip_version`,
+    /// `direction`, and the counts as in [`Self::as_df`].
+    pub fn pair_df(&self) -> PolarsResult<DataFrame> {
+        let rows = dir_rows(&self.per_pair);
+        let keys = vec![
+            Series::new("from", map_rows(&rows, |((from, ..), ..)| from.0)),
+            Series::new("to", map_rows(&rows, |((_, to, _), ..)| to.0)),
+            Series::new("ip_version", map_rows(&rows, |((.., v), ..)| v.as_str())),
+            Series::new("direction", map_rows(&rows, |(_, d, _)| *d)),
+        ];
+        counts_df(keys, rows.iter().map(|(.., counts)| counts))
+    }
+
+    /// Per-as-set table, with columns `as_set`, `ip_version`,
+    /// and the counts as in [`Self::as_df`].
+    pub fn as_set_df(&self) -> PolarsResult<DataFrame> {
+        let rows = sorted_rows(&self.per_as_set);
+        let keys = vec![
+            Series::new("as_set", map_rows(&rows, |((name, _), _)| name.as_str())),
+            Series::new("ip_version", map_rows(&rows, |((_, v), _)| v.as_str())),
+        ];
+        counts_df(keys, rows.iter().map(|(_, counts)| counts))
+    }
+
+    /// Per-registry table, with columns `registry`, `ip_version`,
+    /// `direction`, and the counts as in [`Self::as_df`].
+    pub fn registry_df(&self) -> PolarsResult<DataFrame> {
+        let rows = dir_rows(&self.per_registry);
+        let keys = vec![
+            Series::new("registry", map_rows(&rows, |((name, _), ..)| name.as_str())),
+            Series::new("ip_version", map_rows(&rows, |((_, v), ..)| v.as_str())),
+            Series::new("direction", map_rows(&rows, |(_, d, _)| *d)),
+        ];
+        counts_df(keys, rows.iter().map(|(.., counts)| counts))
+    }
+
+    /// Per-collector-AS table, with columns `collector_asn`, `ip_version`,
+    /// `good`, `neutral` and `bad`.
+    pub fn collector_df(&self) -> PolarsResult<DataFrame> {
+        let rows = sorted_rows(&self.per_collector);
+        let keys = vec![
+            Series::new("collector_asn", map_rows(&rows, |((num, _), _)| num.0)),
+            Series::new("ip_version", map_rows(&rows, |((_, v), _)| v.as_str())),
+        ];
+        summaries_df(keys, rows.iter().map(|(_, summaries)| summaries))
+    }
+
+    /// Per-collector-peer table, with columns `collector_asn`,
+    /// `collector_ip`, and the rest as in [`Self::collector_df`].
+    pub fn peer_df(&self) -> PolarsResult<DataFrame> {
+        let rows = sorted_rows(&self.per_peer);
+        let keys = vec![
+            Series::new("collector_asn", map_rows(&rows, |((p, _), _)| p.asn.0)),
+            Series::new(
+                "collector_ip",
+                map_rows(&rows, |((p, _), _)| p.ip.to_string()),
+            ),
+            Series::new("ip_version", map_rows(&rows, |((_, v), _)| v.as_str())),
+        ];
+        summaries_df(keys, rows.iter().map(|(_, summaries)| summaries))
+    }
+
+    /// Write the tables as `as`, `pair`, `as_set`, `registry`,
+    /// `collector` and `peer` files in `dir`, in `format`.
+    pub fn write_to_dir<P: AsRef<Path>>(&self, dir: P, format: StatsFormat) -> Result<()> {
+        let extension = match format {
+            StatsFormat::Csv => "csv",
+            StatsFormat::Parquet => "parquet",
+        };
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for (name, mut df) in [
+            ("as", self.as_df()?),
+            ("pair", self.pair_df()?),
+            ("as_set", self.as_set_df()?),
+            ("registry", self.registry_df()?),
+            ("collector", self.collector_df()?),
+            ("peer", self.peer_df()?),
+        ] {
+            let file = File::create(dir.join(format!("{name}.{extension}")))?;
+            match format {
+                StatsFormat::Csv => CsvWriter::new(file).finish(&mut df)?,
+                StatsFormat::Parquet => _ = ParquetWriter::new(file).finish(&mut df)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format of the tables [`Stats::write_to_dir`] writes.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StatsFormat {
+    Csv,
+    Parquet,
+}
+
+impl FromStr for StatsFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "csv" => Self::Csv,
+            "parquet" => Self::Parquet,
+            other => bail!("Statistics can only be written as csv or parquet, not {other}"),
+        })
+    }
+}
+
+/// Rows of `map` sorted by key, with an import row and an export row
+/// for each key.
+fn dir_rows<K>(map: &DashMap<K, DirCounts>) -> Vec<(K, &'static str, Counts)>
+where
+    K: Clone + Eq + Hash + Ord,
+{
+    let mut rows: Vec<_> = map
+        .iter()
+        .flat_map(|entry| {
+            let DirCounts { import, export } = entry.value().clone();
+            [
+                (entry.key().clone(), "import", import),
+                (entry.key().clone(), "export", export),
+            ]
+        })
+        .collect();
+    rows.sort_unstable_by(|(a, a_dir, _), (b, b_dir, _)| (a, a_dir).cmp(&(b, b_dir)));
+    rows
+}
+
+/// Rows of `map` sorted by key.
+fn sorted_rows<K, V>(map: &DashMap<K, V>) -> Vec<(K, V)>
+where
+    K: Clone + Eq + Hash + Ord,
+    V: Clone,
+{
+    let mut rows: Vec<_> = map
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().clone()))
+        .collect();
+    rows.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    rows
+}
+
+fn map_rows<'a, R, T, F>(rows: &'a [R], f: F) -> Vec<T>
+where
+    F: FnMut(&'a R) -> T,
+{
+    rows.iter().map(f).collect()
+}
+
+/// Table of `keys` followed by columns of `counts`.
+fn counts_df<'a, I>(mut columns: Vec<Series>, counts: I) -> PolarsResult<DataFrame>
+where
+    I: IntoIterator<Item = &'a Counts>,
+{
+    let counts: Vec<_> = counts.into_iter().collect();
+    columns.push(Series::new("ok", map_rows(&counts, |c| c.ok)));
+    columns.push(Series::new("skip", map_rows(&counts, |c| c.skip)));
+    columns.push(Series::new("err", map_rows(&counts, |c| c.err)));
+    for name in ReportItem::NAMES {
+        let column = map_rows(&counts, |c| c.items.get(name).copied().unwrap_or_default());
+        columns.push(Series::new(name, column));
+    }
+    DataFrame::new(columns)
+}
+
+/// Table of `keys` followed by columns of `summaries`.
+fn summaries_df<'a, I>(mut columns: Vec<Series>, summaries: I) -> PolarsResult<DataFrame>
+where
+    I: IntoIterator<Item = &'a Summaries>,
+{
+    let summaries: Vec<_> = summaries.into_iter().collect();
+    columns.push(Series::new("good", map_rows(&summaries, |s| s.good)));
+    columns.push(Series::new("neutral", map_rows(&summaries, |s| s.neutral)));
+    columns.push(Series::new("bad", map_rows(&summaries, |s| s.bad)));
+    DataFrame::new(columns)
+}
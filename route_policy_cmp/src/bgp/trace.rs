@@ -0,0 +1,163 @@
+//! Evaluation trace recorded when [`Verbosity::trace`] is set.
+//!
+//! Each check of a policy rule pushes a [`TraceNode`] onto a thread-local
+//! stack, and attaches it to its parent once the check finishes,
+//! so the checking functions do not need to pass the trace around.
+//! A check of one route always runs on a single thread.
+use std::{cell::RefCell, fmt::Write};
+
+use crate::parse::*;
+
+use super::*;
+
+/// A rule visited during evaluation, with its outcome and the rules visited
+/// to evaluate it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TraceNode {
+    pub rule: String,
+    pub outcome: Truth,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TraceNode>,
+}
+
+impl TraceNode {
+    pub fn new(rule: String) -> Self {
+        Self {
+            rule,
+            outcome: Truth::Unknown,
+            children: Vec::new(),
+        }
+    }
+
+    /// Render `self` as an indented text tree, one rule per line.
+    pub fn render(&self) -> String {
+        let mut result = String::new();
+        self.render_to(&mut result, 0);
+        result
+    }
+
+    /// Render `self` as JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    fn render_to(&self, result: &mut String, depth: usize) {
+        _ = writeln!(
+            result,
+            "{:indent$}{}: {:?}",
+            "",
+            self.rule,
+            self.outcome,
+            indent = depth * 2
+        );
+        for child in &self.children {
+            child.render_to(result, depth + 1);
+        }
+    }
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<TraceNode>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Outcome of a check to record in a [`TraceNode`].
+pub trait TraceOutcome {
+    fn outcome(&self) -> Truth;
+}
+
+impl TraceOutcome for AnyReport {
+    fn outcome(&self) -> Truth {
+        self.truth()
+    }
+}
+
+impl TraceOutcome for AllReport {
+    fn outcome(&self) -> Truth {
+        match self {
+            Ok(None) => Truth::True,
+            Ok(Some(_)) => Truth::Unknown,
+            Err(items) if items.iter().all(ReportItem::is_definite) => Truth::False,
+            Err(_) => Truth::Unknown,
+        }
+    }
+}
+
+/// Run `check` and record it as a node for `rule` if `enabled` and
+/// a trace is being [`collect`]ed on this thread.
+pub fn traced<R, F, C>(enabled: bool, rule: F, check: C) -> R
+where
+    R: TraceOutcome,
+    F: FnOnce() -> String,
+    C: FnOnce() -> R,
+{
+    if !enabled || STACK.with(|stack| stack.borrow().is_empty()) {
+        return check();
+    }
+    STACK.with(|stack| stack.borrow_mut().push(TraceNode::new(rule())));
+    let result = check();
+    let mut node = pop();
+    node.outcome = result.outcome();
+    STACK.with(|stack| {
+        if let Some(parent) = stack.borrow_mut().last_mut() {
+            parent.children.push(node);
+        }
+    });
+    result
+}
+
+/// Run `check` and collect the nodes [`traced`] within it under a root
+/// node for `rule`. The root's outcome is left for the caller to set.
+pub fn collect<R, C>(rule: String, check: C) -> (R, TraceNode)
+where
+    C: FnOnce() -> R,
+{
+    STACK.with(|stack| stack.borrow_mut().push(TraceNode::new(rule)));
+    let result = check();
+    (result, pop())
+}
+
+fn pop() -> TraceNode {
+    STACK
+        .with(|stack| stack.borrow_mut().pop())
+        .expect("Pushed before checking")
+}
+
+/// Short description of `filter` without its sub-filters.
+pub fn describe_filter(filter: &Filter) -> String {
+    match filter {
+        Filter::FilterSet(name) => format!("filter-set {name}"),
+        Filter::Any => "ANY".into(),
+        Filter::AddrPrefixSet(prefixes) => {
+            let prefixes: Vec<_> = prefixes
+                .iter()
+                .map(|p| format!("{}{}", p.address_prefix, p.range_operator))
+                .collect();
+            format!("{{{}}}", prefixes.join(", "))
+        }
+        Filter::RouteSet(name, op) => format!("route-set {name}{op}"),
+        Filter::AsNum(num, op) => format!("AS{num}{op}"),
+        Filter::AsSet(name, op) => format!("as-set {name}{op}"),
+        Filter::AsPathRE(expr) => format!("<{expr}>"),
+        Filter::And { .. } => "AND".into(),
+        Filter::Or { .. } => "OR".into(),
+        Filter::Not(_) => "NOT".into(),
+        Filter::Group(_) => "()".into(),
+        Filter::Community(call) => format!("community {call:?}"),
+        Filter::Invalid(reason) => format!("invalid filter {reason}"),
+    }
+}
+
+/// Short description of `as_expr` without its sub-expressions.
+pub fn describe_as_expr(as_expr: &AsExpr) -> String {
+    match as_expr {
+        AsExpr::Single(AsName::Num(num)) => format!("AS{num}"),
+        AsExpr::Single(AsName::Set(name)) => format!("as-set {name}"),
+        AsExpr::Single(AsName::Invalid(reason)) => format!("invalid AS name {reason}"),
+        AsExpr::PeeringSet(name) => format!("peering-set {name}"),
+        AsExpr::And { .. } => "AND".into(),
+        AsExpr::Or { .. } => "OR".into(),
+        AsExpr::Except { .. } => "EXCEPT".into(),
+        AsExpr::Group(_) => "()".into(),
+    }
+}
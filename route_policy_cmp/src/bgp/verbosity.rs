@@ -1,7 +1,9 @@
 use super::*;
 
 /// Verbosity level.
-#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// Fields missing when deserializing take their [`Default`] values.
+#[derive(Clone, Copy, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(default)]
 pub struct Verbosity {
     /// Stop checking the AS path at the first [`Report`].
     pub stop_at_first: bool,
@@ -13,6 +15,9 @@ pub struct Verbosity {
     pub per_entry_err: bool,
     /// All errors.
     pub all_err: bool,
+    /// Record a tree of every rule visited, with the outcome at each node,
+    /// in a [`Report::Trace`].
+    pub trace: bool,
 }
 
 impl std::fmt::Debug for Verbosity {
@@ -24,6 +29,7 @@ impl std::fmt::Debug for Verbosity {
             show_success,
             per_entry_err,
             all_err,
+            trace,
         } = self;
         for (is_true, tag) in [
             (stop_at_error, "stop_at_error"),
@@ -31,6 +37,7 @@ impl std::fmt::Debug for Verbosity {
             (show_success, "show_success"),
             (per_entry_err, "per_entry_err"),
             (all_err, "all_err"),
+            (trace, "trace"),
         ] {
             if *is_true {
                 result.entry(&tag);
@@ -47,6 +54,7 @@ impl Verbosity {
         show_success: bool,
         per_entry_err: bool,
         all_err: bool,
+        trace: bool,
     ) -> Self {
         Self {
             stop_at_first: stop_at_error,
@@ -54,6 +62,16 @@ impl Verbosity {
             show_success,
             per_entry_err,
             all_err,
+            trace,
+        }
+    }
+
+    /// `self` but with `all_err`, so failures can be classified by
+    /// [`ToTruth::truth`].
+    pub fn with_all_err(self) -> Self {
+        Self {
+            all_err: true,
+            ..self
         }
     }
 }
@@ -66,6 +84,7 @@ impl Default for Verbosity {
             show_success: false,
             per_entry_err: false,
             all_err: false,
+            trace: false,
         }
     }
 }
@@ -118,6 +137,26 @@ pub trait VerbosityReport {
         }
     }
 
+    /// Negate `report` under three-valued logic:
+    /// true becomes false because of `reason`, definite false becomes true,
+    /// and unknown stays unknown, keeping the reasons it is unknown.
+    /// `report` should be generated with [`Verbosity::with_all_err`] so
+    /// failures caused by bad RPSL or recursion are seen as unknown.
+    fn not_any_report<F>(&self, report: AnyReport, reason: F) -> AnyReport
+    where
+        F: Fn() -> MatchProblem,
+    {
+        match (report.truth(), report) {
+            (Truth::True, _) => self.no_match_any_report(reason),
+            (Truth::False, _) => None,
+            (Truth::Unknown, Some((items, _))) if self.get_verbosity().show_skips => {
+                let items = items.into_iter().filter(|item| !item.is_definite());
+                Some((items.collect(), false))
+            }
+            (Truth::Unknown, _) => empty_skip_any_report(),
+        }
+    }
+
     fn skip_all_report<F>(&self, reason: F) -> AllReport
     where
         F: Fn() -> SkipReason,
@@ -4,6 +4,8 @@ use std::{
     process::Command,
 };
 
+use hashbrown::HashMap;
+
 use crate::cmd::OutputChild;
 
 use super::*;
@@ -14,29 +16,146 @@ pub struct Line {
     pub raw: String,
     /// Designed to be directly used.
     pub compare: Compare,
+    /// Vantage point that saw the route.
+    pub collector: CollectorPeer,
+    /// Seconds since the epoch when the route was dumped or announced.
+    pub timestamp: u64,
+    /// Communities as `<AS>:<value>`.
+    pub communities: Vec<String>,
     /// Slot used to store the generated report about this line.
     pub report: Option<Vec<Report>>,
+    /// Slot used to store the RPKI route origin validation state.
+    pub rpki: Option<RpkiState>,
+    /// Slot used to store the route leak reports about this line.
+    pub leaks: Option<Vec<Report>>,
+    /// Slot used to store the ASPA verification reports about this line.
+    pub aspa: Option<Vec<Report>>,
 }
 
 impl Line {
-    pub fn new(raw: String, compare: Compare, report: Option<Vec<Report>>) -> Self {
+    pub fn new(
+        raw: String,
+        compare: Compare,
+        collector: CollectorPeer,
+        report: Option<Vec<Report>>,
+    ) -> Self {
         Self {
             raw,
             compare,
+            collector,
+            timestamp: 0,
+            communities: Vec::new(),
             report,
+            rpki: None,
+            leaks: None,
+            aspa: None,
         }
     }
 
     /// Parse `raw` into a [`Line`].
     pub fn from_raw(raw: String) -> Result<Self> {
-        let compare = Compare::with_line_dump(&raw)?;
-        Ok(Self::new(raw, compare, None))
+        Self::from_raw_normalizer(raw, &Normalizer::default())
+    }
+
+    /// Like [`Self::from_raw`], but normalize the AS path using `normalizer`.
+    pub fn from_raw_normalizer(raw: String, normalizer: &Normalizer) -> Result<Self> {
+        let (prefix, as_path, collector, communities) = parse_table_dump(&raw)?;
+        let communities = communities.into_iter().map(String::from).collect();
+        let timestamp = parse_timestamp(&raw)?;
+        let compare = Compare::with_normalizer(prefix, as_path, normalizer);
+        Ok(Self {
+            timestamp,
+            communities,
+            ..Self::new(raw, compare, collector, None)
+        })
     }
 
     /// Generate report on `self` and store in `self.report`.
     pub fn check(&mut self, dump: &QueryDump) {
         self.report = Some(self.compare.check(dump));
     }
+
+    /// Like [`Self::check`], but reuse results from `cache`,
+    /// see [`Compare::check_cached`].
+    pub fn check_cached(&mut self, cache: &PairCache) {
+        self.report = Some(self.compare.check_cached(cache));
+    }
+
+    /// Validate the route origin against `vrps` and store in `self.rpki`.
+    pub fn check_rpki(&mut self, vrps: &Vrps) {
+        self.rpki = Some(self.compare.rpki_state(vrps));
+    }
+
+    /// Check for route leaks using `rels` and store in `self.leaks`.
+    pub fn check_leaks(&mut self, rels: &AsRelDb) {
+        self.leaks = Some(self.compare.check_leaks(rels));
+    }
+
+    /// Verify the AS path against `aspas` and store in `self.aspa`.
+    pub fn check_aspa(&mut self, aspas: &Aspas, direction: AspaDirection) {
+        self.aspa = Some(self.compare.check_aspa(aspas, direction));
+    }
+
+    /// Whether ASPA verification found the AS path invalid.
+    /// `self.aspa` needs to be generated first.
+    pub fn is_aspa_invalid(&self) -> bool {
+        self.aspa.iter().flatten().any(|report| {
+            matches!(
+                report,
+                Report::AspaPath {
+                    state: AspaState::Invalid,
+                    ..
+                }
+            )
+        })
+    }
+
+    /// Whether the RPSL checks found nothing bad but the route is a leak.
+    /// Both `self.report` and `self.leaks` need to be generated first.
+    pub fn is_rpsl_allowed_leak(&self) -> bool {
+        match (&self.report, &self.leaks) {
+            (Some(report), Some(leaks)) => {
+                !report.iter().any(Report::is_bad_rpsl) && leaks.iter().any(Report::is_leak)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Run `check` on the first of `lines` with each distinct [`Compare`],
+/// in parallel, and copy the `report`, `rpki`, `leaks` and `aspa` it generates to
+/// the other lines with the same [`Compare`], seen by other vantage points.
+/// Return the number of distinct [`Compare`]s checked.
+pub fn check_dedup<F>(lines: &mut [Line], check: F) -> usize
+where
+    F: Fn(&mut Line) + Sync,
+{
+    let mut firsts = HashMap::with_capacity(lines.len());
+    let owners: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| *firsts.entry(&line.compare).or_insert(index))
+        .collect();
+    let n_distinct = firsts.len();
+    drop(firsts);
+
+    lines
+        .par_iter_mut()
+        .zip(&owners)
+        .enumerate()
+        .filter(|(index, (_, owner))| index == *owner)
+        .for_each(|(_, (line, _))| check(line));
+    for (index, owner) in owners.into_iter().enumerate() {
+        if index != owner {
+            let (checked, rest) = lines.split_at_mut(index);
+            let (checked, line) = (&checked[owner], &mut rest[0]);
+            line.report.clone_from(&checked.report);
+            line.rpki = checked.rpki;
+            line.leaks.clone_from(&checked.leaks);
+            line.aspa.clone_from(&checked.aspa);
+        }
+    }
+    n_distinct
 }
 
 /// Read MRT file at `path` using the `bgpdump` executable.
@@ -49,15 +168,44 @@ where
 }
 
 /// Pack all the [`Line`]s from `output_child`'s output.
-pub fn pack_lines(mut output_child: OutputChild) -> Result<Vec<Line>> {
-    let mut lines = Vec::new();
+pub fn pack_lines(output_child: OutputChild) -> Result<Vec<Line>> {
+    stream_lines(output_child, Normalizer::default()).collect()
+}
+
+/// Lazily read the [`Line`]s from `output_child`'s output one by one,
+/// normalizing their AS paths using `normalizer`.
+pub fn stream_lines(
+    mut output_child: OutputChild,
+    normalizer: Normalizer,
+) -> impl Iterator<Item = Result<Line>> {
     let mut line = String::new();
+    std::iter::from_fn(move || match output_child.stdout.read_line(&mut line) {
+        Ok(0) => None,
+        Ok(_) => Some(Line::from_raw_normalizer(mem::take(&mut line), &normalizer)),
+        Err(err) => Some(Err(err.into())),
+    })
+}
 
-    while output_child.stdout.read_line(&mut line)? > 0 {
-        let raw = mem::take(&mut line);
-        lines.push(Line::from_raw(raw)?);
-    }
-    Ok(lines)
+/// Skip the lines in `lines` that are not routes or fail to parse,
+/// e.g., withdrawals in BGP4MP updates or malformed JSON messages,
+/// with a warning, counting them in `n_skipped`.
+/// Errors reading the input are kept.
+pub fn skip_bad_lines<'a, I>(
+    lines: I,
+    n_skipped: &'a mut usize,
+) -> impl Iterator<Item = Result<Line>> + 'a
+where
+    I: IntoIterator<Item = Result<Line>>,
+    I::IntoIter: 'a,
+{
+    lines.into_iter().filter(move |line| match line {
+        Err(err) if !err.is::<io::Error>() => {
+            warn!("Skipping {err:#}.");
+            *n_skipped += 1;
+            false
+        }
+        _ => true,
+    })
 }
 
 /// Start a `bgpdump` process that reads `path`.
@@ -0,0 +1,457 @@
+//! Command-line interface.
+use std::{io::stdout, net::TcpListener, path::PathBuf, time::Duration};
+
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::{
+    analysis::simulate::Simulation,
+    api::{self, Api},
+    bgp::{AspaDirection, JsonFormat, Normalizer, QueryDump, RecordFormat, StatsFormat, Verbosity},
+    fs::{self, BmpOptions, BmpSource, ReportOptions},
+    parse::{Asn, Dump, MEMORY_CEILING},
+    shell,
+    whois::Whois,
+};
+
+use super::*;
+
+/// Parse RPSL policies from IRR dumps and check BGP routes against them.
+///
+/// Set `RUST_LOG=route_policy_cmp=debug` to see progress.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Number of threads to use, all CPUs by default.
+    #[arg(short = 'j', long, global = true)]
+    pub threads: Option<usize>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Parse one IRR database file into a parsed dump.
+    Parse {
+        /// IRR database file to read.
+        file: String,
+        /// Directory to write the parsed dump to.
+        output_dir: String,
+        #[command(flatten)]
+        memory: MemoryArgs,
+    },
+    /// Parse all IRR database files in a directory into one parsed dump.
+    ParseAll {
+        /// Directory of IRR database files to read.
+        input_dir: String,
+        /// Directory to write the parsed dump to.
+        output_dir: String,
+        #[command(flatten)]
+        memory: MemoryArgs,
+    },
+    /// Parse priority and backup IRR databases, preferring the objects in
+    /// the priority databases.
+    ParsePriority {
+        /// Directory of priority IRR database files to read.
+        priority_dir: String,
+        /// Directory of backup IRR database files to read.
+        backup_dir: String,
+        /// Directory to write the parsed dump to.
+        output_dir: String,
+        #[command(flatten)]
+        memory: MemoryArgs,
+    },
+    /// Read a parsed dump and log its object counts.
+    Read {
+        /// Directory of the parsed dump.
+        input_dir: String,
+    },
+    /// Check the routes in an MRT file or JSON updates against a parsed dump.
+    Report(ReportArgs),
+    /// Receive BGP Monitoring Protocol (BMP) streams from routers and check
+    /// the routes as they arrive.
+    Bmp(BmpArgs),
+    /// Infer AS relationships from the shape of aut-num policies,
+    /// written in the CAIDA format.
+    InferRel {
+        /// Directory of the parsed dump.
+        parsed_dir: String,
+        /// File to write the inferred relationships to.
+        output_file: String,
+    },
+    /// Check the exports of each AS against the imports of its neighbors,
+    /// written as JSON Lines.
+    Bilateral {
+        /// Directory of the parsed dump.
+        parsed_dir: String,
+        /// File to write the mismatches to.
+        output_file: String,
+    },
+    /// Simulate the propagation of a prefix through the policies.
+    Simulate(SimulateArgs),
+    /// Load a parsed dump and query it interactively.
+    Shell {
+        /// Directory of the parsed dump.
+        parsed_dir: String,
+    },
+    /// Serve IRRd-style whois queries from a parsed dump.
+    Whois {
+        /// Directory of the parsed dump.
+        parsed_dir: String,
+        /// Address to listen on.
+        #[arg(short, long, default_value = "127.0.0.1:4343")]
+        address: String,
+    },
+    /// Serve a JSON API over HTTP to check routes and look up objects.
+    Serve(ServeArgs),
+    /// Print a shell completion script to stdout.
+    Completions {
+        /// Shell to generate the script for.
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Directory of the parsed dump.
+    pub parsed_dir: String,
+    /// MRT file to read routes from, using `bgpdump`,
+    /// or file of JSON updates with `--json`, `-` for stdin.
+    pub input: String,
+    /// Read `input` as JSON updates in this format, one message per line:
+    /// `ris-live` or `exabgp`.
+    #[arg(long)]
+    pub json: Option<JsonFormat>,
+    /// VRP file in JSON or CSV to validate route origins against.
+    #[arg(long)]
+    pub vrp: Option<String>,
+    /// CAIDA AS relationship file to check route leaks against.
+    #[arg(long)]
+    pub as_rel: Option<String>,
+    /// ASPA file in JSON to verify AS paths against.
+    #[arg(long)]
+    pub aspa: Option<String>,
+    /// Verify AS paths with `--aspa` as received from customers, peers or
+    /// route servers (`upstream`), or from providers (`downstream`).
+    #[arg(long, default_value = "upstream", requires = "aspa")]
+    pub aspa_direction: AspaDirection,
+    /// File to write one record per route to.
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Format of `--output`: `jsonl`, `csv` or `parquet`.
+    /// Guessed from its extension by default.
+    #[arg(long)]
+    pub format: Option<RecordFormat>,
+    /// Directory to write compliance statistics tables to.
+    /// Implies `--all-err`.
+    #[arg(long, conflicts_with_all = ["stop_at_first", "hide_skips", "hide_success"])]
+    pub stats_dir: Option<String>,
+    /// Format of the statistics tables: `csv` or `parquet`.
+    #[arg(long, default_value = "csv")]
+    pub stats_format: StatsFormat,
+    /// Only check this many routes from the start of the input.
+    #[arg(short = 'n', long)]
+    pub sample_size: Option<usize>,
+    #[command(flatten)]
+    pub verbosity: VerbosityArgs,
+    #[command(flatten)]
+    pub normalize: NormalizeArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct BmpArgs {
+    /// Directory of the parsed dump.
+    pub parsed_dir: String,
+    /// Address to accept BMP connections from routers on.
+    #[arg(short, long, default_value = "0.0.0.0:11019")]
+    pub listen: String,
+    /// Read BMP messages captured from a connection in this file
+    /// instead of listening.
+    #[arg(long, conflicts_with = "listen")]
+    pub replay: Option<String>,
+    /// File to write one record per route to, instead of JSON Lines to stdout.
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Format of `--output`: `jsonl`, `csv` or `parquet`.
+    /// Guessed from its extension by default.
+    #[arg(long)]
+    pub format: Option<RecordFormat>,
+    /// Directory to write compliance statistics tables to.
+    /// Implies `--all-err`.
+    #[arg(long, conflicts_with_all = ["stop_at_first", "hide_skips", "hide_success"])]
+    pub stats_dir: Option<String>,
+    /// Format of the statistics tables: `csv` or `parquet`.
+    #[arg(long, default_value = "csv")]
+    pub stats_format: StatsFormat,
+    /// Seconds between rewrites of the statistics tables while listening.
+    #[arg(long, default_value_t = 60)]
+    pub stats_secs: u64,
+    #[command(flatten)]
+    pub verbosity: VerbosityArgs,
+    #[command(flatten)]
+    pub normalize: NormalizeArgs,
+}
+
+/// Flags for [`Verbosity`], defaulting to reporting everything.
+#[derive(Debug, Args)]
+pub struct VerbosityArgs {
+    /// Stop checking each AS path at the first report.
+    #[arg(long)]
+    pub stop_at_first: bool,
+    /// Do not report skipped checks.
+    #[arg(long)]
+    pub hide_skips: bool,
+    /// Do not report successful checks.
+    #[arg(long)]
+    pub hide_success: bool,
+    /// Report which policy entries fail to match.
+    #[arg(long)]
+    pub per_entry_err: bool,
+    /// Report the reason of every failure.
+    #[arg(long)]
+    pub all_err: bool,
+    /// Record the tree of every rule visited. Implies `--all-err`.
+    #[arg(long)]
+    pub trace: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct NormalizeArgs {
+    /// Remove private ASNs from AS paths before checking them.
+    #[arg(long)]
+    pub remove_private: bool,
+    /// Remove reserved ASNs and AS_TRANS from AS paths before checking them.
+    #[arg(long)]
+    pub remove_reserved: bool,
+    /// Remove confederation segments from AS paths before checking them.
+    #[arg(long)]
+    pub remove_confed: bool,
+    /// Keep AS_SETs with a single member instead of replacing them with it.
+    #[arg(long)]
+    pub keep_single_sets: bool,
+}
+
+impl From<&NormalizeArgs> for Normalizer {
+    fn from(args: &NormalizeArgs) -> Self {
+        Normalizer {
+            remove_private: args.remove_private,
+            remove_reserved: args.remove_reserved,
+            remove_confed: args.remove_confed,
+            expand_single_sets: !args.keep_single_sets,
+            ..Normalizer::default()
+        }
+    }
+}
+
+impl From<&VerbosityArgs> for Verbosity {
+    fn from(args: &VerbosityArgs) -> Self {
+        Verbosity::new(
+            args.stop_at_first,
+            !args.hide_skips,
+            !args.hide_success,
+            args.per_entry_err,
+            args.all_err,
+            args.trace,
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct MemoryArgs {
+    /// Write parsed objects and routes to a new shard of the parsed dump
+    /// once the buffered ones take this many MiB of RPSL text and prefixes.
+    /// Buffered objects take a few times this much memory once parsed.
+    /// The names of all objects and the ASes with routes are also kept in
+    /// memory until the end, which this does not bound.
+    #[arg(long, default_value_t = MEMORY_CEILING >> 20)]
+    pub memory_mib: usize,
+}
+
+impl MemoryArgs {
+    /// The ceiling in bytes.
+    pub fn ceiling(&self) -> usize {
+        self.memory_mib << 20
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SimulateArgs {
+    /// Directory of the parsed dump.
+    pub parsed_dir: String,
+    /// Prefix to propagate.
+    pub prefix: IpNet,
+    /// AS originating the prefix.
+    pub origin: Asn,
+    /// Maximum AS path length to propagate to, including the origin.
+    #[arg(long, default_value_t = Simulation::default().max_depth)]
+    pub max_depth: usize,
+    /// Propagate over hops whose checks are skipped as if permitted.
+    #[arg(long)]
+    pub allow_unknown: bool,
+    /// Stop once this many paths are found.
+    #[arg(long, default_value_t = Simulation::default().max_paths)]
+    pub max_paths: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Directory of the parsed dump, or of parsed dumps with `--watch`.
+    pub parsed_dir: PathBuf,
+    /// Address to listen on.
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    pub address: String,
+    /// Serve the subdirectory of `parsed_dir` with the greatest name,
+    /// and switch to any newer one that appears.
+    #[arg(long)]
+    pub watch: bool,
+    /// Seconds between checks for a newer dump directory with `--watch`.
+    #[arg(long, default_value_t = 60)]
+    pub poll_secs: u64,
+}
+
+impl Cli {
+    pub fn run(self) -> Result<()> {
+        if let Some(threads) = self.threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()?;
+            debug!("Using {threads} threads.");
+        }
+
+        match self.command {
+            Command::Parse {
+                file,
+                output_dir,
+                memory,
+            } => fs::parse(&file, &output_dir, memory.ceiling()),
+            Command::ParseAll {
+                input_dir,
+                output_dir,
+                memory,
+            } => fs::parse_all(&input_dir, &output_dir, memory.ceiling()),
+            Command::ParsePriority {
+                priority_dir,
+                backup_dir,
+                output_dir,
+                memory,
+            } => fs::parse_priority(&priority_dir, &backup_dir, &output_dir, memory.ceiling()),
+            Command::Read { input_dir } => fs::read(&input_dir),
+            Command::Report(args) => report(args),
+            Command::Bmp(args) => bmp(args),
+            Command::InferRel {
+                parsed_dir,
+                output_file,
+            } => fs::infer_rel(&parsed_dir, &output_file),
+            Command::Bilateral {
+                parsed_dir,
+                output_file,
+            } => fs::bilateral(&parsed_dir, &output_file),
+            Command::Simulate(args) => {
+                let simulation = Simulation {
+                    max_depth: args.max_depth,
+                    allow_unknown: args.allow_unknown,
+                    max_paths: args.max_paths,
+                };
+                debug!(
+                    "Will simulate {} originated by AS{} with {simulation:?}.",
+                    args.prefix, args.origin
+                );
+                fs::simulate(&args.parsed_dir, args.prefix, args.origin, simulation)
+            }
+            Command::Shell { parsed_dir } => {
+                let parsed = Dump::pal_read(&parsed_dir)?;
+                parsed.log_count();
+                shell::Shell::new(QueryDump::from_dump(parsed)).run()
+            }
+            Command::Whois {
+                parsed_dir,
+                address,
+            } => {
+                let parsed = Dump::pal_read(&parsed_dir)?;
+                parsed.log_count();
+                let whois = Whois::new(QueryDump::from_dump(parsed));
+                let listener =
+                    TcpListener::bind(&address).with_context(|| format!("binding to {address}"))?;
+                debug!("Serving whois queries on {address}.");
+                whois.serve(listener)
+            }
+            Command::Serve(args) => serve(args),
+            Command::Completions { shell } => {
+                let mut command = Self::command();
+                let name = command.get_name().to_string();
+                clap_complete::generate(shell, &mut command, name, &mut stdout());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn serve(args: ServeArgs) -> Result<()> {
+    let (api, poll) = match args.watch {
+        true => (
+            Api::watching(args.parsed_dir)?,
+            Some(Duration::from_secs(args.poll_secs)),
+        ),
+        false => {
+            let dump = api::load(&args.parsed_dir)?;
+            (Api::new(args.parsed_dir, dump), None)
+        }
+    };
+    let server = tiny_http::Server::http(&args.address)
+        .map_err(|err| anyhow::anyhow!("binding to {}: {err}", args.address))?;
+    debug!("Serving the JSON API on {}.", args.address);
+    api.serve(server, poll);
+    Ok(())
+}
+
+/// `output` with `format`, or the format guessed from its extension.
+fn output_format(
+    output: &Option<String>,
+    format: Option<RecordFormat>,
+) -> Result<Option<(&str, RecordFormat)>> {
+    Ok(match (output, format) {
+        (Some(path), Some(format)) => Some((path.as_str(), format)),
+        (Some(path), None) => Some((path.as_str(), RecordFormat::from_path(path)?)),
+        (None, _) => None,
+    })
+}
+
+fn bmp(args: BmpArgs) -> Result<()> {
+    let source = match &args.replay {
+        Some(path) => BmpSource::Replay(path),
+        None => BmpSource::Listen(&args.listen),
+    };
+    let options = BmpOptions {
+        source,
+        output: output_format(&args.output, args.format)?,
+        stats: args
+            .stats_dir
+            .as_deref()
+            .map(|stats_dir| (stats_dir, args.stats_format)),
+        stats_interval: Duration::from_secs(args.stats_secs),
+        verbosity: (&args.verbosity).into(),
+        normalizer: (&args.normalize).into(),
+    };
+    debug!("Will check BMP routes with {options:?}.");
+    fs::bmp(&args.parsed_dir, options)
+}
+
+fn report(args: ReportArgs) -> Result<()> {
+    let output = output_format(&args.output, args.format)?;
+    let options = ReportOptions {
+        vrp_path: args.vrp.as_deref(),
+        as_rel_path: args.as_rel.as_deref(),
+        aspa: args.aspa.as_deref().map(|aspa| (aspa, args.aspa_direction)),
+        output,
+        stats: args
+            .stats_dir
+            .as_deref()
+            .map(|stats_dir| (stats_dir, args.stats_format)),
+        sample_size: args.sample_size,
+        verbosity: (&args.verbosity).into(),
+        normalizer: (&args.normalize).into(),
+        json: args.json,
+    };
+    debug!("Will report on {} with {options:?}.", args.input);
+    fs::report(&args.parsed_dir, &args.input, options)
+}
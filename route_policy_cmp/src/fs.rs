@@ -1,30 +1,31 @@
 use chardetng::EncodingDetector;
 use encoding_rs::Encoding;
 use encoding_rs_io::DecodeReaderBytesBuilder;
-use std::{fs::*, io::*, path::Path};
+use std::{
+    fmt,
+    fs::*,
+    io::*,
+    net::TcpListener,
+    path::Path,
+    sync::{mpsc::sync_channel, Mutex},
+    thread,
+    time::Duration,
+};
 
 use super::{
+    analysis::{bilateral::*, infer_rel::*, simulate::*},
     bgp::*,
     irr::*,
-    parse::{parse_lexed, Dump},
+    parse::{Dump, ShardWriter},
     Result, *,
 };
 
-pub fn parse(filename: &str, output_dir: &str) -> Result<()> {
-    let encoding = detect_file_encoding(filename)?;
-    let decoder = DecodeReaderBytesBuilder::new()
-        .encoding(Some(encoding))
-        .build(File::open(filename)?);
-    let reader = BufReader::new(decoder);
-    let dump = read_db(reader)?;
-    dump.log_count();
-
-    let parsed = parse_lexed(dump);
-    debug!("Starting to write the parsed dump.");
-    parsed.pal_write(output_dir)?;
-    debug!("Wrote the parsed dump.");
-
-    Ok(())
+/// Parse the IRR DB `filename` into shards in `output_dir`,
+/// buffering at most about `ceiling` bytes of objects.
+pub fn parse(filename: &str, output_dir: &str, ceiling: usize) -> Result<()> {
+    let writer = Mutex::new(ShardWriter::new(output_dir, ceiling)?);
+    stream_db(open_db(filename)?, &writer)?;
+    finish(writer)
 }
 
 pub fn read(input_dir: &str) -> Result<()> {
@@ -34,33 +35,53 @@ pub fn read(input_dir: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn parse_all(input_dir: &str) -> Result<Dump> {
-    let readers = read_dir(input_dir)?
+/// Parse all IRR DB files in `input_dir` into shards in `output_dir`.
+/// When some DBs have the same keys, any value could be used.
+pub fn parse_all(input_dir: &str, output_dir: &str, ceiling: usize) -> Result<()> {
+    let writer = Mutex::new(ShardWriter::new(output_dir, ceiling)?);
+    stream_dir(input_dir, &writer)?;
+    finish(writer)
+}
+
+/// Parse the IRR DB files in `priority_dir` and `backup_dir` into shards in
+/// `output_dir`, with objects in the former overriding those in the latter.
+pub fn parse_priority(
+    priority_dir: &str,
+    backup_dir: &str,
+    output_dir: &str,
+    ceiling: usize,
+) -> Result<()> {
+    let writer = Mutex::new(ShardWriter::new(output_dir, ceiling)?);
+    stream_dir(backup_dir, &writer)?;
+    writer.lock().expect("Not poisoned").next_generation()?;
+    stream_dir(priority_dir, &writer)?;
+    finish(writer)
+}
+
+/// Stream the IRR DB files in `input_dir` to `writer` in parallel,
+/// opening each file only once it is its turn.
+fn stream_dir(input_dir: &str, writer: &Mutex<ShardWriter>) -> Result<()> {
+    debug!("Starting to read and parse {input_dir}.");
+    read_dir(input_dir)?
         .par_bridge()
-        .map(|entry| {
-            let path = entry?.path();
-            let encoding = detect_file_encoding(&path)?;
-            let decoder = DecodeReaderBytesBuilder::new()
-                .encoding(Some(encoding))
-                .build(File::open(path)?);
-            let reader = BufReader::new(decoder);
-            Ok(reader)
-        })
-        .collect::<Result<Vec<_>>>()?;
-
-    debug!("Starting to read and parse.");
-    parse_dbs(readers)
-}
-pub fn parse_priority(priority_dir: &str, backup_dir: &str, output_dir: &str) -> Result<()> {
-    let priority = parse_all(priority_dir)?;
-    let backup = parse_all(backup_dir)?;
-    let parsed = backup.merge(priority);
-    parsed.log_count();
+        .try_for_each(|entry| stream_db(open_db(entry?.path())?, writer))
+}
 
-    debug!("Starting to write the parsed dump.");
-    parsed.pal_write(output_dir)?;
-    debug!("Wrote the parsed dump.");
+fn open_db<P>(path: P) -> Result<BufReader<impl Read>>
+where
+    P: AsRef<Path>,
+{
+    let encoding = detect_file_encoding(&path)?;
+    let decoder = DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .build(File::open(path)?);
+    Ok(BufReader::new(decoder))
+}
 
+fn finish(writer: Mutex<ShardWriter>) -> Result<()> {
+    debug!("Finishing the parsed dump.");
+    writer.into_inner().expect("Not poisoned").finish()?;
+    debug!("Wrote the parsed dump.");
     Ok(())
 }
 
@@ -100,39 +121,500 @@ where
     }
 }
 
-pub fn report(parsed_dir: &str, mrt_dir: &str) -> Result<()> {
+pub fn infer_rel(parsed_dir: &str, output_file: &str) -> Result<()> {
+    let parsed = Dump::pal_read(parsed_dir)?;
+    parsed.log_count();
+
+    let inferences = infer_relationships(&parsed);
+    let n_contradictory = inferences
+        .values()
+        .filter(|inference| matches!(inference, PairInference::Contradictory { .. }))
+        .count();
+    let n_one_sided = inferences
+        .values()
+        .filter(|inference| matches!(inference, PairInference::OneSided { .. }))
+        .count();
+    println!(
+        "Inferred {} AS pairs, {n_one_sided} one-sided, {n_contradictory} contradictory.",
+        inferences.len()
+    );
+
+    write_caida(&inferences, BufWriter::new(File::create(output_file)?))?;
+    debug!("Wrote inferred relationships to {output_file}.");
+    Ok(())
+}
+
+pub fn bilateral(parsed_dir: &str, output_file: &str) -> Result<()> {
     let parsed = Dump::pal_read(parsed_dir)?;
     parsed.log_count();
 
     let query = QueryDump::from_dump(parsed);
     debug!("Converted Dump to QueryDump");
 
-    let mut bgp_lines = parse_mrt(mrt_dir)?;
-    debug!("Read {} lines from {mrt_dir}", bgp_lines.len());
+    let mismatches = check_bilateral(&query);
+    println!("Found {} bilateral mismatches.", mismatches.len());
 
-    const SIZE: usize = 0x10000;
-    bgp_lines[..SIZE].par_iter_mut().for_each(|line| {
-        line.compare.verbosity = Verbosity {
-            stop_at_first: false,
-            show_skips: true,
-            show_success: true,
-            ..Verbosity::default()
-        };
-        line.check(&query);
-    });
-    debug!("Generated {SIZE} reports");
-
-    let n_error: usize = bgp_lines[..SIZE]
-        .par_iter()
-        .map(|line| {
-            if line.report.as_ref().unwrap().is_empty() {
-                0
-            } else {
-                1
+    let mut writer = BufWriter::new(File::create(output_file)?);
+    for mismatch in &mismatches {
+        serde_json::to_writer(&mut writer, mismatch)?;
+        writeln!(writer)?;
+    }
+    debug!("Wrote bilateral mismatches to {output_file}.");
+    Ok(())
+}
+
+pub fn simulate(
+    parsed_dir: &str,
+    prefix: IpNet,
+    origin: crate::parse::Asn,
+    simulation: Simulation,
+) -> Result<()> {
+    let parsed = Dump::pal_read(parsed_dir)?;
+    parsed.log_count();
+
+    let query = QueryDump::from_dump(parsed);
+    debug!("Converted Dump to QueryDump");
+
+    let propagation = simulation.propagate(&query, prefix, origin);
+    for path in &propagation.paths {
+        let path: Vec<_> = path.iter().map(ToString::to_string).collect();
+        println!("{}", path.join(" "));
+    }
+    println!(
+        "{} permitted paths reaching {} ASes{}.",
+        propagation.paths.len(),
+        propagation.reached().len(),
+        if propagation.truncated {
+            ", truncated"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Number of routes checked at a time by [`report`].
+pub const REPORT_CHUNK_SIZE: usize = 0x10000;
+
+/// Options for [`report`].
+#[derive(Clone, Debug)]
+pub struct ReportOptions<'a> {
+    /// VRP file to validate route origins against.
+    pub vrp_path: Option<&'a str>,
+    /// CAIDA AS relationship file to check route leaks against.
+    pub as_rel_path: Option<&'a str>,
+    /// ASPA file to verify AS paths against, and the verification direction.
+    pub aspa: Option<(&'a str, AspaDirection)>,
+    /// File to write a [`ReportRecord`] for each route to, and its format.
+    pub output: Option<(&'a str, RecordFormat)>,
+    /// Directory to write [`Stats`] tables to, and their format.
+    /// Routes are then checked with [`Stats::verbosity`].
+    pub stats: Option<(&'a str, StatsFormat)>,
+    /// Only check this many routes from the start of the input.
+    pub sample_size: Option<usize>,
+    pub verbosity: Verbosity,
+    /// How to normalize AS paths before checking them.
+    pub normalizer: Normalizer,
+    /// Read the input as JSON updates in this format instead of MRT.
+    pub json: Option<JsonFormat>,
+}
+
+impl Default for ReportOptions<'_> {
+    fn default() -> Self {
+        Self {
+            vrp_path: None,
+            as_rel_path: None,
+            aspa: None,
+            output: None,
+            stats: None,
+            sample_size: None,
+            json: None,
+            normalizer: Normalizer::default(),
+            verbosity: Verbosity {
+                stop_at_first: false,
+                show_skips: true,
+                show_success: true,
+                ..Verbosity::default()
+            },
+        }
+    }
+}
+
+/// Check all routes in the MRT file, or JSON updates file, at `input`
+/// against the parsed dump in `parsed_dir`, [`REPORT_CHUNK_SIZE`] routes
+/// at a time, as configured in `options`. `input` may be `-` for JSON
+/// updates from stdin.
+/// The next chunk is read while the current one is being checked,
+/// so at most three chunks are in memory.
+pub fn report(parsed_dir: &str, input: &str, options: ReportOptions) -> Result<()> {
+    let ReportOptions {
+        vrp_path,
+        as_rel_path,
+        aspa,
+        output,
+        stats,
+        sample_size,
+        verbosity,
+        normalizer,
+        json,
+    } = options;
+    let parsed = Dump::pal_read(parsed_dir)?;
+    parsed.log_count();
+
+    let query = QueryDump::from_dump(parsed);
+    debug!("Converted Dump to QueryDump");
+    let (aggregate, verbosity) = match stats {
+        Some(_) => (Some(Stats::new(&query)), Stats::verbosity(verbosity)?),
+        None => (None, verbosity),
+    };
+    let cache = PairCache::new(&query, verbosity);
+
+    let vrps = vrp_path.map(Vrps::from_file).transpose()?;
+    let rels = as_rel_path.map(AsRelDb::from_file).transpose()?;
+    let aspas = aspa
+        .map(|(path, direction)| Aspas::from_file(path).map(|aspas| (aspas, direction)))
+        .transpose()?;
+    let mut writer = output
+        .map(|(path, format)| RecordWriter::create(path, format))
+        .transpose()?;
+
+    let (sender, receiver) = sync_channel::<Vec<Line>>(1);
+    let mut n_route = 0;
+    let mut n_bad = 0;
+    let mut n_allowed_leak = 0;
+    let mut n_aspa_invalid = 0;
+    let mut rpki_counts = BTreeMap::new();
+    let n_skipped = thread::scope(|scope| {
+        let reader = scope.spawn(move || -> Result<usize> {
+            let (mut chunk, mut n_skipped) = (Vec::with_capacity(REPORT_CHUNK_SIZE), 0);
+            let lines: Box<dyn Iterator<Item = Result<Line>>> = match json {
+                None => Box::new(stream_lines(read_mrt(input)?, normalizer)),
+                Some(format) => Box::new(stream_json_lines(open_input(input)?, format, normalizer)),
+            };
+            let lines = skip_bad_lines(lines, &mut n_skipped);
+            for line in lines.take(sample_size.unwrap_or(usize::MAX)) {
+                chunk.push(line?);
+                if chunk.len() == REPORT_CHUNK_SIZE {
+                    let full = mem::replace(&mut chunk, Vec::with_capacity(REPORT_CHUNK_SIZE));
+                    if sender.send(full).is_err() {
+                        break; // The checking side failed.
+                    }
+                }
             }
-        })
-        .sum();
-    println!("{n_error} errors reported in {SIZE} routes.");
+            if !chunk.is_empty() {
+                _ = sender.send(chunk);
+            }
+            Ok(n_skipped)
+        });
+
+        for mut lines in receiver {
+            let n_distinct = check_dedup(&mut lines, |line| {
+                line.compare.verbosity = verbosity;
+                line.check_cached(&cache);
+                if let Some(vrps) = &vrps {
+                    line.check_rpki(vrps);
+                }
+                if let Some(rels) = &rels {
+                    line.check_leaks(rels);
+                }
+                if let Some((aspas, direction)) = &aspas {
+                    line.check_aspa(aspas, *direction);
+                }
+            });
+            debug!(
+                "Checked {n_distinct} distinct routes for {} vantage point routes",
+                lines.len()
+            );
+
+            n_route += lines.len();
+            n_bad += lines
+                .par_iter()
+                .filter(|line| Summary::of(line.report.as_ref().unwrap()) == Summary::Bad)
+                .count();
+            count_rpki(&lines, &mut rpki_counts);
+            n_allowed_leak += lines
+                .par_iter()
+                .filter(|line| line.is_rpsl_allowed_leak())
+                .count();
+            n_aspa_invalid += lines
+                .par_iter()
+                .filter(|line| line.is_aspa_invalid())
+                .count();
+            if let Some(aggregate) = &aggregate {
+                lines.par_iter().for_each(|line| {
+                    let reports = line.report.as_ref().unwrap();
+                    aggregate.record(&line.compare, reports);
+                    aggregate.record_vantage_point(line.collector, &line.compare.prefix, reports);
+                });
+            }
+            if let Some(writer) = &mut writer {
+                let records = lines
+                    .par_iter()
+                    .map(ReportRecord::from_line)
+                    .collect::<Result<Vec<_>>>()?;
+                writer.write_batch(&records)?;
+            }
+            debug!("Generated {n_route} reports, pair cache {}", cache.stats());
+        }
+        reader.join().expect("Route reader panicked")
+    })?;
+    if let Some(writer) = writer {
+        writer.finish()?;
+    }
+    if let (Some(aggregate), Some((stats_dir, format))) = (aggregate, stats) {
+        aggregate.write_to_dir(stats_dir, format)?;
+        debug!("Wrote statistics to {stats_dir}.");
+    }
+
+    println!("{n_bad} routes with errors in {n_route} routes.");
+    if n_skipped > 0 {
+        println!("Skipped {n_skipped} lines that are not routes or fail to parse.");
+    }
+    println!("Pair cache: {}.", cache.stats());
+    if vrps.is_some() {
+        print_rpki_cross_tab(&rpki_counts);
+    }
+    if rels.is_some() {
+        println!("{n_allowed_leak} routes allowed by RPSL are leaks in {n_route} routes.");
+    }
+    if aspas.is_some() {
+        println!("{n_aspa_invalid} routes have ASPA invalid paths in {n_route} routes.");
+    }
 
     Ok(())
 }
+
+/// Where [`bmp`] receives BMP messages from.
+#[derive(Clone, Copy, Debug)]
+pub enum BmpSource<'a> {
+    /// Address to accept connections from routers on.
+    Listen(&'a str),
+    /// File of BMP messages captured from a connection.
+    Replay(&'a str),
+}
+
+/// Options for [`bmp`].
+#[derive(Clone, Debug)]
+pub struct BmpOptions<'a> {
+    pub source: BmpSource<'a>,
+    /// File to write a [`ReportRecord`] for each route to, and its format.
+    /// JSON Lines to stdout if `None`.
+    pub output: Option<(&'a str, RecordFormat)>,
+    /// Directory to write [`Stats`] tables to, and their format.
+    /// Routes are then checked with [`Stats::verbosity`].
+    pub stats: Option<(&'a str, StatsFormat)>,
+    /// Interval to rewrite the [`Stats`] tables at while listening.
+    pub stats_interval: Duration,
+    pub verbosity: Verbosity,
+    /// How to normalize AS paths before checking them.
+    pub normalizer: Normalizer,
+}
+
+/// Check the routes in BMP Route Monitoring messages as they arrive,
+/// writing a [`ReportRecord`] for each and keeping [`Stats`] live.
+pub fn bmp(parsed_dir: &str, options: BmpOptions) -> Result<()> {
+    let BmpOptions {
+        source,
+        output,
+        stats,
+        stats_interval,
+        verbosity,
+        normalizer,
+    } = options;
+    if let (BmpSource::Listen(_), Some((path, RecordFormat::Parquet))) = (source, output) {
+        bail!("{path}: Parquet is only readable once finished, use JSON Lines or CSV to listen");
+    }
+    let verbosity = match stats {
+        Some(_) => Stats::verbosity(verbosity)?,
+        None => verbosity,
+    };
+    let parsed = Dump::pal_read(parsed_dir)?;
+    parsed.log_count();
+    let query = QueryDump::from_dump(parsed);
+    let aggregate = Stats::new(&query);
+    let writer = output
+        .map(|(path, format)| RecordWriter::create(path, format))
+        .transpose()?;
+    let writer = Mutex::new(writer);
+    let write_stats = || -> Result<()> {
+        if let Some((stats_dir, format)) = stats {
+            aggregate.write_to_dir(stats_dir, format)?;
+            debug!("Wrote statistics to {stats_dir}.");
+        }
+        Ok(())
+    };
+
+    match source {
+        BmpSource::Listen(address) => {
+            let listener = TcpListener::bind(address)?;
+            debug!("Listening for BMP on {address}.");
+            thread::scope(|scope| {
+                if stats.is_some() {
+                    scope.spawn(|| loop {
+                        thread::sleep(stats_interval);
+                        if let Err(err) = write_stats() {
+                            error!("Failed to write statistics: {err:#}.");
+                        }
+                    });
+                }
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            warn!("Failed to accept a BMP connection: {err}.");
+                            continue;
+                        }
+                    };
+                    let (query, aggregate, writer) = (&query, &aggregate, &writer);
+                    scope.spawn(move || {
+                        let peer = stream.peer_addr();
+                        debug!("BMP connection from {peer:?}.");
+                        let result = check_bmp(
+                            BufReader::new(stream),
+                            query,
+                            verbosity,
+                            &normalizer,
+                            aggregate,
+                            writer,
+                        );
+                        match result {
+                            Ok(counts) => debug!("{peer:?} closed after {counts}."),
+                            Err(err) => error!("BMP connection from {peer:?}: {err:#}."),
+                        }
+                    });
+                }
+            });
+        }
+        BmpSource::Replay(path) => {
+            let reader = BufReader::new(File::open(path)?);
+            let counts = check_bmp(reader, &query, verbosity, &normalizer, &aggregate, &writer)?;
+            debug!("Checked {counts} replayed from {path}.");
+            if let Some(writer) = writer.into_inner().expect("Not poisoned") {
+                writer.finish()?;
+            }
+            write_stats()?;
+        }
+    }
+    Ok(())
+}
+
+/// Check the routes in the BMP stream `reader` and write their records
+/// to `writer`, or stdout if it is `None`.
+/// Messages that fail to decode are skipped, but failing to read the next
+/// message ends the stream.
+pub fn check_bmp<R: Read>(
+    reader: R,
+    query: &QueryDump,
+    verbosity: Verbosity,
+    normalizer: &Normalizer,
+    aggregate: &Stats,
+    writer: &Mutex<Option<RecordWriter>>,
+) -> Result<BmpCounts> {
+    let mut counts = BmpCounts::default();
+    let mut reader = BmpReader::new(reader);
+    while let Some(message) = reader.next_bytes()? {
+        let message = match BmpMessage::decode(&message) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Skipping BMP message: {err:#}.");
+                counts.skipped += 1;
+                continue;
+            }
+        };
+        let (peer, update) = match message {
+            BmpMessage::RouteMonitoring { peer, update } => (peer, update),
+            BmpMessage::PeerUp(peer) => {
+                debug!("Peer AS{} at {} up.", peer.asn, peer.address);
+                continue;
+            }
+            BmpMessage::PeerDown(peer) => {
+                debug!("Peer AS{} at {} down.", peer.asn, peer.address);
+                continue;
+            }
+            BmpMessage::Other(_) => continue,
+        };
+        let records: Vec<_> = update
+            .compares(normalizer)
+            .into_par_iter()
+            .map(|compare| {
+                let compare = compare.verbosity(verbosity);
+                let reports = compare.check(query);
+                aggregate.record(&compare, &reports);
+                aggregate.record_vantage_point(peer.collector(), &compare.prefix, &reports);
+                let record =
+                    ReportRecord::new(compare.prefix, &update.as_path, peer.collector(), reports);
+                ReportRecord {
+                    timestamp: peer.timestamp.into(),
+                    ..record
+                }
+            })
+            .collect();
+        counts.routes += records.len();
+        if records.is_empty() {
+            continue;
+        }
+        let mut writer = writer.lock().expect("Not poisoned");
+        match writer.as_mut() {
+            Some(writer) => {
+                writer.write_batch(&records)?;
+                writer.flush()?;
+            }
+            None => {
+                let mut stdout = stdout().lock();
+                for record in &records {
+                    serde_json::to_writer(&mut stdout, record)?;
+                    writeln!(stdout)?;
+                }
+                stdout.flush()?;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Numbers of routes checked and messages skipped by [`check_bmp`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BmpCounts {
+    pub routes: usize,
+    pub skipped: usize,
+}
+
+impl fmt::Display for BmpCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} routes, skipping {} undecodable messages",
+            self.routes, self.skipped
+        )
+    }
+}
+
+/// Buffered reader of the file at `path`, or stdin if it is `-`.
+fn open_input(path: &str) -> Result<Box<dyn BufRead>> {
+    Ok(match path {
+        "-" => Box::new(stdin().lock()),
+        path => Box::new(BufReader::new(File::open(path)?)),
+    })
+}
+
+/// Count the routes in `lines` with and without bad RPSL reports
+/// for each RPKI state into `counts`.
+fn count_rpki(lines: &[Line], counts: &mut BTreeMap<(RpkiState, bool), usize>) {
+    for line in lines {
+        if let (Some(state), Some(reports)) = (line.rpki, &line.report) {
+            let bad = reports.iter().any(Report::is_bad_rpsl);
+            *counts.entry((state, bad)).or_default() += 1;
+        }
+    }
+}
+
+/// Print the number of routes with and without bad RPSL reports
+/// for each RPKI state, as counted by [`count_rpki`].
+fn print_rpki_cross_tab(counts: &BTreeMap<(RpkiState, bool), usize>) {
+    println!("RPKI state\tRPSL ok\tRPSL bad");
+    for state in [RpkiState::Valid, RpkiState::Invalid, RpkiState::NotFound] {
+        let ok = counts.get(&(state, false)).unwrap_or(&0);
+        let bad = counts.get(&(state, true)).unwrap_or(&0);
+        println!("{state:?}\t{ok}\t{bad}");
+    }
+}
@@ -1,7 +1,8 @@
 use std::{
     io::{BufReader, Read},
     process::ChildStdout,
-    sync::mpsc::Sender,
+    sync::{mpsc::SyncSender, Mutex},
+    thread,
 };
 
 use lazy_regex::regex;
@@ -9,8 +10,8 @@ use lazy_regex::regex;
 use super::{
     lex::*,
     parse::{
-        dump::{self, merge_dumps},
-        parse_lexed,
+        lex::{parse_aut_num_name, parse_lexed_as_set, parse_lexed_route_set},
+        ShardWriter,
     },
     *,
 };
@@ -47,18 +48,16 @@ pub fn read_line_wait(reader: &mut BufReader<ChildStdout>) -> Result<String> {
 
 pub fn parse_object(
     obj: RPSLObject,
-    as_sets: &mut Vec<AsOrRouteSet>,
-    route_sets: &mut Vec<AsOrRouteSet>,
-    send_aut_num: &mut Sender<RPSLObject>,
-    send_peering_set: &mut Sender<RPSLObject>,
-    send_filter_set: &mut Sender<RPSLObject>,
-    as_routes: &mut BTreeMap<String, Vec<String>>,
+    writer: &Mutex<ShardWriter>,
+    send_aut_num: &SyncSender<RPSLObject>,
+    send_peering_set: &SyncSender<RPSLObject>,
+    send_filter_set: &SyncSender<RPSLObject>,
 ) -> Result<()> {
     match obj.class.as_str() {
         "aut-num" => send_aut_num.send(obj)?,
-        "as-set" => parse_as_set(obj, as_sets),
-        "route" | "route6" => parse_route(obj, as_routes),
-        "route-set" => parse_route_set(obj, route_sets),
+        "as-set" => parse_as_set(obj, writer)?,
+        "route" | "route6" => parse_route(obj, writer)?,
+        "route-set" => parse_route_set(obj, writer)?,
         "filter-set" => send_filter_set.send(obj)?,
         "peering-set" => send_peering_set.send(obj)?,
         _ => (),
@@ -66,97 +65,93 @@ pub fn parse_object(
     Ok(())
 }
 
-fn parse_as_set(obj: RPSLObject, as_sets: &mut Vec<AsOrRouteSet>) {
+fn parse_as_set(obj: RPSLObject, writer: &Mutex<ShardWriter>) -> Result<()> {
     let members = gather_members(&obj.body);
-    as_sets.push(AsOrRouteSet::new(obj.name, obj.body, members));
-    match as_sets.len() {
-        l if l % 0xFF == 0 => debug!("Parsed {l} as_sets."),
-        _ => (),
+    match parse_lexed_as_set(AsOrRouteSet::new(obj.name, obj.body, members)) {
+        Ok((name, as_set)) => writer
+            .lock()
+            .expect("Not poisoned")
+            .push_as_set(name, as_set),
+        Err(e) => {
+            error!("{e:#}");
+            Ok(())
+        }
     }
 }
 
-fn parse_route(obj: RPSLObject, as_routes: &mut BTreeMap<String, Vec<String>>) {
+fn parse_route(obj: RPSLObject, writer: &Mutex<ShardWriter>) -> Result<()> {
     for RpslExpr {
         key,
         expr, /*AS*/
     } in expressions(lines_continued(obj.body.lines()))
     {
         if key == "origin" {
-            as_routes
-                .entry(expr.to_uppercase())
-                .or_default()
-                .push(obj.name /*The route*/);
-            return;
+            let parsed = parse_aut_num_name(&expr)
+                .and_then(|num| Ok((num, obj.name.parse()? /*The route*/)));
+            return match parsed {
+                Ok((num, route)) => writer.lock().expect("Not poisoned").push_route(num, route),
+                Err(e) => {
+                    error!("Parsing route {} of {expr}: {e:#}.", obj.name);
+                    Ok(())
+                }
+            };
         }
     }
     error!("Route object {} does not have an `origin` field.", obj.name);
+    Ok(())
 }
 
-fn parse_route_set(obj: RPSLObject, route_sets: &mut Vec<AsOrRouteSet>) {
+fn parse_route_set(obj: RPSLObject, writer: &Mutex<ShardWriter>) -> Result<()> {
     let members = gather_members(&obj.body);
-    route_sets.push(AsOrRouteSet::new(obj.name, obj.body, members));
-    match route_sets.len() {
-        l if l % 0xFF == 0 => debug!("Parsed {l} route_sets."),
-        _ => (),
+    match parse_lexed_route_set(AsOrRouteSet::new(obj.name, obj.body, members)) {
+        Ok((name, route_set)) => writer
+            .lock()
+            .expect("Not poisoned")
+            .push_route_set(name, route_set),
+        Err(e) => {
+            error!("{e:#}");
+            Ok(())
+        }
     }
 }
 
 const ONE_MEBIBYTE: usize = 1024 * 1024;
 
-pub fn read_db<R>(db: BufReader<R>) -> Result<Dump>
+/// Lex `db` and parse its objects, pushing each to `writer` once parsed.
+/// aut-num, peering-set and filter-set objects are lexed by `pypy3` workers
+/// while the rest of `db` is read.
+pub fn stream_db<R>(db: BufReader<R>, writer: &Mutex<ShardWriter>) -> Result<()>
 where
     R: Read,
 {
-    let (mut as_sets, mut route_sets, mut as_routes) = (Vec::new(), Vec::new(), BTreeMap::new());
-    let (mut send_aut_num, aut_num_worker) = spawn_aut_num_worker()?;
-    let (mut send_peering_set, peering_set_worker) = spawn_peering_set_worker()?;
-    let (mut send_filter_set, filter_set_worker) = spawn_filter_set_worker()?;
-
-    for obj in rpsl_objects(io_wrapper_lines(db)) {
-        if obj.body.len() > ONE_MEBIBYTE {
-            // <https://github.com/SichangHe/parse_rpsl_policy/issues/6#issuecomment-1566121009>
-            warn!(
-                "Skipping {} object `{}` with body larger than 1MiB.",
-                obj.class, obj.name
-            );
-            continue;
-        }
-
-        parse_object(
-            obj,
-            &mut as_sets,
-            &mut route_sets,
-            &mut send_aut_num,
-            &mut send_peering_set,
-            &mut send_filter_set,
-            &mut as_routes,
-        )?;
-    }
-
-    drop((send_aut_num, send_peering_set, send_filter_set));
-    let aut_nums = aut_num_worker.join().unwrap()?;
-    let peering_sets = peering_set_worker.join().unwrap()?;
-    let filter_sets = filter_set_worker.join().unwrap()?;
-
-    Ok(Dump {
-        aut_nums,
-        as_sets,
-        route_sets,
-        peering_sets,
-        filter_sets,
-        as_routes,
+    thread::scope(|scope| {
+        let (send_aut_num, aut_num_worker) = spawn_aut_num_worker(scope, writer);
+        let (send_peering_set, peering_set_worker) = spawn_peering_set_worker(scope, writer);
+        let (send_filter_set, filter_set_worker) = spawn_filter_set_worker(scope, writer);
+
+        let read = rpsl_objects(io_wrapper_lines(db)).try_for_each(|obj| {
+            if obj.body.len() > ONE_MEBIBYTE {
+                // <https://github.com/SichangHe/parse_rpsl_policy/issues/6#issuecomment-1566121009>
+                warn!(
+                    "Skipping {} object `{}` with body larger than 1MiB.",
+                    obj.class, obj.name
+                );
+                return Ok(());
+            }
+            parse_object(
+                obj,
+                writer,
+                &send_aut_num,
+                &send_peering_set,
+                &send_filter_set,
+            )
+        });
+
+        // A failed worker makes `read` fail to send to it, so check workers first.
+        drop((send_aut_num, send_peering_set, send_filter_set));
+        aut_num_worker.join().unwrap()?;
+        peering_set_worker.join().unwrap()?;
+        filter_set_worker.join().unwrap()?;
+        read
     })
 }
-
-/// When some DBs have the same keys, any value could be used.
-pub fn parse_dbs<I, R>(dbs: I) -> Result<dump::Dump>
-where
-    I: IntoParallelIterator<Item = BufReader<R>>,
-    R: Read,
-{
-    let dumps = dbs
-        .into_par_iter()
-        .map(|db| read_db(db).map(parse_lexed))
-        .collect::<Result<_>>()?;
-    Ok(merge_dumps(dumps))
-}
@@ -2,105 +2,146 @@
 
 use std::{
     process::Command,
-    sync::mpsc::{channel, Receiver, Sender},
-    thread::{spawn, JoinHandle},
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Mutex,
+    },
+    thread::{Scope, ScopedJoinHandle},
 };
 
-use crate::{cmd::PipedChild, serialize::from_str};
+use crate::{
+    cmd::PipedChild,
+    parse::{
+        lex::{parse_lexed_aut_num, parse_lexed_filter_set, parse_lexed_peering_set},
+        ShardWriter,
+    },
+    serialize::from_str,
+};
 
 use super::*;
 
-pub fn spawn_aut_num_worker() -> Result<(Sender<RPSLObject>, JoinHandle<Result<Vec<AutNum>>>)> {
-    let (send, recv) = channel();
-    let worker = spawn(|| {
-        aut_num_worker(recv).map_err(|e| {
+/// Objects queued for each worker before the lexer waits for it.
+const WORKER_QUEUE: usize = 0x100;
+
+pub fn spawn_aut_num_worker<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    writer: &'scope Mutex<ShardWriter>,
+) -> (SyncSender<RPSLObject>, ScopedJoinHandle<'scope, Result<()>>) {
+    let (send, recv) = sync_channel(WORKER_QUEUE);
+    let worker = scope.spawn(|| {
+        aut_num_worker(recv, writer).map_err(|e| {
             error!("aut_num_worker: {e:#}.");
             e
         })
     });
-    Ok((send, worker))
+    (send, worker)
 }
 
-fn aut_num_worker(recv: Receiver<RPSLObject>) -> Result<Vec<AutNum>> {
+fn aut_num_worker(recv: Receiver<RPSLObject>, writer: &Mutex<ShardWriter>) -> Result<()> {
     let mut aut_num_child =
         PipedChild::new(Command::new("pypy3").args(["-m", "rpsl_policy.aut_num"]))?;
 
-    let mut aut_nums = Vec::new();
+    let mut count = 0;
     while let Ok(obj) = recv.recv() {
         obj.write_to(&mut aut_num_child.stdin)?;
         let line = read_line_wait(&mut aut_num_child.stdout)?;
         let mut aut_num: AutNum = from_str(&line)?;
         (aut_num.name, aut_num.body) = (obj.name, obj.body);
-        aut_nums.push(aut_num);
-        match aut_nums.len() {
+        match parse_lexed_aut_num(aut_num) {
+            Ok((num, aut_num)) => writer
+                .lock()
+                .expect("Not poisoned")
+                .push_aut_num(num, aut_num)?,
+            Err(e) => error!("{e:#}"),
+        }
+        count += 1;
+        match count {
             l if l % 0xFFF == 0 => debug!("Parsed {l} aut_nums."),
             _ => (),
         }
     }
     warn!("aut_num_worker exiting normally.");
-    Ok(aut_nums)
+    Ok(())
 }
 
-pub fn spawn_peering_set_worker(
-) -> Result<(Sender<RPSLObject>, JoinHandle<Result<Vec<PeeringSet>>>)> {
-    let (send, recv) = channel();
-    let worker = spawn(|| {
-        peering_set_worker(recv).map_err(|e| {
+pub fn spawn_peering_set_worker<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    writer: &'scope Mutex<ShardWriter>,
+) -> (SyncSender<RPSLObject>, ScopedJoinHandle<'scope, Result<()>>) {
+    let (send, recv) = sync_channel(WORKER_QUEUE);
+    let worker = scope.spawn(|| {
+        peering_set_worker(recv, writer).map_err(|e| {
             error!("peering_set_worker: {e:#}.");
             e
         })
     });
-    Ok((send, worker))
+    (send, worker)
 }
 
-fn peering_set_worker(recv: Receiver<RPSLObject>) -> Result<Vec<PeeringSet>> {
+fn peering_set_worker(recv: Receiver<RPSLObject>, writer: &Mutex<ShardWriter>) -> Result<()> {
     let mut peering_set_child =
         PipedChild::new(Command::new("pypy3").args(["-m", "rpsl_policy.peering_set"]))?;
 
-    let mut peering_sets = Vec::new();
+    let mut count = 0;
     while let Ok(obj) = recv.recv() {
         obj.write_to(&mut peering_set_child.stdin)?;
         let line = read_line_wait(&mut peering_set_child.stdout)?;
         let mut peering_set: PeeringSet = from_str(&line)?;
         (peering_set.name, peering_set.body) = (obj.name, obj.body);
-        peering_sets.push(peering_set);
-        match peering_sets.len() {
+        match parse_lexed_peering_set(peering_set) {
+            Ok((name, peering_set)) => writer
+                .lock()
+                .expect("Not poisoned")
+                .push_peering_set(name, peering_set)?,
+            Err(e) => error!("{e:#}"),
+        }
+        count += 1;
+        match count {
             l if l % 0xFF == 0 => debug!("Parsed {l} peering_sets."),
             _ => (),
         }
     }
     warn!("peering_set_worker exiting normally.");
-    Ok(peering_sets)
+    Ok(())
 }
 
-pub fn spawn_filter_set_worker() -> Result<(Sender<RPSLObject>, JoinHandle<Result<Vec<FilterSet>>>)>
-{
-    let (send, recv) = channel();
-    let worker = spawn(|| {
-        filter_set_worker(recv).map_err(|e| {
+pub fn spawn_filter_set_worker<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    writer: &'scope Mutex<ShardWriter>,
+) -> (SyncSender<RPSLObject>, ScopedJoinHandle<'scope, Result<()>>) {
+    let (send, recv) = sync_channel(WORKER_QUEUE);
+    let worker = scope.spawn(|| {
+        filter_set_worker(recv, writer).map_err(|e| {
             error!("filter_set_worker: {e:#}.");
             e
         })
     });
-    Ok((send, worker))
+    (send, worker)
 }
 
-fn filter_set_worker(recv: Receiver<RPSLObject>) -> Result<Vec<FilterSet>> {
+fn filter_set_worker(recv: Receiver<RPSLObject>, writer: &Mutex<ShardWriter>) -> Result<()> {
     let mut filter_set_child =
         PipedChild::new(Command::new("pypy3").args(["-m", "rpsl_policy.filter_set"]))?;
 
-    let mut filter_sets = Vec::new();
+    let mut count = 0;
     while let Ok(obj) = recv.recv() {
         obj.write_to(&mut filter_set_child.stdin)?;
         let line = read_line_wait(&mut filter_set_child.stdout)?;
         let mut filter_set: FilterSet = from_str(&line)?;
         (filter_set.name, filter_set.body) = (obj.name, obj.body);
-        filter_sets.push(filter_set);
-        match filter_sets.len() {
+        match parse_lexed_filter_set(filter_set) {
+            Ok((name, filter_set)) => writer
+                .lock()
+                .expect("Not poisoned")
+                .push_filter_set(name, filter_set)?,
+            Err(e) => error!("{e:#}"),
+        }
+        count += 1;
+        match count {
             l if l % 0xF == 0 => debug!("Parsed {l} filter_sets."),
             _ => (),
         }
     }
     warn!("filter_set_worker exiting normally.");
-    Ok(filter_sets)
+    Ok(())
 }
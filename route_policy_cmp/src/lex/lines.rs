@@ -67,15 +67,15 @@ where
     }
 }
 
-pub fn dedup_whitespace(string: &str) -> Cow<str> {
+pub fn dedup_whitespace(string: &str) -> Cow<'_, str> {
     regex_replace_all!(r"\s+", string, |_| " ")
 }
 
-pub fn cleanup_right_whitespace(string: &str) -> Cow<str> {
+pub fn cleanup_right_whitespace(string: &str) -> Cow<'_, str> {
     dedup_whitespace(string.trim_end())
 }
 
-pub fn cleanup_whitespace(string: &str) -> Cow<str> {
+pub fn cleanup_whitespace(string: &str) -> Cow<'_, str> {
     dedup_whitespace(string.trim())
 }
 
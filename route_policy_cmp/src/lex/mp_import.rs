@@ -21,6 +21,10 @@ pub struct Entry {
     pub mp_peerings: Vec<PeeringAction>,
     #[serde(default)]
     pub mp_filter: Filter,
+    /// Index of the attribute this entry comes from among the continued
+    /// lines of the object body.
+    #[serde(default)]
+    pub index: Option<usize>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
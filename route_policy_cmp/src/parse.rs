@@ -2,28 +2,37 @@ use super::*;
 
 pub mod action;
 pub mod address_prefix;
+pub mod asn;
 pub mod aut_num;
 pub mod aut_sys;
 pub mod dump;
 pub mod filter;
 pub mod lex;
 pub mod mp_import;
+pub mod name;
 pub mod peering;
 pub mod router_expr;
 pub mod set;
+pub mod shard;
 
 pub use {
     action::{parse_actions, Actions},
     address_prefix::{match_ips, AddrPfxRange, RangeOperator},
+    asn::{Asn, AS_TRANS},
     aut_num::AutNum,
     aut_sys::{is_as_set, parse_as_name, AsName},
     dump::Dump,
     filter::{is_filter_set, parse_filter, Filter},
     lex::parse_lexed,
     mp_import::{parse_imports, Casts, Entry, Versions},
+    name::{SetName, Symbol},
     peering::{
         is_peering_set, parse_mp_peering, parse_mp_peerings, AsExpr, Peering, PeeringAction,
     },
     router_expr::{parse_router_expr, RouterExpr},
-    set::{is_route_set_name, AsSet, FilterSet, PeeringSet, RouteSet, RouteSetMember},
+    set::{
+        is_any_as_set, is_any_route_set, is_route_set_name, is_set_name, parent_set_name, AsSet,
+        FilterSet, PeeringSet, RouteSet, RouteSetMember,
+    },
+    shard::{ShardWriter, MEMORY_CEILING},
 };
@@ -23,6 +23,12 @@ impl std::fmt::Debug for AddrPfxRange {
     }
 }
 
+impl std::fmt::Display for AddrPfxRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.address_prefix, self.range_operator)
+    }
+}
+
 impl FromStr for AddrPfxRange {
     type Err = anyhow::Error;
 
@@ -0,0 +1,94 @@
+use std::{fmt::Display, str::FromStr};
+
+use lazy_regex::regex_captures;
+
+use super::*;
+
+/// AS_TRANS, used in place of 4-byte ASNs by 2-byte speakers.
+/// <https://www.rfc-editor.org/rfc/rfc6793>.
+pub const AS_TRANS: Asn = Asn(23456);
+
+/// An autonomous system number, 32-bit according to
+/// <https://www.rfc-editor.org/rfc/rfc6793>.
+///
+/// Parsed from asplain (`65546`, `AS65546`) or asdot (`1.10`, `AS1.10`),
+/// and displayed in asplain.
+/// <https://www.rfc-editor.org/rfc/rfc5396>.
+#[derive(Clone, Copy, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct Asn(pub u32);
+
+impl Asn {
+    /// Private ASNs.
+    /// <https://www.rfc-editor.org/rfc/rfc6996>.
+    pub fn is_private(self) -> bool {
+        (64512..=65534).contains(&self.0) || (4_200_000_000..=4_294_967_294).contains(&self.0)
+    }
+
+    /// Reserved ASNs: AS0 and the last ASNs.
+    /// <https://www.rfc-editor.org/rfc/rfc7607>,
+    /// <https://www.rfc-editor.org/rfc/rfc7300>.
+    pub fn is_reserved(self) -> bool {
+        matches!(self.0, 0 | 65535 | 4_294_967_295)
+    }
+
+    /// ASNs for documentation use.
+    /// <https://www.rfc-editor.org/rfc/rfc5398>.
+    pub fn is_documentation(self) -> bool {
+        (64496..=64511).contains(&self.0) || (65536..=65551).contains(&self.0)
+    }
+
+    /// Whether `self` is [`AS_TRANS`].
+    pub fn is_as_trans(self) -> bool {
+        self == AS_TRANS
+    }
+
+    /// Whether `self` needs 4 bytes to be represented.
+    pub fn is_4_byte(self) -> bool {
+        self.0 > u16::MAX as u32
+    }
+
+    /// `self` in asdot notation, e.g., `1.10` for 65546,
+    /// and `2914` for 2914.
+    pub fn to_asdot(self) -> String {
+        match self.is_4_byte() {
+            true => format!("{}.{}", self.0 >> 16, self.0 & 0xFFFF),
+            false => self.0.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Asn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for Asn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for Asn {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for Asn {
+    type Err = anyhow::Error;
+
+    /// Parse asplain or asdot, optionally prefixed with `AS`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, high, low) = regex_captures!(r"^(?:AS)?(\d+)(?:\.(\d+))?$"i, s)
+            .context(format!("`{s}` is not an AS number"))?;
+        if low.is_empty() {
+            let num = high.parse().context(format!("`{s}` is out of range"))?;
+            return Ok(Self(num));
+        }
+        let high: u16 = high.parse().context(format!("`{s}` is out of range"))?;
+        let low: u16 = low.parse().context(format!("`{s}` is out of range"))?;
+        Ok(Self(((high as u32) << 16) | low as u32))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::lex::{expressions, lines_continued};
+
 use super::*;
 
 #[derive(Clone, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -7,6 +9,33 @@ pub struct AutNum {
     pub exports: Versions,
 }
 
+impl AutNum {
+    /// The key and expression of the attribute at `index` among the
+    /// continued lines of `self.body`, e.g.,
+    /// `("mp-export", "to AS-ANY announce AS2914:AS-GLOBAL")`.
+    pub fn attribute(&self, index: usize) -> Option<(String, String)> {
+        let line = lines_continued(self.body.lines()).nth(index)?;
+        let expr = expressions([line]).next()?;
+        Some((expr.key, expr.expr))
+    }
+
+    /// The registry `self` comes from, in its `source` attribute, e.g., `RIPE`.
+    pub fn source(&self) -> Option<String> {
+        expressions(lines_continued(self.body.lines()))
+            .find(|expr| expr.key == "source")
+            .map(|expr| expr.expr.to_uppercase())
+    }
+
+    /// Cite the attribute at `index` in aut-num `num`, e.g.,
+    /// "AS2914 mp-export attribute #7: `to AS-ANY announce AS2914:AS-GLOBAL`".
+    /// Attributes are counted from 1 in `self.body`; an attribute continued
+    /// over several lines counts once, so this is not a line number.
+    pub fn cite(&self, num: Asn, index: usize) -> Option<String> {
+        let (key, expr) = self.attribute(index)?;
+        Some(format!("AS{num} {key} attribute #{}: `{expr}`", index + 1))
+    }
+}
+
 impl std::fmt::Debug for AutNum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut r = f.debug_struct("AutNum");
@@ -1,10 +1,8 @@
-use lazy_regex::regex_is_match;
-
 use super::{lex::parse_aut_num_name, *};
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum AsName {
-    Num(usize),
+    Num(Asn),
     Set(String),
     Invalid(String),
 }
@@ -21,5 +19,5 @@ pub fn parse_as_name(field: String) -> Result<AsName> {
 }
 
 pub fn is_as_set(field: &str) -> bool {
-    regex_is_match!(r"^(AS\d+:)?AS-\S+$"i, field)
+    is_set_name(field, "AS-")
 }
@@ -5,16 +5,19 @@ use std::{
     thread::available_parallelism,
 };
 
+use hashbrown::HashSet;
 use itertools::izip;
 
 use crate::serialize::from_reader;
 
+use super::lex::parse_aut_num_name;
+
 use super::*;
 
 /// Parsed RPSL dump.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Dump {
-    pub aut_nums: BTreeMap<usize, AutNum>,
+    pub aut_nums: BTreeMap<Asn, AutNum>,
     pub as_sets: BTreeMap<String, AsSet>,
     pub route_sets: BTreeMap<String, RouteSet>,
     pub peering_sets: BTreeMap<String, PeeringSet>,
@@ -22,7 +25,7 @@ pub struct Dump {
     /// The AS numbers with Vec of their routes.
     /// <https://www.rfc-editor.org/rfc/rfc2622#section-4>.
     /// Each value should always be sorted.
-    pub as_routes: BTreeMap<usize, Vec<IpNet>>,
+    pub as_routes: BTreeMap<Asn, Vec<IpNet>>,
 }
 
 pub fn split_n_btreemap<K, V>(mut map: BTreeMap<K, V>, n: usize) -> Vec<BTreeMap<K, V>>
@@ -119,6 +122,34 @@ impl Dump {
         self
     }
 
+    /// Like [`Self::merge`], but combine the routes of each AS in both,
+    /// as written in different shards by a
+    /// [`ShardWriter`](super::ShardWriter).
+    pub fn combine(mut self, mut other: Self) -> Self {
+        for (num, routes) in &mut other.as_routes {
+            if let Some(mut ours) = self.as_routes.remove(num) {
+                ours.append(routes);
+                ours.sort_unstable();
+                ours.dedup();
+                *routes = ours;
+            }
+        }
+        self.merge(other)
+    }
+
+    /// Remove hierarchical sets whose parent object does not exist.
+    /// The parent of `AS1:AS-FOO` is the aut-num `AS1`,
+    /// and the parent of `AS-FOO:AS-BAR:AS2` is the as-set `AS-FOO:AS-BAR`.
+    /// Call this only after all the [`Dump`]s are merged.
+    /// <https://www.rfc-editor.org/rfc/rfc2622#section-5>.
+    pub fn remove_orphan_sets(&mut self) {
+        let aut_nums = &self.aut_nums;
+        remove_orphans(&mut self.as_sets, aut_nums, "as_set");
+        remove_orphans(&mut self.route_sets, aut_nums, "route_set");
+        remove_orphans(&mut self.peering_sets, aut_nums, "peering_set");
+        remove_orphans(&mut self.filter_sets, aut_nums, "filter_set");
+    }
+
     /// Read a [`Dump`] from `directory` in parallel.
     /// All files need to JSON and serialized from [Dump],
     /// presumably, they were written using [`pal_write`](#method.pal_write)
@@ -138,6 +169,15 @@ impl Dump {
         Ok(merge_dumps(dumps))
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.aut_nums.is_empty()
+            && self.as_sets.is_empty()
+            && self.route_sets.is_empty()
+            && self.peering_sets.is_empty()
+            && self.filter_sets.is_empty()
+            && self.as_routes.is_empty()
+    }
+
     pub fn log_count(&self) {
         let Self {
             aut_nums,
@@ -181,7 +221,51 @@ where
     Ok(())
 }
 
-/// Merge `dumps` into a single [`Dump`] in parallel, with no ordering guarantee.
+/// Remove sets in `sets` whose parent is neither in `aut_nums` nor in `sets`.
+fn remove_orphans<V>(
+    sets: &mut BTreeMap<String, V>,
+    aut_nums: &BTreeMap<Asn, AutNum>,
+    class: &str,
+) {
+    let kept = kept_sets(
+        sets.keys().map(String::as_str),
+        |num| aut_nums.contains_key(&num),
+        class,
+    );
+    sets.retain(|name, _| kept.contains(&name.to_uppercase()));
+}
+
+/// Uppercase names in `names` whose parent is either an aut-num for which
+/// `has_aut_num` holds or another kept name.
+/// Parents are checked before their children,
+/// so the descendants of an orphan are removed as well.
+pub fn kept_sets<'a, I, F>(names: I, has_aut_num: F, class: &str) -> HashSet<String>
+where
+    I: IntoIterator<Item = &'a str>,
+    F: Fn(Asn) -> bool,
+{
+    let mut names: Vec<_> = names.into_iter().collect();
+    names.sort_by_key(|name| name.matches(':').count());
+    let mut kept = HashSet::with_capacity(names.len());
+    for name in names {
+        let has_parent = match parent_set_name(name) {
+            None => true,
+            Some(parent) => match parse_aut_num_name(parent) {
+                Ok(num) => has_aut_num(num),
+                Err(_) => kept.contains(&parent.to_uppercase()),
+            },
+        };
+        if has_parent {
+            kept.insert(name.to_uppercase());
+        } else {
+            warn!("Removing {class} {name} because its parent does not exist.");
+        }
+    }
+    kept
+}
+
+/// Merge `dumps` into a single [`Dump`] in parallel, with no ordering guarantee,
+/// combining the routes of each AS across `dumps`.
 pub fn merge_dumps(dumps: Vec<Dump>) -> Dump {
-    dumps.into_par_iter().reduce(Dump::default, Dump::merge)
+    dumps.into_par_iter().reduce(Dump::default, Dump::combine)
 }
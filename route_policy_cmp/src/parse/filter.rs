@@ -42,7 +42,7 @@ pub fn parse_path_attribute(attr: String, mp_peerings: &[PeeringAction]) -> Filt
     } else if attr.ends_with("^-") || attr.ends_with("^+") {
         Filter::AsPathRE(attr)
     } else if is_filter_set(&attr) {
-        Filter::FilterSet(attr)
+        Filter::FilterSet(attr.into())
     } else if let Some(filter) = try_parse_route_set(&attr) {
         filter
     } else if let Some(filter) = try_parse_as_set(&attr) {
@@ -55,7 +55,7 @@ pub fn parse_path_attribute(attr: String, mp_peerings: &[PeeringAction]) -> Filt
 }
 
 pub fn is_filter_set(attr: &str) -> bool {
-    regex_is_match!(r"^(AS\d+:)?fltr-\S+$"i, attr)
+    is_set_name(attr, "FLTR-")
 }
 
 /// PeerAS can be used instead of the AS number of the peer AS.
@@ -89,29 +89,31 @@ pub fn peer_as_filter(mp_peerings: &[PeeringAction]) -> Filter {
 }
 
 pub fn try_parse_route_set(attr: &str) -> Option<Filter> {
-    regex_captures!(r"^((?:AS\d+:)?rs-[^\s\^]+)(\^[+-])?$"i, attr).and_then(
-        |(_, name, operator)| {
-            operator
+    regex_captures!(r"^([^\s\^]+)(\^[+-])?$", attr).and_then(|(_, name, operator)| {
+        match is_route_set_name(name) {
+            true => operator
                 .parse()
                 .ok()
-                .map(|op| Filter::RouteSet(name.into(), op))
-        },
-    )
+                .map(|op| Filter::RouteSet(name.into(), op)),
+            false => None,
+        }
+    })
 }
 
 pub fn try_parse_as_set(attr: &str) -> Option<Filter> {
-    regex_captures!(r"^((?:AS\d+:)?AS-[^\s\^]+)(\^[+-])?$"i, attr).and_then(
-        |(_, name, operator)| {
-            operator
-                .parse()
-                .ok()
-                .map(|op| Filter::AsSet(name.into(), op))
-        },
-    )
+    regex_captures!(r"^([^\s\^]+)(\^[+-])?$", attr).and_then(|(_, name, operator)| match is_as_set(
+        name,
+    ) {
+        true => operator
+            .parse()
+            .ok()
+            .map(|op| Filter::AsSet(name.into(), op)),
+        false => None,
+    })
 }
 
 pub fn try_parse_as_num(attr: &str) -> Option<Filter> {
-    regex_captures!(r"^AS(\d+)(\^[+-])?$"i, attr).and_then(|(_, number, operator)| {
+    regex_captures!(r"^(AS\d+(?:\.\d+)?)(\^[+-])?$"i, attr).and_then(|(_, number, operator)| {
         operator
             .parse()
             .ok()
@@ -126,7 +128,7 @@ pub fn try_parse_as_num(attr: &str) -> Option<Filter> {
 #[derive(Clone, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum Filter {
     /// `<filter-set-name>`: An RPSL name that starts with `fltr-`.
-    FilterSet(String),
+    FilterSet(SetName),
     Any,
     /// An explicit list of address prefixes enclosed in braces '{' and '}'.  The policy filter matches the set of routes whose destination address-prefix is in the set.
     /// An address prefix can be optionally followed by a range operator.
@@ -135,11 +137,11 @@ pub enum Filter {
     /// A route set name matches the set of routes that are members of the set.
     /// May also be implicitly defined route sets
     /// <https://www.rfc-editor.org/rfc/rfc2622#section-5.3>.
-    RouteSet(String, RangeOperator),
+    RouteSet(SetName, RangeOperator),
     /// An AS number.
-    AsNum(usize, RangeOperator),
+    AsNum(Asn, RangeOperator),
     /// A name of an as-set object.
-    AsSet(String, RangeOperator),
+    AsSet(SetName, RangeOperator),
     /// An AS-path regular expression can be used as a policy filter by enclosing the expression in `<' and `>'.
     /// Basically, we do not deal with this at present.
     /// We also throw unrecognized filters under this.
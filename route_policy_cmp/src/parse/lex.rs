@@ -1,4 +1,4 @@
-use lazy_regex::regex_captures;
+use lazy_regex::regex_is_match;
 
 use super::*;
 use crate::lex;
@@ -25,14 +25,14 @@ pub fn parse_lexed(lexed: lex::Dump) -> Dump {
     dump
 }
 
-pub fn parse_lexed_aut_nums(lexed: Vec<lex::AutNum>) -> BTreeMap<usize, AutNum> {
+pub fn parse_lexed_aut_nums(lexed: Vec<lex::AutNum>) -> BTreeMap<Asn, AutNum> {
     lexed
         .into_par_iter()
         .filter_map(|l| parse_lexed_aut_num(l).map_err(|e| error!("{e:#}")).ok())
         .collect()
 }
 
-pub fn parse_lexed_aut_num(aut_num: lex::AutNum) -> Result<(usize, AutNum)> {
+pub fn parse_lexed_aut_num(aut_num: lex::AutNum) -> Result<(Asn, AutNum)> {
     let num = parse_aut_num_name(&aut_num.name).context(format!("parsing {aut_num:?}"))?;
     let lex::AutNum {
         name: _,
@@ -52,12 +52,11 @@ pub fn parse_lexed_aut_num(aut_num: lex::AutNum) -> Result<(usize, AutNum)> {
     ))
 }
 
-pub fn parse_aut_num_name(name: &str) -> Result<usize> {
-    match regex_captures!(r"^AS(\d+)$"i, name) {
-        Some((_, num)) => num
-            .parse()
-            .map_err(|err| Error::new(err).context(format!("parsing {name}"))),
-        None => bail!("AS number name `{name}` does not match pattern"),
+/// Parse an AS number name in asplain or asdot, e.g., `AS2914` or `AS1.10`.
+pub fn parse_aut_num_name(name: &str) -> Result<Asn> {
+    match regex_is_match!(r"^AS\d+(\.\d+)?$"i, name) {
+        true => name.parse().context(format!("parsing {name}")),
+        false => bail!("AS number name `{name}` does not match pattern"),
     }
 }
 
@@ -173,7 +172,7 @@ pub fn parse_lexed_filter_set(lexed: lex::FilterSet) -> Result<(String, FilterSe
 
 pub fn parse_lexed_as_routes(
     as_routes: BTreeMap<String, Vec<String>>,
-) -> BTreeMap<usize, Vec<IpNet>> {
+) -> BTreeMap<Asn, Vec<IpNet>> {
     as_routes
         .into_iter()
         .filter_map(|as_route| {
@@ -184,7 +183,7 @@ pub fn parse_lexed_as_routes(
         .collect()
 }
 
-pub fn parse_lexed_as_route((name, routes): &(String, Vec<String>)) -> Result<(usize, Vec<IpNet>)> {
+pub fn parse_lexed_as_route((name, routes): &(String, Vec<String>)) -> Result<(Asn, Vec<IpNet>)> {
     let num = parse_aut_num_name(name)?;
     let routes: Result<_> = routes.iter().map(|r| Ok(r.parse()?)).collect();
     let mut routes: Vec<_> = routes?;
@@ -34,12 +34,14 @@ pub fn parse_entry(entry: mp_import::Entry) -> Entry {
     let mp_import::Entry {
         mp_peerings,
         mp_filter,
+        index,
     } = entry;
     let mp_peerings = parse_mp_peerings(mp_peerings);
     let mp_filter = parse_filter(mp_filter, &mp_peerings);
     Entry {
         mp_peerings,
         mp_filter,
+        index,
     }
 }
 
@@ -58,6 +60,13 @@ impl Versions {
     pub fn is_default(&self) -> bool {
         *self == Self::default()
     }
+
+    /// All entries for any address family and cast.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        [&self.any, &self.ipv4, &self.ipv6]
+            .into_iter()
+            .flat_map(Casts::entries)
+    }
 }
 
 impl std::fmt::Debug for Versions {
@@ -107,6 +116,13 @@ impl Casts {
     pub fn is_default(&self) -> bool {
         *self == Self::default()
     }
+
+    /// All entries for any cast.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        [&self.any, &self.unicast, &self.multicast]
+            .into_iter()
+            .flatten()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -115,4 +131,8 @@ pub struct Entry {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub mp_peerings: Vec<PeeringAction>,
     pub mp_filter: Filter,
+    /// Index of the attribute this entry comes from among the continued
+    /// lines of the aut-num body, see [`AutNum::attribute`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
 }
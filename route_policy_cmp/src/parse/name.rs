@@ -0,0 +1,131 @@
+use std::{cmp::Ordering, fmt, hash, ops::Deref, sync::Arc};
+
+use super::*;
+
+/// ID of a set name interned in a [`QueryDump`](crate::bgp::QueryDump).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Symbol(pub u32);
+
+impl Symbol {
+    /// The symbol of names not interned yet.
+    pub const UNRESOLVED: Self = Self(u32::MAX);
+
+    pub fn index(self) -> Option<usize> {
+        (self != Self::UNRESOLVED).then_some(self.0 as usize)
+    }
+}
+
+/// Name of an as-set, route-set, filter-set or peering-set referenced
+/// from a policy or another set.
+/// Names with the same text share one allocation and are resolved to a
+/// [`Symbol`] once the [`QueryDump`](crate::bgp::QueryDump) built from them
+/// interns them, so looking them up is indexing into a vector.
+/// Compared, hashed, printed and (de)serialized as the text only.
+#[derive(Clone)]
+pub struct SetName {
+    name: Arc<str>,
+    pub symbol: Symbol,
+}
+
+impl SetName {
+    pub fn new(name: Arc<str>, symbol: Symbol) -> Self {
+        Self { name, symbol }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// The shared text.
+    pub fn shared(&self) -> &Arc<str> {
+        &self.name
+    }
+}
+
+impl Deref for SetName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<&str> for SetName {
+    fn from(value: &str) -> Self {
+        Self::new(value.into(), Symbol::UNRESOLVED)
+    }
+}
+
+impl From<String> for SetName {
+    fn from(value: String) -> Self {
+        Self::new(value.into(), Symbol::UNRESOLVED)
+    }
+}
+
+impl From<&String> for SetName {
+    fn from(value: &String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl PartialEq for SetName {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for SetName {}
+
+impl PartialEq<str> for SetName {
+    fn eq(&self, other: &str) -> bool {
+        &*self.name == other
+    }
+}
+
+impl PartialEq<&str> for SetName {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.name == *other
+    }
+}
+
+impl hash::Hash for SetName {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state)
+    }
+}
+
+impl PartialOrd for SetName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SetName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+
+impl fmt::Debug for SetName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.name, f)
+    }
+}
+
+impl fmt::Display for SetName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl Serialize for SetName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for SetName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
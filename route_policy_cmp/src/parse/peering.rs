@@ -1,6 +1,5 @@
 use std::convert::identity;
 
-use lazy_regex::regex_is_match;
 use serde_with::skip_serializing_none;
 
 use crate::lex::{mp_import, peering};
@@ -41,7 +40,7 @@ pub fn parse_mp_peering(mp_peering: peering::Peering) -> Peering {
 }
 
 pub fn is_peering_set(field: &str) -> bool {
-    regex_is_match!(r"^(AS\d+:)?prng-\S+$"i, field)
+    is_set_name(field, "PRNG-")
 }
 
 pub fn parse_as_expr(as_expr: peering::AsExpr) -> AsExpr {
@@ -53,7 +52,7 @@ pub fn parse_as_expr(as_expr: peering::AsExpr) -> AsExpr {
 
 pub fn parse_single_as_expr(single: String) -> AsExpr {
     if is_peering_set(&single) {
-        AsExpr::PeeringSet(single)
+        AsExpr::PeeringSet(single.into())
     } else {
         AsExpr::Single(
             parse_as_name(single).map_or_else(|e| AsName::Invalid(e.to_string()), identity),
@@ -127,7 +126,7 @@ impl std::fmt::Debug for PeeringAction {
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum AsExpr {
     Single(AsName),
-    PeeringSet(String),
+    PeeringSet(SetName),
     And {
         left: Box<AsExpr>,
         right: Box<AsExpr>,
@@ -1,18 +1,18 @@
-use lazy_regex::{regex_captures, regex_is_match};
+use lazy_regex::regex_captures;
 
-use super::*;
+use super::{lex::parse_aut_num_name, *};
 
 /// <https://www.rfc-editor.org/rfc/rfc2622#section-5.1>
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct AsSet {
     pub body: String,
     /// AS numbers; should be kept sorted.
-    pub members: Vec<usize>,
+    pub members: Vec<Asn>,
     pub set_members: Vec<String>,
 }
 
 impl AsSet {
-    pub fn new(mut body: String, mut members: Vec<usize>, mut set_members: Vec<String>) -> Self {
+    pub fn new(mut body: String, mut members: Vec<Asn>, mut set_members: Vec<String>) -> Self {
         body.shrink_to_fit();
         members.shrink_to_fit();
         members.sort_unstable();
@@ -26,7 +26,52 @@ impl AsSet {
 }
 
 pub fn is_route_set_name(attr: &str) -> bool {
-    regex_is_match!(r"^(AS\d+:)?rs-\S+$"i, attr)
+    is_set_name(attr, "RS-")
+}
+
+/// Check if `name` is a valid, possibly hierarchical, set name whose set
+/// components start with `prefix`, e.g., `AS-` for as-sets.
+/// A hierarchical name is a list of AS numbers and set names separated by
+/// colons, where at least one component is a set name, and all set name
+/// components are of the same type.
+/// <https://www.rfc-editor.org/rfc/rfc2622#section-5>.
+pub fn is_set_name(name: &str, prefix: &str) -> bool {
+    let mut has_set_component = false;
+    for component in name.split(':') {
+        if is_set_component(component, prefix) {
+            has_set_component = true;
+        } else if parse_aut_num_name(component).is_err() {
+            return false;
+        }
+    }
+    has_set_component
+}
+
+fn is_set_component(component: &str, prefix: &str) -> bool {
+    component.len() > prefix.len()
+        && component
+            .get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        && !component.contains(|c: char| c.is_whitespace() || c == '^')
+}
+
+/// The name of the object that `name` is hierarchically defined under,
+/// obtained by removing the last colon-separated component.
+/// `None` if `name` is not hierarchical.
+pub fn parent_set_name(name: &str) -> Option<&str> {
+    name.rsplit_once(':').map(|(parent, _)| parent)
+}
+
+/// The reserved set `AS-ANY` that contains all ASes.
+/// <https://www.rfc-editor.org/rfc/rfc2622#section-5.1>.
+pub fn is_any_as_set(name: &str) -> bool {
+    name.eq_ignore_ascii_case("AS-ANY")
+}
+
+/// The reserved set `RS-ANY` that contains all routes.
+/// <https://www.rfc-editor.org/rfc/rfc2622#section-5.2>.
+pub fn is_any_route_set(name: &str) -> bool {
+    name.eq_ignore_ascii_case("RS-ANY")
 }
 
 /// <https://www.rfc-editor.org/rfc/rfc2622#section-5.2>
@@ -34,7 +79,8 @@ pub fn is_route_set_name(attr: &str) -> bool {
 pub struct RouteSet {
     pub body: String,
     /// List of `<address-prefix-range>` or `<route-set-name>` or
-    /// `<route-set-name><range-operator>`.
+    /// `<route-set-name><range-operator>`, or AS numbers and as-sets,
+    /// optionally followed by `<range-operator>`.
     pub members: Vec<RouteSetMember>,
 }
 
@@ -44,6 +90,12 @@ pub enum RouteSetMember {
     Range(AddrPfxRange),
     /// `<route-set-name><range-operator>`
     NameOp(String, RangeOperator),
+    /// `<as-number><range-operator>`, standing for the routes registered
+    /// with the AS as their origin.
+    AsNum(Asn, RangeOperator),
+    /// `<as-set-name><range-operator>`, standing for the routes registered
+    /// with any AS in the as-set as their origin.
+    AsSet(String, RangeOperator),
 }
 
 impl std::fmt::Debug for RouteSetMember {
@@ -59,6 +111,34 @@ impl std::fmt::Debug for RouteSetMember {
                 }
                 r.finish()
             }
+            AsNum(arg0, arg1) => {
+                let mut r = f.debug_tuple("AsNum");
+                r.field(arg0);
+                if *arg1 != RangeOperator::NoOp {
+                    r.field(arg1);
+                }
+                r.finish()
+            }
+            AsSet(arg0, arg1) => {
+                let mut r = f.debug_tuple("AsSet");
+                r.field(arg0);
+                if *arg1 != RangeOperator::NoOp {
+                    r.field(arg1);
+                }
+                r.finish()
+            }
+        }
+    }
+}
+
+/// RPSL form of the member, as in the `members` attribute.
+impl std::fmt::Display for RouteSetMember {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use RouteSetMember::*;
+        match self {
+            Range(range) => write!(f, "{range}"),
+            NameOp(name, op) | AsSet(name, op) => write!(f, "{name}{op}"),
+            AsNum(num, op) => write!(f, "AS{num}{op}"),
         }
     }
 }
@@ -68,9 +148,22 @@ impl From<String> for RouteSetMember {
         if let Ok(range) = value.parse() {
             Self::Range(range)
         } else if let Ok((name, op)) = try_parse_name_operator(&value) {
-            Self::NameOp(name.into(), op)
+            Self::from_name_op(name.into(), op)
+        } else {
+            Self::from_name_op(value, RangeOperator::NoOp)
+        }
+    }
+}
+
+impl RouteSetMember {
+    /// Classify `name` as an as-set, an AS number, or a route set name.
+    pub fn from_name_op(name: String, op: RangeOperator) -> Self {
+        if is_as_set(&name) {
+            Self::AsSet(name, op)
+        } else if let Ok(num) = parse_aut_num_name(&name) {
+            Self::AsNum(num, op)
         } else {
-            Self::NameOp(value, RangeOperator::NoOp)
+            Self::NameOp(name, op)
         }
     }
 }
@@ -0,0 +1,285 @@
+//! Writing a parsed dump in shards as objects are parsed,
+//! so parsing large IRRs only keeps a bounded amount of objects in memory.
+use std::{
+    fs::{create_dir_all, remove_file, File},
+    hash::Hash,
+    io::{BufReader, BufWriter, Write},
+    mem::size_of,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::serialize::from_reader;
+
+use super::{dump::kept_sets, *};
+
+/// Default of [`ShardWriter::ceiling`], 1GiB.
+pub const MEMORY_CEILING: usize = 1 << 30;
+
+/// Objects of a later generation override objects of an earlier generation
+/// with the same key.
+type Generation = u32;
+
+/// Writer of a parsed dump to a directory, buffering parsed objects and
+/// routes and writing them to a new shard whenever they grow past
+/// [`Self::ceiling`].
+///
+/// The keys of the objects written and the ASes with routes are not bounded
+/// by the ceiling: they stay in memory until [`Self::finish`], which removes
+/// orphan sets and objects overridden by a later generation from the shards.
+/// The shards can then be read with [`Dump::pal_read`],
+/// which combines the routes of each AS across shards.
+#[derive(Debug)]
+pub struct ShardWriter {
+    directory: PathBuf,
+    /// Buffered objects are written once their bodies and routes take this
+    /// many bytes.
+    /// The parsed objects take a small multiple of that.
+    pub ceiling: usize,
+    generation: Generation,
+    buffer: Dump,
+    buffered: usize,
+    shards: Vec<(PathBuf, Generation)>,
+    aut_nums: HashMap<Asn, Generation>,
+    as_sets: HashMap<String, Generation>,
+    route_sets: HashMap<String, Generation>,
+    peering_sets: HashMap<String, Generation>,
+    filter_sets: HashMap<String, Generation>,
+    as_routes: HashMap<Asn, Generation>,
+}
+
+impl ShardWriter {
+    /// Writer to `directory`, which is created if non-existent;
+    /// otherwise, it is assumed empty.
+    pub fn new<P>(directory: P, ceiling: usize) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let directory = directory.as_ref().to_owned();
+        create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            ceiling,
+            generation: 0,
+            buffer: Dump::default(),
+            buffered: 0,
+            shards: Vec::new(),
+            aut_nums: HashMap::new(),
+            as_sets: HashMap::new(),
+            route_sets: HashMap::new(),
+            peering_sets: HashMap::new(),
+            filter_sets: HashMap::new(),
+            as_routes: HashMap::new(),
+        })
+    }
+
+    pub fn push_aut_num(&mut self, num: Asn, aut_num: AutNum) -> Result<()> {
+        let weight = aut_num.body.len();
+        buffer(
+            &mut self.aut_nums,
+            &mut self.buffer.aut_nums,
+            self.generation,
+            num,
+            aut_num,
+        );
+        self.buffered(weight)
+    }
+
+    pub fn push_as_set(&mut self, name: String, as_set: AsSet) -> Result<()> {
+        let weight = name.len() + as_set.body.len();
+        buffer(
+            &mut self.as_sets,
+            &mut self.buffer.as_sets,
+            self.generation,
+            name,
+            as_set,
+        );
+        self.buffered(weight)
+    }
+
+    pub fn push_route_set(&mut self, name: String, route_set: RouteSet) -> Result<()> {
+        let weight = name.len() + route_set.body.len();
+        let (index, sets) = (&mut self.route_sets, &mut self.buffer.route_sets);
+        buffer(index, sets, self.generation, name, route_set);
+        self.buffered(weight)
+    }
+
+    pub fn push_peering_set(&mut self, name: String, peering_set: PeeringSet) -> Result<()> {
+        let weight = name.len() + peering_set.body.len();
+        let (index, sets) = (&mut self.peering_sets, &mut self.buffer.peering_sets);
+        buffer(index, sets, self.generation, name, peering_set);
+        self.buffered(weight)
+    }
+
+    pub fn push_filter_set(&mut self, name: String, filter_set: FilterSet) -> Result<()> {
+        let weight = name.len() + filter_set.body.len();
+        let (index, sets) = (&mut self.filter_sets, &mut self.buffer.filter_sets);
+        buffer(index, sets, self.generation, name, filter_set);
+        self.buffered(weight)
+    }
+
+    /// Record that `num` originates `route`.
+    /// Routes of `num` from a later generation override all its routes from
+    /// earlier generations, like in [`Dump::merge`].
+    pub fn push_route(&mut self, num: Asn, route: IpNet) -> Result<()> {
+        self.as_routes.insert(num, self.generation);
+        self.buffer.as_routes.entry(num).or_default().push(route);
+        self.buffered(size_of::<IpNet>())
+    }
+
+    fn buffered(&mut self, weight: usize) -> Result<()> {
+        self.buffered += weight;
+        match self.buffered >= self.ceiling {
+            true => self.flush(),
+            false => Ok(()),
+        }
+    }
+
+    /// Write the buffered objects to a new shard, if any.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let path = self.directory.join(format!("{}.json", self.shards.len()));
+        let mut buffer = mem::take(&mut self.buffer);
+        for routes in buffer.as_routes.values_mut() {
+            routes.sort_unstable();
+        }
+        write_shard(&path, &buffer).context(format!("writing {}", path.display()))?;
+        debug!(
+            "Wrote {} bytes of objects to {}.",
+            self.buffered,
+            path.display()
+        );
+        self.shards.push((path, self.generation));
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Flush, so objects pushed from now on override those pushed before.
+    pub fn next_generation(&mut self) -> Result<()> {
+        self.flush()?;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Flush, then rewrite each shard one by one without orphan sets and
+    /// objects or routes overridden by a later generation.
+    /// See [`Dump::remove_orphan_sets`].
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()?;
+        let has_aut_num = |num| self.aut_nums.contains_key(&num);
+        let kept = [
+            kept_sets(
+                self.as_sets.keys().map(String::as_str),
+                has_aut_num,
+                "as_set",
+            ),
+            kept_sets(
+                self.route_sets.keys().map(String::as_str),
+                has_aut_num,
+                "route_set",
+            ),
+            kept_sets(
+                self.peering_sets.keys().map(String::as_str),
+                has_aut_num,
+                "peering_set",
+            ),
+            kept_sets(
+                self.filter_sets.keys().map(String::as_str),
+                has_aut_num,
+                "filter_set",
+            ),
+        ];
+
+        let (mut counts, mut n_shards) = ([0; 5], 0);
+        for (path, generation) in &self.shards {
+            let file = File::open(path).context(format!("reading {}", path.display()))?;
+            let mut shard: Dump = from_reader(BufReader::new(file))?;
+            let aut_nums = &self.aut_nums;
+            shard
+                .aut_nums
+                .retain(|num, _| aut_nums.get(num) == Some(generation));
+            retain_sets(&mut shard.as_sets, &self.as_sets, &kept[0], generation);
+            retain_sets(
+                &mut shard.route_sets,
+                &self.route_sets,
+                &kept[1],
+                generation,
+            );
+            retain_sets(
+                &mut shard.peering_sets,
+                &self.peering_sets,
+                &kept[2],
+                generation,
+            );
+            retain_sets(
+                &mut shard.filter_sets,
+                &self.filter_sets,
+                &kept[3],
+                generation,
+            );
+            let as_routes = &self.as_routes;
+            shard
+                .as_routes
+                .retain(|num, _| as_routes.get(num) == Some(generation));
+            match shard.is_empty() {
+                true => remove_file(path)?,
+                false => {
+                    write_shard(path, &shard)?;
+                    n_shards += 1;
+                }
+            }
+            let lengths = [
+                shard.aut_nums.len(),
+                shard.as_sets.len(),
+                shard.route_sets.len(),
+                shard.peering_sets.len(),
+                shard.filter_sets.len(),
+            ];
+            for (count, length) in counts.iter_mut().zip(lengths) {
+                *count += length;
+            }
+        }
+        let n_as_routes = self.as_routes.len();
+        let [aut_nums, as_sets, route_sets, peering_sets, filter_sets] = counts;
+        debug!(
+            "Wrote {aut_nums} aut_nums, {as_sets} as_sets, {route_sets} route_sets, {peering_sets} peering_sets, {filter_sets} filter_sets, {n_as_routes} as_routes in {n_shards} shards."
+        );
+        Ok(())
+    }
+}
+
+/// Buffer `value` in `map` and record its `generation` in `index`.
+fn buffer<K, V>(
+    index: &mut HashMap<K, Generation>,
+    map: &mut BTreeMap<K, V>,
+    generation: Generation,
+    key: K,
+    value: V,
+) where
+    K: Clone + Eq + Hash + Ord,
+{
+    index.insert(key.clone(), generation);
+    map.insert(key, value);
+}
+
+/// Keep the sets in `sets` last pushed in `generation` and not orphans.
+fn retain_sets<V>(
+    sets: &mut BTreeMap<String, V>,
+    index: &HashMap<String, Generation>,
+    kept: &HashSet<String>,
+    generation: &Generation,
+) {
+    sets.retain(|name, _| {
+        index.get(name) == Some(generation) && kept.contains(&name.to_uppercase())
+    });
+}
+
+fn write_shard(path: &Path, shard: &Dump) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, shard)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,244 @@
+//! Interactive shell to query a [`QueryDump`] loaded once.
+use std::{env, fmt::Write, path::PathBuf};
+
+use rustyline::{
+    completion::Completer, error::ReadlineError, history::DefaultHistory, Editor, Helper,
+    Highlighter, Hinter, Validator,
+};
+
+use crate::{
+    analysis::bilateral::expand_route_set_ranges,
+    bgp::{cmp::RECURSION_LIMIT, map::parse_as_path, Compare, QueryDump, Report, Verbosity},
+    parse::Asn,
+};
+
+use super::*;
+
+pub const HELP: &str = "\
+aut-num <AS>                Show the aut-num object.
+expand <set>                Expand an as-set, route-set, filter-set or peering-set.
+check <prefix> <AS path>    Check a route, with the AS path starting from the collector.
+explain <prefix> <AS path>  Check a route and show every rule visited,
+                            and the policy lines that did not match.
+members-of <AS or as-set>   Show the as-sets directly containing the AS or as-set.
+help                        Show this message.
+quit                        Exit the shell.";
+
+const COMMANDS: [&str; 7] = [
+    "aut-num",
+    "check",
+    "expand",
+    "explain",
+    "help",
+    "members-of",
+    "quit",
+];
+
+/// Answers the commands in [`HELP`] on `dump`.
+pub struct Shell {
+    pub dump: QueryDump,
+}
+
+impl Shell {
+    pub fn new(dump: QueryDump) -> Self {
+        Self { dump }
+    }
+
+    /// Run the read-eval-print loop on the terminal until `quit` or EOF,
+    /// with history saved to `~/.route_policy_cmp_history`.
+    pub fn run(&self) -> Result<()> {
+        let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+        editor.set_helper(Some(ShellHelper::new(&self.dump)));
+        let history =
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".route_policy_cmp_history"));
+        if let Some(history) = &history {
+            _ = editor.load_history(history);
+        }
+
+        println!("Type `help` for the commands.");
+        loop {
+            let line = match editor.readline("rpsl> ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            editor.add_history_entry(&line)?;
+            match self.execute(&line) {
+                Ok(Some(output)) => println!("{output}"),
+                Ok(None) => break,
+                Err(err) => println!("Error: {err:#}."),
+            }
+        }
+
+        if let Some(history) = &history {
+            editor.save_history(history)?;
+        }
+        Ok(())
+    }
+
+    /// Answer command `line`, or `None` to quit.
+    pub fn execute(&self, line: &str) -> Result<Option<String>> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let output = match command {
+            "aut-num" => self.aut_num(rest)?,
+            "expand" => self.expand(rest)?,
+            "check" => self.check(rest, false)?,
+            "explain" => self.check(rest, true)?,
+            "members-of" => self.members_of(rest)?,
+            "help" => HELP.into(),
+            "quit" | "exit" => return Ok(None),
+            other => bail!("unknown command `{other}`, type `help` for the commands"),
+        };
+        Ok(Some(output))
+    }
+
+    fn aut_num(&self, rest: &str) -> Result<String> {
+        let num: Asn = rest.parse()?;
+        match self.dump.aut_nums.get(&num) {
+            Some(aut_num) => Ok(format!("aut-num: AS{num}\n{}", aut_num.body.trim_end())),
+            None => bail!("AS{num} is not recorded"),
+        }
+    }
+
+    fn expand(&self, name: &str) -> Result<String> {
+        let name = &name.to_uppercase();
+        let mut output = String::new();
+        if let Some((nums, unrecorded)) = self.dump.expand_as_set(name) {
+            let nums: Vec<_> = nums.iter().map(|num| format!("AS{num}")).collect();
+            writeln!(output, "{} ASes: {}", nums.len(), nums.join(" "))?;
+            if !unrecorded.is_empty() {
+                writeln!(output, "Unrecorded sets: {}", unrecorded.join(" "))?;
+            }
+        } else if self.dump.route_sets.contains_key(name) {
+            match expand_route_set_ranges(&self.dump, name, RECURSION_LIMIT) {
+                Some(ranges) => {
+                    writeln!(output, "{} prefixes:", ranges.len())?;
+                    for range in ranges {
+                        writeln!(output, "{range}")?;
+                    }
+                }
+                None => writeln!(output, "{name} cannot be expanded to prefixes.")?,
+            }
+        } else if let Some(filter_set) = self.dump.filter_sets.get(name) {
+            for filter in &filter_set.filters {
+                writeln!(output, "{filter:?}")?;
+            }
+        } else if let Some(peering_set) = self.dump.peering_sets.get(name) {
+            for peering in &peering_set.peerings {
+                writeln!(output, "{peering:?}")?;
+            }
+        } else {
+            bail!("{name} is not a recorded set");
+        }
+        Ok(output.trim_end().into())
+    }
+
+    fn check(&self, rest: &str, explain: bool) -> Result<String> {
+        let (prefix, as_path) = rest
+            .split_once(' ')
+            .context("specify a prefix and an AS path")?;
+        let prefix = prefix.parse().context("bad prefix")?;
+        let as_path = parse_as_path(as_path.trim())?;
+        let verbosity = Verbosity {
+            stop_at_first: false,
+            show_skips: true,
+            show_success: true,
+            per_entry_err: explain,
+            all_err: explain,
+            trace: explain,
+        };
+        let reports = Compare::new(prefix, as_path)
+            .verbosity(verbosity)
+            .check(&self.dump);
+        let mut output = String::new();
+        for report in &reports {
+            match report {
+                Report::Trace { root } => output.push_str(&root.render()),
+                report => {
+                    writeln!(output, "{report:?}")?;
+                    for citation in report.citations(&self.dump) {
+                        writeln!(output, "  {citation}")?;
+                    }
+                }
+            }
+        }
+        Ok(output.trim_end().into())
+    }
+
+    fn members_of(&self, name: &str) -> Result<String> {
+        let names = match name.parse::<Asn>() {
+            Ok(num) => self.dump.as_sets_containing_num(num),
+            Err(_) => self.dump.as_sets_containing_set(&name.to_uppercase()),
+        };
+        Ok(match names.is_empty() {
+            true => format!("No as-set directly contains {name}."),
+            false => names.join("\n"),
+        })
+    }
+}
+
+/// Tab completion of commands and object names.
+#[derive(Helper, Hinter, Highlighter, Validator)]
+pub struct ShellHelper {
+    /// Names of all aut-nums and sets, sorted.
+    names: Vec<String>,
+}
+
+impl ShellHelper {
+    pub fn new(dump: &QueryDump) -> Self {
+        let mut names: Vec<_> = dump
+            .aut_nums
+            .keys()
+            .map(|num| format!("AS{num}"))
+            .chain(dump.as_sets.keys().map(String::from))
+            .chain(dump.route_sets.keys().map(String::from))
+            .chain(dump.filter_sets.keys().map(String::from))
+            .chain(dump.peering_sets.keys().map(String::from))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        Self { names }
+    }
+
+    /// Commands if the word at `start` is the first one in `line`,
+    /// otherwise object names, that start with `word`.
+    pub fn candidates(&self, line: &str, start: usize, word: &str) -> Vec<String> {
+        if line[..start].trim().is_empty() {
+            return COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| command.to_string())
+                .collect();
+        }
+        let word = word.to_uppercase();
+        let from = self
+            .names
+            .partition_point(|name| name.as_str() < word.as_str());
+        self.names[from..]
+            .iter()
+            .take_while(|name| name.starts_with(&word))
+            .take(0x100)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(' ').map_or(0, |space| space + 1);
+        Ok((start, self.candidates(line, start, &line[start..pos])))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    bgp::AsRelDb,
+    parse::{RangeOperator::NoOp, *},
+    test::fixture::entry,
+};
+
+use super::*;
+
+mod bilateral;
+mod infer_rel;
+mod simulate;
+
+/// An aut-num importing from and exporting to neighbors with the filters.
+fn aut_num(imports: Vec<(u32, Filter)>, exports: Vec<(u32, Filter)>) -> AutNum {
+    let mut aut_num = AutNum::default();
+    for (remote, filter) in imports {
+        aut_num.imports.any.any.push(entry(remote, filter));
+    }
+    for (remote, filter) in exports {
+        aut_num.exports.any.any.push(entry(remote, filter));
+    }
+    aut_num
+}
+
+fn as_num(num: u32) -> Filter {
+    Filter::AsNum(Asn(num), NoOp)
+}
@@ -0,0 +1,81 @@
+use maplit::btreemap;
+
+use crate::{analysis::bilateral::*, bgp::QueryDump};
+
+use super::*;
+
+use Mismatch::*;
+
+#[test]
+fn check() -> Result<()> {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            Asn(1) => aut_num(
+                vec![],
+                vec![(2, as_num(1)), (3, as_num(1)), (5, Filter::Any), (6, as_num(1))],
+            ),
+            // Only accepts one of the two routes of AS1.
+            Asn(2) => aut_num(
+                vec![(1, Filter::AddrPrefixSet(vec!["10.0.0.0/8".parse()?]))],
+                vec![],
+            ),
+            // Does not import from AS1.
+            Asn(3) => aut_num(vec![(7, Filter::Any)], vec![]),
+            // AS1 does not export to AS4.
+            Asn(4) => aut_num(vec![(1, Filter::Any)], vec![]),
+            Asn(5) => aut_num(vec![(1, Filter::Any)], vec![]),
+            // Import cannot be evaluated, so the routes are not rejected.
+            Asn(6) => aut_num(vec![(1, Filter::Invalid("AS1 AND".into()))], vec![]),
+        },
+        as_routes: btreemap! {
+            Asn(1) => vec!["10.0.0.0/8".parse()?, "11.0.0.0/8".parse()?],
+        },
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let expected = vec![
+        NotAccepted {
+            from: Asn(1),
+            to: Asn(2),
+            prefixes: vec!["11.0.0.0/8".parse()?],
+        },
+        Unexpandable {
+            from: Asn(1),
+            to: Asn(5),
+        },
+        ExportOnly {
+            from: Asn(1),
+            to: Asn(3),
+        },
+        ImportOnly {
+            from: Asn(1),
+            to: Asn(4),
+        },
+    ];
+    assert_eq!(check_bilateral(&query), expected);
+    Ok(())
+}
+
+#[test]
+fn route_set_ranges() -> Result<()> {
+    let dump = Dump {
+        route_sets: btreemap! {
+            "RS-INNER".into() => RouteSet {
+                body: String::new(),
+                members: vec!["10.0.0.0/8^+".to_string().into(), "AS1".to_string().into()],
+            },
+            "RS-OUTER".into() => RouteSet {
+                body: String::new(),
+                members: vec!["RS-INNER^16".to_string().into(), "192.0.2.0/24".to_string().into()],
+            },
+        },
+        as_routes: btreemap! {Asn(1) => vec!["11.0.0.0/8".parse()?]},
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let ranges = expand_route_set_ranges(&query, "RS-OUTER", 8).unwrap();
+    let ranges: Vec<_> = ranges.iter().map(ToString::to_string).collect();
+    assert_eq!(ranges, ["10.0.0.0/8^+", "11.0.0.0/8^16", "192.0.2.0/24"]);
+    assert_eq!(expand_route_set_ranges(&query, "RS-ANY", 8), None);
+    Ok(())
+}
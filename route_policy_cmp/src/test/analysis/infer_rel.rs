@@ -0,0 +1,76 @@
+use maplit::btreemap;
+
+use crate::analysis::infer_rel::*;
+
+use super::*;
+
+use AsRel::*;
+use NeighborRole::*;
+
+#[test]
+fn infer() -> Result<()> {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            // AS1 buys transit from AS10, and peers with AS2 and AS3.
+            Asn(1) => aut_num(
+                vec![(10, Filter::Any), (2, as_num(2)), (3, as_num(3))],
+                vec![(10, as_num(1)), (2, as_num(1)), (3, as_num(1))],
+            ),
+            // AS2 agrees with AS1.
+            Asn(2) => aut_num(vec![(1, as_num(1))], vec![(1, as_num(2))]),
+            // AS3 thinks AS1 is its customer.
+            Asn(3) => aut_num(vec![(1, as_num(1))], vec![(1, Filter::Any)]),
+            // AS10 sells transit to AS1.
+            Asn(10) => aut_num(vec![(1, as_num(1))], vec![(1, Filter::Any)]),
+            // AS20 imports and exports `ANY` with AS1, which is unclear.
+            Asn(20) => aut_num(vec![(1, Filter::Any)], vec![(1, Filter::Any)]),
+        },
+        ..Dump::default()
+    };
+    let inferences = infer_relationships(&dump);
+    let expected = btreemap! {
+        (Asn(1), Asn(2)) => PairInference::Consistent(Peers(Asn(1), Asn(2))),
+        (Asn(1), Asn(3)) => PairInference::Contradictory {
+            a: Asn(1),
+            b: Asn(3),
+            a_view: Peer,
+            b_view: Customer,
+        },
+        (Asn(1), Asn(10)) => PairInference::Consistent(ProviderCustomer {
+            provider: Asn(10),
+            customer: Asn(1),
+        }),
+    };
+    assert_eq!(inferences, expected);
+
+    let mut caida = Vec::new();
+    write_caida(&inferences, &mut caida)?;
+    let caida = String::from_utf8(caida)?;
+    let lines: Vec<_> = caida.lines().skip(1).collect();
+    assert_eq!(lines, ["1|2|0", "10|1|-1"]);
+
+    let db = AsRelDb::from_caida(caida.as_bytes())?;
+    assert_eq!(Peers(Asn(1), Asn(2)).agrees_with(&db), Some(true));
+    assert_eq!(Peers(Asn(1), Asn(10)).agrees_with(&db), Some(false));
+    assert_eq!(Peers(Asn(1), Asn(3)).agrees_with(&db), None);
+    Ok(())
+}
+
+#[test]
+fn one_sided() {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            Asn(1) => aut_num(vec![(10, Filter::Any)], vec![]),
+        },
+        ..Dump::default()
+    };
+    assert_eq!(
+        infer_relationships(&dump),
+        btreemap! {
+            (Asn(1), Asn(10)) => PairInference::OneSided {
+                rel: ProviderCustomer { provider: Asn(10), customer: Asn(1) },
+                declared_by: Asn(1),
+            },
+        }
+    );
+}
@@ -0,0 +1,63 @@
+use maplit::btreemap;
+
+use crate::{analysis::simulate::*, bgp::QueryDump};
+
+use super::*;
+
+fn query() -> Result<QueryDump> {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            Asn(1) => aut_num(vec![], vec![(2, as_num(1)), (3, as_num(1))]),
+            Asn(2) => aut_num(vec![(1, as_num(1))], vec![(4, Filter::Any)]),
+            // Does not accept the prefix from AS1.
+            Asn(3) => aut_num(
+                vec![(1, Filter::AddrPrefixSet(vec!["11.0.0.0/8".parse()?]))],
+                vec![],
+            ),
+            Asn(4) => aut_num(vec![(2, Filter::Any)], vec![(5, Filter::Any)]),
+            Asn(5) => aut_num(vec![(4, Filter::Any)], vec![]),
+        },
+        as_routes: btreemap! {Asn(1) => vec!["10.0.0.0/8".parse()?]},
+        ..Dump::default()
+    };
+    Ok(QueryDump::from_dump(dump))
+}
+
+fn paths(paths: &[&[u32]]) -> Vec<Vec<Asn>> {
+    paths
+        .iter()
+        .map(|path| path.iter().copied().map(Asn).collect())
+        .collect()
+}
+
+#[test]
+fn propagate() -> Result<()> {
+    let query = query()?;
+    let prefix = "10.0.0.0/8".parse()?;
+    let simulation = Simulation {
+        max_depth: 3,
+        ..Simulation::default()
+    };
+    let propagation = simulation.propagate(&query, prefix, Asn(1));
+    assert_eq!(propagation.paths, paths(&[&[2, 1], &[4, 2, 1]]));
+    assert!(!propagation.truncated);
+
+    let simulation = Simulation {
+        max_depth: 8,
+        ..Simulation::default()
+    };
+    let propagation = simulation.propagate(&query, prefix, Asn(1));
+    assert_eq!(
+        propagation.reached(),
+        btreemap! {Asn(2) => 2, Asn(4) => 3, Asn(5) => 4}
+    );
+
+    let simulation = Simulation {
+        max_paths: 1,
+        ..Simulation::default()
+    };
+    let propagation = simulation.propagate(&query, prefix, Asn(1));
+    assert_eq!(propagation.paths, paths(&[&[2, 1]]));
+    assert!(propagation.truncated);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::{env::temp_dir, fs::remove_dir_all, process};
+
+use maplit::btreemap;
+use serde_json::json;
+
+use crate::{
+    api::Api,
+    bgp::QueryDump,
+    parse::{dump::pal_write_dump, *},
+    test::fixture::entry,
+};
+
+use super::*;
+
+fn dump() -> Dump {
+    let mut as1 = AutNum {
+        body: "as-name: ONE\n".into(),
+        ..AutNum::default()
+    };
+    as1.imports.any.any.push(entry(2, Filter::Any));
+    Dump {
+        aut_nums: btreemap! {Asn(1) => as1},
+        as_sets: btreemap! {
+            "AS-OUTER".into() => AsSet::new(String::new(), vec![Asn(2)], vec!["AS-INNER".into()]),
+            "AS-INNER".into() => AsSet::new(String::new(), vec![Asn(3)], vec![]),
+        },
+        as_routes: btreemap! {Asn(2) => vec!["10.0.0.0/8".parse().unwrap()]},
+        ..Dump::default()
+    }
+}
+
+#[test]
+fn endpoints() {
+    let api = Api::new("parsed".into(), QueryDump::from_dump(dump()));
+
+    let body = json!({
+        "prefix": "10.0.0.0/8",
+        "as_path": "1 2",
+        "verbosity": {"stop_at_first": false, "show_success": true},
+    });
+    let (status, value) = api.handle("POST", "/check", &body.to_string());
+    assert_eq!(status, 200);
+    assert_eq!(value["summary"], "Good");
+    assert_eq!(
+        value["reports"],
+        json!([{"GoodImport": {"from": 2, "to": 1}}])
+    );
+    assert_eq!(api.handle("POST", "/check", "{}").0, 400);
+    let body = json!({"prefix": "10.0.0.0/8", "as_path": "1 x"});
+    assert_eq!(api.handle("POST", "/check", &body.to_string()).0, 400);
+
+    let (status, value) = api.handle("GET", "/aut-num/AS1", "");
+    assert_eq!(status, 200);
+    assert_eq!(value["body"], "as-name: ONE\n");
+    assert_eq!(api.handle("GET", "/aut-num/AS9", "").0, 404);
+    assert_eq!(api.handle("GET", "/aut-num/x", "").0, 400);
+    assert_eq!(
+        api.handle("GET", "/as-set/as-inner", "").1["members"],
+        json!([3])
+    );
+    assert_eq!(api.handle("GET", "/route-set/RS-NONE", "").0, 404);
+
+    assert_eq!(
+        api.handle("GET", "/expand/AS-OUTER", "").1,
+        json!({"members": [2, 3], "unrecorded": []})
+    );
+    assert_eq!(api.handle("GET", "/status", "").1["aut_nums"], 1);
+    assert_eq!(api.handle("DELETE", "/status", "").0, 404);
+}
+
+#[test]
+fn hot_reload() -> Result<()> {
+    let parent = temp_dir().join(format!("dumps-{}", process::id()));
+    pal_write_dump(&vec![dump()], parent.join("2023-06-19"))?;
+    let api = Api::watching(parent.clone())?;
+    assert_eq!(api.current().0, parent.join("2023-06-19"));
+    assert_eq!(api.reload(false)?, None);
+
+    let mut newer = dump();
+    newer.aut_nums.insert(Asn(2), AutNum::default());
+    pal_write_dump(&vec![newer], parent.join("2023-06-20"))?;
+    assert_eq!(api.reload(false)?, Some(parent.join("2023-06-20")));
+    assert_eq!(api.handle("GET", "/status", "").1["aut_nums"], 2);
+
+    remove_dir_all(parent)?;
+    Ok(())
+}
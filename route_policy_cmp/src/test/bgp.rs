@@ -1,10 +1,33 @@
 //! This is originally copied from
 //! <https://github.com/cunha/measurements/blob/9a14123b4c9d47297fa4c284ff8dd0834ba73936/bgp/bgpmap/src/lib.rs>.
-use crate::bgp::map::{parse_table_dump, AsPathEntry};
+use std::{
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    bgp::{
+        check_dedup,
+        map::{parse_table_dump, AsPathEntry},
+        skip_bad_lines, Line, Report,
+    },
+    parse::Asn,
+};
 
 use super::*;
 
+mod aspa;
+mod bmp;
 mod cmp;
+mod intern;
+mod json;
+mod memo;
+mod normalize;
+mod record;
+mod relationship;
+mod rpki;
+mod stats;
+mod trace;
 
 #[test]
 fn table_dump() -> Result<()> {
@@ -15,24 +38,33 @@ fn table_dump() -> Result<()> {
     assert_eq!(
         aspath,
         vec![
-            AsPathEntry::Seq(34224),
-            AsPathEntry::Seq(6939),
-            AsPathEntry::Seq(8003)
+            AsPathEntry::Seq(Asn(34224)),
+            AsPathEntry::Seq(Asn(6939)),
+            AsPathEntry::Seq(Asn(8003))
         ]
     );
-    assert_eq!(*origin, AsPathEntry::Seq(8003));
-    assert_eq!(vp.asn, 34224);
+    assert_eq!(*origin, AsPathEntry::Seq(Asn(8003)));
+    assert_eq!(vp.asn, Asn(34224));
     assert_eq!(vp.ip.to_string(), "94.156.252.18");
     assert_eq!(communities, vec!["34224:333", "34224:334", "34224:2040"]);
 
+    let line = "BGP4MP|1619481601|A|94.156.252.18|34224|6.132.0.0/14|34224 6939 8003|IGP|94.156.252.18|0|0|34224:333|NAG||";
+    let (pfx, aspath, vp, _) = parse_table_dump(line)?;
+    assert_eq!(
+        (pfx.to_string(), aspath.len(), vp.asn),
+        ("6.132.0.0/14".into(), 3, Asn(34224))
+    );
+    let line = "BGP4MP|1619481601|W|94.156.252.18|34224|6.132.0.0/14";
+    assert!(parse_table_dump(line).is_err());
+
     let line = "TABLE_DUMP2|1619481601|B|94.156.252.18|34224|6.132.0.0/14|34224 {6939} 8003|IGP|94.156.252.18|0|0|34224:333 34224:334 34224:2040|NAG|||";
     let (_pfx, aspath, _vp, _) = parse_table_dump(line)?;
     assert_eq!(
         aspath,
         vec![
-            AsPathEntry::Seq(34224),
-            AsPathEntry::Set(vec![6939]),
-            AsPathEntry::Seq(8003)
+            AsPathEntry::Seq(Asn(34224)),
+            AsPathEntry::Set(vec![Asn(6939)]),
+            AsPathEntry::Seq(Asn(8003))
         ]
     );
 
@@ -41,9 +73,9 @@ fn table_dump() -> Result<()> {
     assert_eq!(
         aspath,
         vec![
-            AsPathEntry::Seq(34224),
-            AsPathEntry::Set(vec![6939, 6940]),
-            AsPathEntry::Seq(8003)
+            AsPathEntry::Seq(Asn(34224)),
+            AsPathEntry::Set(vec![Asn(6939), Asn(6940)]),
+            AsPathEntry::Seq(Asn(8003))
         ]
     );
 
@@ -52,12 +84,28 @@ fn table_dump() -> Result<()> {
     assert_eq!(
         aspath,
         vec![
-            AsPathEntry::Seq(34224),
-            AsPathEntry::Set(vec![6939, 6940]),
-            AsPathEntry::Set(vec![8003, 8004])
+            AsPathEntry::Seq(Asn(34224)),
+            AsPathEntry::Set(vec![Asn(6939), Asn(6940)]),
+            AsPathEntry::Set(vec![Asn(8003), Asn(8004)])
         ]
     );
 
+    let line = "TABLE_DUMP2|1619481601|B|94.156.252.18|34224|6.132.0.0/14|34224 (65001 65002) [65003,65004] 8003|IGP|94.156.252.18|0|0|34224:333 34224:334 34224:2040|NAG|||";
+    let (_pfx, aspath, _vp, _) = parse_table_dump(line)?;
+    assert_eq!(
+        aspath,
+        vec![
+            AsPathEntry::Seq(Asn(34224)),
+            AsPathEntry::ConfedSeq(vec![Asn(65001), Asn(65002)]),
+            AsPathEntry::ConfedSet(vec![Asn(65003), Asn(65004)]),
+            AsPathEntry::Seq(Asn(8003))
+        ]
+    );
+
+    let line = "TABLE_DUMP2|1619481601|B|94.156.252.18|34224|6.132.0.0/14|34224 (65001 65002 8003|IGP|94.156.252.18|0|0|34224:333 34224:334 34224:2040|NAG|||";
+    let res = parse_table_dump(line).unwrap_err();
+    assert_eq!(res.to_string(), "as-path-entry-no-match");
+
     let line = "TABLE_DUMP2|1619481601|B|94.156.252.18|34224|6.132.0.0/34|34224 6939 8003|IGP|94.156.252.18|0|0|34224:333 34224:334 34224:2040|NAG|||";
     let res = parse_table_dump(line).unwrap_err();
     assert_eq!(res.to_string(), "bad-prefix");
@@ -96,3 +144,82 @@ fn table_dump() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn dedup() -> Result<()> {
+    let mut lines = [
+        ("192.0.2.1", 64496, "10.0.0.0/8|64496 3333"),
+        ("192.0.2.2", 64497, "10.0.0.0/8|64496 3333"),
+        ("192.0.2.3", 64498, "10.0.0.0/8|64498 3333"),
+        ("2001:db8::1", 64496, "10.0.0.0/8|64496 3333"),
+    ]
+    .into_iter()
+    .map(|(ip, asn, route)| {
+        Line::from_raw(format!(
+            "TABLE_DUMP2|1619481601|B|{ip}|{asn}|{route}|IGP|{ip}|0|0||NAG|||"
+        ))
+    })
+    .collect::<Result<Vec<_>>>()?;
+    assert_eq!(lines[3].collector.asn, Asn(64496));
+    assert_eq!(lines[3].collector.ip.to_string(), "2001:db8::1");
+
+    let n_check = AtomicUsize::new(0);
+    let n_distinct = check_dedup(&mut lines, |line| {
+        n_check.fetch_add(1, Ordering::Relaxed);
+        line.report = Some(Vec::new());
+        let from = line.collector.asn;
+        line.leaks = Some(vec![Report::GoodSingleExport { from }]);
+        line.aspa = Some(Vec::new());
+    });
+    assert_eq!((n_distinct, n_check.into_inner()), (2, 2));
+    for (line, checked_by) in lines.iter().zip([64496, 64496, 64498, 64496]) {
+        assert_eq!(line.report, Some(Vec::new()));
+        let expected = Report::GoodSingleExport {
+            from: Asn(checked_by),
+        };
+        assert_eq!(line.leaks, Some(vec![expected]));
+        assert_eq!(line.aspa, Some(Vec::new()));
+    }
+    Ok(())
+}
+
+#[test]
+fn as_path_entry_serde() -> Result<()> {
+    let as_path = vec![
+        AsPathEntry::Seq(Asn(34224)),
+        AsPathEntry::Set(vec![Asn(6939), Asn(6940)]),
+        AsPathEntry::ConfedSeq(vec![Asn(65001), Asn(65002)]),
+        AsPathEntry::ConfedSet(vec![Asn(65003), Asn(65004)]),
+    ];
+    let json = serde_json::to_string(&as_path)?;
+    assert_eq!(
+        json,
+        r#"[34224,[6939,6940],{"confed_seq":[65001,65002]},{"confed_set":[65003,65004]}]"#
+    );
+    assert_eq!(serde_json::from_str::<Vec<AsPathEntry>>(&json)?, as_path);
+    Ok(())
+}
+
+#[test]
+fn skip_bad() -> Result<()> {
+    let lines = [
+        "BGP4MP|1687212000|A|192.0.2.1|64496|10.0.0.0/8|64496 64497|IGP|192.0.2.1|0|0||NAG||",
+        "BGP4MP|1687212000|W|192.0.2.1|64496|10.0.0.0/8",
+        "BGP4MP|1687212000|STATE|192.0.2.1|64496|1|6",
+        "BGP4MP|1687212000|A|192.0.2.1|64496|10.0.0.0/8|64496 6449x|IGP|192.0.2.1|0|0||NAG||",
+        "BGP4MP|1687212000|A|192.0.2.1|64496|11.0.0.0/8|64496 64497|IGP|192.0.2.1|0|0||NAG||",
+    ]
+    .into_iter()
+    .map(|raw| Line::from_raw(raw.into()))
+    .chain([Err(io::Error::from(io::ErrorKind::BrokenPipe).into())]);
+    let mut n_skipped = 0;
+    let lines: Vec<_> = skip_bad_lines(lines, &mut n_skipped).collect();
+    assert_eq!(n_skipped, 3);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[1].as_ref().unwrap().compare.prefix.to_string(),
+        "11.0.0.0/8"
+    );
+    assert!(lines[2].is_err());
+    Ok(())
+}
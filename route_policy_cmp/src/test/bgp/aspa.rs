@@ -0,0 +1,128 @@
+use crate::{
+    bgp::{map::AsPathEntry, Report::*, *},
+    parse::Asn,
+};
+
+use super::*;
+
+use AspaDirection::*;
+use AspaHopCheck::*;
+use AspaState::*;
+
+// AS1 and AS2 are customers of AS10, which is a customer of AS20.
+// AS3 is a customer of AS30. AS20 and AS30 peer. AS4 has no ASPA.
+const JSON: &str = r#"{"aspas": [
+    {"customer": "AS1", "providers": ["AS10"]},
+    {"customer": 2, "providers": [10]},
+    {"customer_asid": 3, "provider_set": [{"asid": 30, "afi_limit": "any"}]},
+    {"customer": "AS10", "providers": ["AS20"]},
+    {"customer": "AS20", "providers": []},
+    {"customer": "AS30", "providers": []}
+]}"#;
+
+fn aspas() -> Result<Aspas> {
+    Aspas::from_json(JSON.as_bytes())
+}
+
+fn path(nums: &[u32]) -> Vec<Asn> {
+    nums.iter().copied().map(Asn).collect()
+}
+
+#[test]
+fn hops() -> Result<()> {
+    let aspas = aspas()?;
+    assert_eq!(aspas.len(), 6);
+    assert_eq!(aspas.hop(Asn(3), Asn(30)), ProviderPlus);
+    assert_eq!(aspas.hop(Asn(1), Asn(20)), NotProviderPlus);
+    assert_eq!(aspas.hop(Asn(4), Asn(30)), NoAttestation);
+    Ok(())
+}
+
+#[test]
+fn verify() -> Result<()> {
+    let aspas = aspas()?;
+    for (nums, direction, expected) in [
+        // Customer routes going up.
+        (&[1, 10, 20][..], Upstream, Valid),
+        (&[1, 10], Upstream, Valid),
+        (&[4, 10], Upstream, Unknown),
+        // AS10 leaks the route from provider AS20 to customer AS1.
+        (&[20, 10, 1], Upstream, Invalid),
+        // Up to the peering of AS20 and AS30, then down.
+        (&[1, 10, 20, 30, 3], Downstream, Valid),
+        (&[1, 10, 20, 30], Downstream, Valid),
+        (&[4, 10, 20, 30, 3], Downstream, Unknown),
+        // Valley: down from AS10 to AS1, then up again to AS10.
+        (&[2, 10, 1, 10, 20], Downstream, Invalid),
+        (&[1, 10, 20, 30, 3, 30], Downstream, Invalid),
+    ] {
+        assert_eq!(
+            aspas.verify(&path(nums), direction),
+            expected,
+            "{nums:?} {direction:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn check_aspa() -> Result<()> {
+    let aspas = aspas()?;
+    let as_path = |nums: &[u32]| nums.iter().map(|n| AsPathEntry::Seq(Asn(*n))).collect();
+    let compare = Compare::new("10.0.0.0/8".parse()?, as_path(&[20, 10, 1]));
+    assert_eq!(compare.check_aspa(&aspas, Upstream), vec![]);
+
+    let compare = Compare::new("10.0.0.0/8".parse()?, as_path(&[1, 10, 20]));
+    assert_eq!(
+        compare.check_aspa(&aspas, Upstream),
+        vec![
+            AspaHop {
+                from: Asn(20),
+                to: Asn(10),
+                up: NotProviderPlus,
+                down: ProviderPlus,
+            },
+            AspaHop {
+                from: Asn(10),
+                to: Asn(1),
+                up: NotProviderPlus,
+                down: ProviderPlus,
+            },
+            AspaPath {
+                direction: Upstream,
+                state: Invalid,
+            },
+        ]
+    );
+
+    let compare = Compare::new(
+        "10.0.0.0/8".parse()?,
+        vec![
+            AsPathEntry::Seq(Asn(10)),
+            AsPathEntry::Set(vec![Asn(1), Asn(2)]),
+        ],
+    );
+    assert_eq!(
+        compare.check_aspa(&aspas, Upstream),
+        vec![AspaPath {
+            direction: Upstream,
+            state: Invalid,
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn line_aspa() -> Result<()> {
+    let aspas = aspas()?;
+    let mut line = Line::from_raw(
+        "TABLE_DUMP2|1619481601|B|192.0.2.1|1|10.0.0.0/8|1 10 20|IGP|192.0.2.1|0|0||NAG|||".into(),
+    )?;
+    assert!(!line.is_aspa_invalid());
+    line.check_aspa(&aspas, Upstream);
+    assert!(line.is_aspa_invalid());
+
+    let record = ReportRecord::from_line(&line)?;
+    assert_eq!(record.aspa, line.aspa);
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::{
+    env::temp_dir,
+    net::{IpAddr, Ipv4Addr},
+    process,
+    sync::Mutex,
+};
+
+use maplit::btreemap;
+
+use crate::{
+    bgp::{map::AsPathEntry::*, *},
+    fs::{check_bmp, BmpCounts},
+    parse::*,
+};
+
+use super::*;
+
+/// BMP message of `message_type` with `body` after the common header.
+fn bmp(message_type: u8, body: &[u8]) -> Vec<u8> {
+    let mut message = vec![3];
+    message.extend(((body.len() + 6) as u32).to_be_bytes());
+    message.push(message_type);
+    message.extend(body);
+    message
+}
+
+/// Per-peer header for IPv4 peer `address` in `asn`.
+fn peer_header(flags: u8, address: [u8; 4], asn: u32) -> Vec<u8> {
+    let mut header = vec![0, flags];
+    header.extend([0; 8]);
+    header.extend([0; 12]);
+    header.extend(address);
+    header.extend(asn.to_be_bytes());
+    header.extend([10, 0, 0, 1]);
+    header.extend(1687212000u32.to_be_bytes());
+    header.extend([0; 4]);
+    header
+}
+
+fn attribute(type_code: u8, value: &[u8]) -> Vec<u8> {
+    let mut attribute = vec![0x40, type_code, value.len() as u8];
+    attribute.extend(value);
+    attribute
+}
+
+fn bgp_update(withdrawn: &[u8], attributes: &[u8], nlri: &[u8]) -> Vec<u8> {
+    let mut body = (withdrawn.len() as u16).to_be_bytes().to_vec();
+    body.extend(withdrawn);
+    body.extend((attributes.len() as u16).to_be_bytes());
+    body.extend(attributes);
+    body.extend(nlri);
+    let mut update = vec![0xff; 16];
+    update.extend(((body.len() + 19) as u16).to_be_bytes());
+    update.push(2);
+    update.extend(body);
+    update
+}
+
+/// Route Monitoring from AS1 announcing 10.0.0.0/8 and 192.0.2.0/24 via
+/// `1 2 {3,4}`, and withdrawing 198.51.100.0/24.
+fn route_monitoring() -> Vec<u8> {
+    route_monitoring_with(&[])
+}
+
+/// [`route_monitoring`] with `extra` path attributes after AS_PATH.
+fn route_monitoring_with(extra: &[u8]) -> Vec<u8> {
+    let mut as_path = vec![2, 2];
+    as_path.extend([1u32, 2].iter().flat_map(|num| num.to_be_bytes()));
+    as_path.extend([1, 2]);
+    as_path.extend([3u32, 4].iter().flat_map(|num| num.to_be_bytes()));
+    let attributes = [&attribute(1, &[0]), &attribute(2, &as_path), extra].concat();
+    let update = bgp_update(&[24, 198, 51, 100], &attributes, &[8, 10, 24, 192, 0, 2]);
+    bmp(0, &[peer_header(0, [192, 0, 2, 1], 1), update].concat())
+}
+
+/// Route Monitoring from a legacy peer AS2 announcing 2001:db8::/32 in
+/// MP_REACH_NLRI via `2 23456`, with AS4_PATH `70000`.
+fn legacy_route_monitoring() -> Vec<u8> {
+    let as_path = [2, 2, 0, 2, 0x5b, 0xa0];
+    let mut as4_path = vec![2, 1];
+    as4_path.extend(70000u32.to_be_bytes());
+    let mut mp_reach = vec![0, 2, 1, 16];
+    mp_reach.extend([0x20, 0x01, 0x0d, 0xb8]);
+    mp_reach.extend([0; 12]);
+    mp_reach.push(0);
+    mp_reach.extend([32, 0x20, 0x01, 0x0d, 0xb8]);
+    let attributes = [
+        attribute(2, &as_path),
+        attribute(14, &mp_reach),
+        attribute(17, &as4_path),
+    ]
+    .concat();
+    let update = bgp_update(&[], &attributes, &[]);
+    bmp(0, &[peer_header(0x20, [192, 0, 2, 2], 2), update].concat())
+}
+
+fn captured() -> Vec<u8> {
+    [
+        bmp(4, &[0, 1, 0, 3, b'r', b't', b'r']),
+        bmp(3, &peer_header(0, [192, 0, 2, 1], 1)),
+        route_monitoring(),
+        legacy_route_monitoring(),
+    ]
+    .concat()
+}
+
+#[test]
+fn decode() -> Result<()> {
+    let messages = BmpReader::new(&captured()[..]).collect::<Result<Vec<_>>>()?;
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0], BmpMessage::Other(4));
+    let BmpMessage::PeerUp(peer) = messages[1] else {
+        panic!("Expected Peer Up, got {:?}", messages[1]);
+    };
+    assert_eq!(peer.address, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+    assert_eq!((peer.asn, peer.timestamp), (Asn(1), 1687212000));
+
+    let BmpMessage::RouteMonitoring { peer, update } = &messages[2] else {
+        panic!("Expected Route Monitoring, got {:?}", messages[2]);
+    };
+    assert_eq!(peer.collector().asn, Asn(1));
+    assert_eq!(
+        update,
+        &Update {
+            announced: vec!["10.0.0.0/8".parse()?, "192.0.2.0/24".parse()?],
+            withdrawn: vec!["198.51.100.0/24".parse()?],
+            as_path: vec![Seq(Asn(1)), Seq(Asn(2)), Set(vec![Asn(3), Asn(4)])],
+        }
+    );
+    assert_eq!(update.compares(&Normalizer::default()).len(), 2);
+
+    let BmpMessage::RouteMonitoring { peer, update } = &messages[3] else {
+        panic!("Expected Route Monitoring, got {:?}", messages[3]);
+    };
+    assert!(peer.legacy_as_path());
+    assert_eq!(update.announced, vec!["2001:db8::/32".parse::<IpNet>()?]);
+    assert_eq!(update.as_path, vec![Seq(Asn(2)), Seq(Asn(70000))]);
+
+    // AS4_PATH from a 4-byte peer is ignored.
+    let mut as4_path = vec![2, 1];
+    as4_path.extend(70000u32.to_be_bytes());
+    let message = route_monitoring_with(&attribute(17, &as4_path));
+    let Some(BmpMessage::RouteMonitoring { update: stray, .. }) =
+        BmpReader::new(&message[..]).next().transpose()?
+    else {
+        panic!("Expected Route Monitoring");
+    };
+    assert_eq!(
+        stray.as_path,
+        [Seq(Asn(1)), Seq(Asn(2)), Set(vec![Asn(3), Asn(4)])]
+    );
+
+    let mut truncated = route_monitoring();
+    truncated.pop();
+    assert!(BmpReader::new(&truncated[..]).next().unwrap().is_err());
+    Ok(())
+}
+
+#[test]
+fn check_stream() -> Result<()> {
+    let query = QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => AutNum::default()},
+        ..Dump::default()
+    });
+    let stats = Stats::new(&query);
+    let path = temp_dir().join(format!("bmp-{}.jsonl", process::id()));
+    let writer = Mutex::new(Some(RecordWriter::create(&path, RecordFormat::JsonLines)?));
+    let verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        ..Verbosity::default()
+    };
+
+    // A Route Monitoring too short for its per-peer header is skipped.
+    let mut stream = bmp(0, &[0; 3]);
+    stream.extend(captured());
+    let counts = check_bmp(
+        &stream[..],
+        &query,
+        verbosity,
+        &Normalizer::default(),
+        &stats,
+        &writer,
+    )?;
+    assert_eq!(
+        counts,
+        BmpCounts {
+            routes: 3,
+            skipped: 1
+        }
+    );
+    writer.into_inner().unwrap().unwrap().finish()?;
+    let records = std::fs::read_to_string(&path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<ReportRecord>, _>>()?;
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].path, "1 2 {3,4}");
+    assert_eq!(
+        (records[0].collector_asn, records[0].collector_ip.as_str()),
+        (Asn(1), "192.0.2.1")
+    );
+    assert_eq!(records[2].prefix, "2001:db8::/32".parse()?);
+    assert!(stats.per_as.contains_key(&(Asn(1), IpVersion::Ipv4)));
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
 use dashmap::DashMap;
-use maplit::hashmap;
+use maplit::{btreemap, hashmap};
 
 use crate::{
-    bgp::{Report::*, ReportItem::*, SkipReason::*, *},
-    parse::*,
+    bgp::{map::AsPathEntry, report::RpslError, Report::*, ReportItem::*, SkipReason::*, *},
+    parse::{RangeOperator::NoOp, *},
+    test::fixture::entry_with,
 };
 
 use super::*;
@@ -33,13 +34,13 @@ fn err_only_checks() -> Result<()> {
 fn expected_err_only_reports() -> [Vec<Report>; 1] {
     [vec![
         BadExport {
-            from: 9583,
-            to: 2914,
+            from: Asn(9583),
+            to: Asn(2914),
             items: vec![],
         },
         BadImport {
-            from: 9583,
-            to: 2914,
+            from: Asn(9583),
+            to: Asn(2914),
             items: vec![],
         },
     ]]
@@ -65,37 +66,34 @@ fn ok_skip_checks() -> Result<()> {
 fn expected_ok_skip_checks() -> [Vec<Report>; 1] {
     [vec![
         BadExport {
-            from: 9583,
-            to: 2914,
+            from: Asn(9583),
+            to: Asn(2914),
             items: vec![],
         },
         BadImport {
-            from: 9583,
-            to: 2914,
+            from: Asn(9583),
+            to: Asn(2914),
             items: vec![],
         },
         NeutralExport {
-            from: 2914,
-            to: 1239,
-            items: vec![
-                Skip(AsSetUnrecorded("AS-ANY".into())),
-                Skip(AsSetRouteUnrecorded("AS2914:AS-GLOBAL".into())),
-            ],
+            from: Asn(2914),
+            to: Asn(1239),
+            items: vec![Skip(AsSetRouteUnrecorded("AS2914:AS-GLOBAL".into()))],
         },
         NeutralImport {
-            from: 2914,
-            to: 1239,
-            items: vec![Skip(AutNumUnrecorded(1239))],
+            from: Asn(2914),
+            to: Asn(1239),
+            items: vec![Skip(AutNumUnrecorded(Asn(1239)))],
         },
         NeutralExport {
-            from: 1239,
-            to: 3130,
-            items: vec![Skip(AutNumUnrecorded(1239))],
+            from: Asn(1239),
+            to: Asn(3130),
+            items: vec![Skip(AutNumUnrecorded(Asn(1239)))],
         },
         NeutralImport {
-            from: 1239,
-            to: 3130,
-            items: vec![Skip(AutNumUnrecorded(3130))],
+            from: Asn(1239),
+            to: Asn(3130),
+            items: vec![Skip(AutNumUnrecorded(Asn(3130)))],
         },
     ]]
 }
@@ -107,15 +105,15 @@ fn stats() -> Result<()> {
         let map = DashMap::new();
         let mut compare = Compare::with_line_dump(line)?;
         compare.as_stats(&query, &map);
-        let actual = HashMap::from_iter(map.into_iter());
+        let actual = HashMap::from_iter(map);
         assert_eq!(expected, actual);
     }
     Ok(())
 }
 
-fn expected_stats() -> [HashMap<usize, AsStats>; 1] {
+fn expected_stats() -> [HashMap<Asn, AsStats>; 1] {
     [
-        hashmap! {3130=> AsStats { import_ok: 0, export_ok: 0, import_skip: 1, export_skip: 0, import_err: 0, export_err: 0 }, 1239=> AsStats { import_ok: 0, export_ok: 0, import_skip: 1, export_skip: 1, import_err: 0, export_err: 0 }, 9583=> AsStats { import_ok: 0, export_ok: 0, import_skip: 0, export_skip: 0, import_err: 0, export_err: 1 }, 2914=> AsStats { import_ok: 0, export_ok: 0, import_skip: 0, export_skip: 1, import_err: 1, export_err: 0 }},
+        hashmap! {Asn(3130)=> AsStats { import_ok: 0, export_ok: 0, import_skip: 1, export_skip: 0, import_err: 0, export_err: 0 }, Asn(1239)=> AsStats { import_ok: 0, export_ok: 0, import_skip: 1, export_skip: 1, import_err: 0, export_err: 0 }, Asn(9583)=> AsStats { import_ok: 0, export_ok: 0, import_skip: 0, export_skip: 0, import_err: 0, export_err: 1 }, Asn(2914)=> AsStats { import_ok: 0, export_ok: 0, import_skip: 0, export_skip: 1, import_err: 1, export_err: 0 }},
     ]
 }
 
@@ -123,3 +121,208 @@ fn query() -> Result<QueryDump> {
     let dump: Dump = serde_json::from_str(DUMP)?;
     Ok(QueryDump::from_dump(dump))
 }
+
+#[test]
+fn route_set_with_as_members() -> Result<()> {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            Asn(1) => AutNum {
+                imports: serde_json::from_str(r#"{"any":{"any":[{"mp_peerings":[{"mp_peering":{"remote_as":{"Single":{"Set":"AS-ANY"}}}}],"mp_filter":{"RouteSet":["RS-PEERS","NoOp"]}}]}}"#)?,
+                ..AutNum::default()
+            },
+        },
+        as_sets: btreemap! {"AS-CUSTOMERS".into() => AsSet::new(String::new(), vec![Asn(3)], vec![])},
+        route_sets: btreemap! {
+            "RS-PEERS".into() => RouteSet {
+                body: String::new(),
+                members: vec!["AS2^+".to_string().into(), "AS-CUSTOMERS".to_string().into()],
+            },
+        },
+        as_routes: btreemap! {
+            Asn(2) => vec!["10.0.0.0/8".parse()?],
+            Asn(3) => vec!["192.168.0.0/16".parse()?],
+        },
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let verbosity = Verbosity {
+        stop_at_first: false,
+        show_success: true,
+        ..Verbosity::default()
+    };
+    for (prefix, as_path, good) in [
+        ("10.1.0.0/16", vec![1, 2], true),
+        ("192.168.0.0/16", vec![1, 3], true),
+        ("192.168.1.0/24", vec![1, 3], false),
+    ] {
+        let as_path = as_path
+            .into_iter()
+            .map(|asn| AsPathEntry::Seq(Asn(asn)))
+            .collect();
+        let compare = Compare::new(prefix.parse()?, as_path).verbosity(verbosity);
+        let import_report = compare.check(&query).pop();
+        let from = compare.as_path[1].to_string().parse()?;
+        let expected = match good {
+            true => GoodImport { from, to: Asn(1) },
+            false => BadImport {
+                from,
+                to: Asn(1),
+                items: vec![],
+            },
+        };
+        assert_eq!(import_report, Some(expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn three_valued_not_except() -> Result<()> {
+    use crate::parse::AsName;
+
+    let not = |filter: Filter| Filter::Not(Box::new(filter));
+    let except = |left: AsName, right: AsName| AsExpr::Except {
+        left: Box::new(AsExpr::Single(left)),
+        right: Box::new(AsExpr::Single(right)),
+    };
+    let any_as = || AsName::Set("AS-ANY".into());
+    let dump = Dump {
+        as_routes: btreemap! {Asn(2) => vec!["10.0.0.0/8".parse()?]},
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        show_success: true,
+        ..Verbosity::default()
+    };
+
+    for (entry, expected) in [
+        // NOT true is false.
+        (
+            entry_with(AsExpr::Single(any_as()), not(Filter::AsNum(Asn(2), NoOp))),
+            BadImport {
+                from: Asn(2),
+                to: Asn(1),
+                items: vec![],
+            },
+        ),
+        // NOT false is true.
+        (
+            entry_with(
+                AsExpr::Single(any_as()),
+                not(Filter::AddrPrefixSet(vec!["1.0.0.0/8".parse()?])),
+            ),
+            GoodImport {
+                from: Asn(2),
+                to: Asn(1),
+            },
+        ),
+        // NOT unknown is unknown, with the reason kept.
+        (
+            entry_with(
+                AsExpr::Single(any_as()),
+                not(Filter::AsSet("AS-MISSING".into(), NoOp)),
+            ),
+            NeutralImport {
+                from: Asn(2),
+                to: Asn(1),
+                items: vec![Skip(AsSetRouteUnrecorded("AS-MISSING".into()))],
+            },
+        ),
+        // NOT invalid is unknown rather than true.
+        (
+            entry_with(AsExpr::Single(any_as()), not(Filter::Invalid("bad".into()))),
+            NeutralImport {
+                from: Asn(2),
+                to: Asn(1),
+                items: vec![BadRpsl(RpslError::InvalidFilter("bad".into()))],
+            },
+        ),
+        // AS-ANY EXCEPT AS2 excludes AS2.
+        (
+            entry_with(except(any_as(), AsName::Num(Asn(2))), Filter::Any),
+            BadImport {
+                from: Asn(2),
+                to: Asn(1),
+                items: vec![],
+            },
+        ),
+        // AS-ANY EXCEPT AS3 includes AS2.
+        (
+            entry_with(except(any_as(), AsName::Num(Asn(3))), Filter::Any),
+            GoodImport {
+                from: Asn(2),
+                to: Asn(1),
+            },
+        ),
+        // AS-ANY EXCEPT AS-MISSING is unknown.
+        (
+            entry_with(
+                except(any_as(), AsName::Set("AS-MISSING".into())),
+                Filter::Any,
+            ),
+            NeutralImport {
+                from: Asn(2),
+                to: Asn(1),
+                items: vec![Skip(AsSetUnrecorded("AS-MISSING".into()))],
+            },
+        ),
+    ] {
+        let mut aut_num = AutNum::default();
+        aut_num.imports.any.any.push(entry);
+        let compare = Compare::new(
+            "10.0.0.0/8".parse()?,
+            vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))],
+        )
+        .verbosity(verbosity);
+        let actual = compare.check_import(&query, &aut_num, Asn(2), Asn(1));
+        assert_eq!(actual, Some(expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn cite_attributes() -> Result<()> {
+    let export = Entry {
+        index: Some(3),
+        ..entry_with(
+            AsExpr::Single(AsName::Set("AS-ANY".into())),
+            Filter::AddrPrefixSet(vec!["192.0.2.0/24".parse()?]),
+        )
+    };
+    let mut aut_num = AutNum {
+        body: "as-name: NTT\nmp-import: from AS50472 accept AS-CHAOS\nremarks: # comment\nmp-export: to AS-ANY\n announce {192.0.2.0/24}\n".into(),
+        ..AutNum::default()
+    };
+    aut_num.exports.any.any.push(export);
+    let dump = Dump {
+        aut_nums: btreemap! {Asn(2914) => aut_num},
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let compare = Compare::new(
+        "10.0.0.0/8".parse()?,
+        vec![AsPathEntry::Seq(Asn(1239)), AsPathEntry::Seq(Asn(2914))],
+    )
+    .verbosity(Verbosity {
+        per_entry_err: true,
+        ..Verbosity::default()
+    });
+
+    let report = compare.check_pair(&query, Asn(2914), Asn(1239));
+    let expected = BadExport {
+        from: Asn(2914),
+        to: Asn(1239),
+        items: vec![
+            NoMatch(MatchProblem::Filter),
+            NoMatch(MatchProblem::Attribute(3)),
+        ],
+    };
+    assert_eq!(report, vec![expected]);
+    assert_eq!(
+        report[0].citations(&query),
+        vec!["AS2914 mp-export attribute #4: `to AS-ANY announce {192.0.2.0/24}`"]
+    );
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use maplit::btreemap;
+
+use crate::{bgp::*, parse::*};
+
+fn as_set_filter(name: &str) -> Entry {
+    Entry {
+        mp_peerings: vec![],
+        mp_filter: Filter::AsSet(name.into(), RangeOperator::NoOp),
+        index: None,
+    }
+}
+
+/// AS1 and AS2 both import AS-ONE, and AS2 also the unrecorded AS-MISSING.
+/// FLTR-ONE is AS-ONE or AS-TWO.
+fn dump(as_sets: &[&str]) -> Dump {
+    let mut as1 = AutNum::default();
+    as1.imports.any.any.push(as_set_filter("AS-ONE"));
+    let mut as2 = as1.clone();
+    as2.imports.any.any.push(as_set_filter("AS-MISSING"));
+    let filter = Filter::Or {
+        left: Box::new(Filter::AsSet("AS-ONE".into(), RangeOperator::NoOp)),
+        right: Box::new(Filter::AsSet("AS-TWO".into(), RangeOperator::NoOp)),
+    };
+    Dump {
+        aut_nums: btreemap! {Asn(1) => as1, Asn(2) => as2},
+        as_sets: as_sets
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    AsSet::new(String::new(), vec![Asn(3)], vec![]),
+                )
+            })
+            .collect(),
+        filter_sets: btreemap! {"FLTR-ONE".into() => FilterSet {
+            body: String::new(),
+            filters: vec![filter],
+        }},
+        ..Dump::default()
+    }
+}
+
+fn import_name(query: &QueryDump, num: u32, index: usize) -> &SetName {
+    match &query.aut_nums[&Asn(num)].imports.any.any[index].mp_filter {
+        Filter::AsSet(name, _) => name,
+        filter => panic!("Expected an as-set filter, got {filter:?}"),
+    }
+}
+
+#[test]
+fn resolve_references() {
+    let query = QueryDump::from_dump(dump(&["AS-ONE", "AS-TWO"]));
+    let (one, also_one) = (import_name(&query, 1, 0), import_name(&query, 2, 0));
+    assert_ne!(one.symbol, Symbol::UNRESOLVED);
+    assert_eq!(one.symbol, also_one.symbol);
+    assert!(Arc::ptr_eq(one.shared(), also_one.shared()));
+    assert_eq!(query.as_set_routes.get(one).unwrap().routes, vec![]);
+    assert!(query.as_sets.get(one).is_some());
+
+    let missing = import_name(&query, 2, 1);
+    assert_ne!(missing.symbol, Symbol::UNRESOLVED);
+    assert!(query.as_sets.get(missing).is_none());
+    assert!(query.filter_sets.get("FLTR-ONE").is_some());
+    assert!(query.as_sets.get(&SetName::from("AS-TWO")).is_some());
+    assert_eq!(
+        query.as_sets.keys().collect::<Vec<_>>(),
+        vec!["AS-ONE", "AS-TWO"]
+    );
+
+    let memory = query.name_memory;
+    assert_eq!((memory.references, memory.unique), (5, 4));
+    assert!(memory.bytes_after > 0 && memory.bytes_before > 0);
+
+    // Keys of the set tables count too.
+    let smaller = QueryDump::from_dump(dump(&["AS-ONE"])).name_memory;
+    assert_eq!(smaller.references, memory.references);
+    assert!(smaller.bytes_before < memory.bytes_before);
+    assert!(smaller.bytes_after < memory.bytes_after);
+}
+
+#[test]
+fn foreign_symbols() {
+    let query = QueryDump::from_dump(dump(&["AS-ONE", "AS-TWO"]));
+    // Symbols of `other` point to different names in `query`.
+    let other = QueryDump::from_dump(dump(&["AS-MISSING", "AS-TWO"]));
+    let missing = import_name(&other, 2, 1);
+    assert!(other.as_sets.get(missing).is_some());
+    assert!(query.as_sets.get(missing).is_none());
+    let one = import_name(&other, 1, 0);
+    assert!(query.as_sets.get(one).is_some());
+}
@@ -0,0 +1,145 @@
+use serde_json::json;
+
+use crate::{
+    bgp::{map::AsPathEntry::*, *},
+    parse::Asn,
+};
+
+use super::*;
+
+fn ris_message() -> serde_json::Value {
+    json!({
+        "type": "ris_message",
+        "data": {
+            "timestamp": 1687212000.53,
+            "peer": "192.0.2.1",
+            "peer_asn": "64496",
+            "id": "21-192-0-2-1-1",
+            "host": "rrc21.ripe.net",
+            "type": "UPDATE",
+            "path": [64496, 64497, [64498, 64499]],
+            "community": [[64496, 1], [64497, 2]],
+            "origin": "igp",
+            "announcements": [
+                {"next_hop": "192.0.2.1", "prefixes": ["10.0.0.0/8", "10.1.0.0/16"]},
+                {"next_hop": "2001:db8::1", "prefixes": ["2001:db8::/32"]},
+            ],
+            "withdrawals": ["198.51.100.0/24"],
+        },
+    })
+}
+
+#[test]
+fn ris_live() -> Result<()> {
+    let message = ris_message();
+    let routes = JsonFormat::RisLive.routes(&message.to_string())?;
+    assert_eq!(routes.len(), 3);
+    assert_eq!(
+        routes[0].to_line_dump(),
+        "BGP4MP|1687212000|A|192.0.2.1|64496|10.0.0.0/8|64496 64497 {64498,64499}|IGP|192.0.2.1|0|0|64496:1 64497:2|NAG||"
+    );
+    assert_eq!(routes[2].next_hop, "2001:db8::1");
+
+    let bare = JsonFormat::RisLive.routes(&message["data"].to_string())?;
+    assert_eq!(bare, routes);
+
+    let keepalive = json!({"type": "ris_message", "data": {
+        "timestamp": 1687212000.0, "peer": "192.0.2.1", "peer_asn": "64496", "type": "KEEPALIVE",
+    }});
+    assert!(JsonFormat::RisLive
+        .routes(&keepalive.to_string())?
+        .is_empty());
+    let pong = json!({"type": "pong", "data": null});
+    assert!(JsonFormat::RisLive.routes(&pong.to_string())?.is_empty());
+    assert!(JsonFormat::RisLive.routes("{").is_err());
+    Ok(())
+}
+
+fn exabgp_message(as_path: serde_json::Value, nlris: serde_json::Value) -> String {
+    json!({
+        "exabgp": "4.0.1",
+        "time": 1687212000.0,
+        "host": "collector",
+        "type": "update",
+        "neighbor": {
+            "address": {"local": "192.0.2.254", "peer": "192.0.2.2"},
+            "asn": {"local": 64511, "peer": 64500},
+            "direction": "receive",
+            "message": {"update": {
+                "attribute": {"origin": "incomplete", "as-path": as_path, "community": [[64500, 3]]},
+                "announce": {
+                    "ipv4 unicast": {"192.0.2.2": nlris},
+                    "ipv4 flow": {"no-nexthop": [{"destination-ipv4": ["10.0.0.0/8"]}]},
+                },
+            }},
+        },
+    })
+    .to_string()
+}
+
+#[test]
+fn exabgp() -> Result<()> {
+    let flat = exabgp_message(json!([64500, 64501]), json!([{"nlri": "10.2.0.0/16"}]));
+    let routes = JsonFormat::ExaBgp.routes(&flat)?;
+    assert_eq!(routes.len(), 1);
+    assert_eq!(
+        routes[0].to_line_dump(),
+        "BGP4MP|1687212000|A|192.0.2.2|64500|10.2.0.0/16|64500 64501|INCOMPLETE|192.0.2.2|0|0|64500:3|NAG||"
+    );
+
+    let segments = exabgp_message(
+        json!({
+            "0": {"element": "as-sequence", "value": [64500]},
+            "1": {"element": "as-set", "value": [64502, 64503]},
+        }),
+        json!({"10.3.0.0/16": {}, "10.4.0.0/16": {}}),
+    );
+    let routes = JsonFormat::ExaBgp.routes(&segments)?;
+    assert_eq!(routes.len(), 2);
+    assert_eq!(
+        routes[1].as_path,
+        vec![Seq(Asn(64500)), Set(vec![Asn(64502), Asn(64503)])]
+    );
+
+    let state = json!({"exabgp": "4.0.1", "time": 1687212000.0, "type": "state"});
+    assert!(JsonFormat::ExaBgp.routes(&state.to_string())?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn stream() -> Result<()> {
+    let input = format!("{}\n\n{}\n", ris_message(), json!({"type": "ris_message"}));
+    let lines: Vec<_> =
+        stream_json_lines(input.as_bytes(), JsonFormat::RisLive, Normalizer::default()).collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].is_err());
+
+    let line = lines.into_iter().next().unwrap()?;
+    assert_eq!(line.compare.prefix, "10.0.0.0/8".parse()?);
+    assert_eq!(line.compare.origin(), None);
+    let record = ReportRecord::from_line(&line)?;
+    assert_eq!(
+        (record.collector_asn, record.path.as_str()),
+        (Asn(64496), "64496 64497 {64498,64499}")
+    );
+    assert_eq!(record.timestamp, 1687212000);
+    assert_eq!(record.communities, ["64496:1", "64497:2"]);
+    Ok(())
+}
+
+#[test]
+fn skip_malformed() -> Result<()> {
+    let input = format!(
+        "{}
+not json
+{}
+",
+        ris_message(),
+        ris_message()
+    );
+    let lines = stream_json_lines(input.as_bytes(), JsonFormat::RisLive, Normalizer::default());
+    let mut n_skipped = 0;
+    let lines = skip_bad_lines(lines, &mut n_skipped).collect::<Result<Vec<_>>>()?;
+    assert_eq!((lines.len(), n_skipped), (6, 1));
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use maplit::btreemap;
+
+use crate::{
+    bgp::{map::AsPathEntry, *},
+    parse::*,
+    test::fixture::entry,
+};
+
+use super::*;
+
+/// AS1 imports 10.0.0.0/8 from AS2, which exports the routes of AS3 to AS1.
+/// The routes of AS3 are unrecorded, so AS2's filter depends on the AS path.
+fn query() -> Result<QueryDump> {
+    let mut as1 = AutNum::default();
+    let prefixes = Filter::AddrPrefixSet(vec!["10.0.0.0/8".parse()?]);
+    as1.imports.any.any.push(entry(2, prefixes));
+    let mut as2 = AutNum::default();
+    as2.exports
+        .any
+        .any
+        .push(entry(1, Filter::AsNum(Asn(3), RangeOperator::NoOp)));
+    Ok(QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => as1, Asn(2) => as2},
+        ..Dump::default()
+    }))
+}
+
+#[test]
+fn same_reports() -> Result<()> {
+    let query = query()?;
+    let verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        show_success: true,
+        ..Verbosity::default()
+    };
+    let cache = PairCache::new(&query, verbosity);
+    let compares = [
+        ("10.0.0.0/8", 3),
+        ("10.0.0.0/8", 4),
+        ("192.0.2.0/24", 3),
+        ("10.0.0.0/8", 3),
+    ]
+    .into_iter()
+    .map(|(prefix, origin)| {
+        let as_path = [1, 2, origin]
+            .into_iter()
+            .map(|num| AsPathEntry::Seq(Asn(num)))
+            .collect();
+        Ok(Compare::new(prefix.parse()?, as_path).verbosity(verbosity))
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+    let reports: Vec<_> = compares.iter().map(|c| c.check(&query)).collect();
+    let cached: Vec<_> = compares.iter().map(|c| c.check_cached(&cache)).collect();
+    assert_eq!(cached, reports);
+    assert_ne!(reports[0], reports[1]);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            peering_hits: 6,
+            peering_misses: 2,
+            filter_hits: 2,
+            filter_misses: 6,
+        }
+    );
+
+    let traced = compares[0].clone().verbosity(Verbosity {
+        trace: true,
+        ..verbosity
+    });
+    assert!(!cache.accepts(&traced));
+    assert_eq!(traced.check_cached(&cache), traced.check(&query));
+    assert_eq!(cache.stats().peering_misses, 2);
+    Ok(())
+}
+
+#[test]
+fn filter_limit() -> Result<()> {
+    let query = query()?;
+    let mut cache = PairCache::new(&query, Verbosity::default());
+    cache.filter_limit = 1;
+    for prefix in ["10.0.0.0/8", "192.0.2.0/24", "10.0.0.0/8", "192.0.2.0/24"] {
+        let as_path = vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))];
+        Compare::new(prefix.parse()?, as_path).check_cached(&cache);
+    }
+    // Only the first filter result is cached, and hit on the third route.
+    let stats = cache.stats();
+    assert_eq!((stats.filter_hits, stats.filter_misses), (1, 7));
+    Ok(())
+}
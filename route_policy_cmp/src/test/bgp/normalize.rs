@@ -0,0 +1,71 @@
+use crate::{
+    bgp::{map::AsPathEntry::*, normalize::*},
+    parse::Asn,
+};
+
+#[test]
+fn default_removes_prepending() {
+    let (as_path, normalizations) = Normalizer::default().normalize(vec![
+        Seq(Asn(1)),
+        Seq(Asn(2)),
+        Seq(Asn(2)),
+        Seq(Asn(2)),
+        Seq(Asn(65000)),
+    ]);
+    assert_eq!(as_path, vec![Seq(Asn(1)), Seq(Asn(2)), Seq(Asn(65000))]);
+    assert_eq!(
+        normalizations,
+        vec![Normalization::Prepending {
+            entry: Seq(Asn(2)),
+            removed: 2
+        }]
+    );
+}
+
+#[test]
+fn default_expands_single_sets() {
+    let (as_path, normalizations) =
+        Normalizer::default().normalize(vec![Seq(Asn(1)), Set(vec![Asn(2)])]);
+    assert_eq!(as_path, vec![Seq(Asn(1)), Seq(Asn(2))]);
+    assert_eq!(normalizations, vec![Normalization::SingleSet(Asn(2))]);
+}
+
+#[test]
+fn remove_all() {
+    let normalizer = Normalizer {
+        remove_prepending: true,
+        remove_private: true,
+        remove_reserved: true,
+        remove_confed: true,
+        expand_single_sets: true,
+    };
+    let (as_path, normalizations) = normalizer.normalize(vec![
+        Seq(Asn(1)),
+        Seq(Asn(64512)),
+        Seq(Asn(1)),
+        ConfedSeq(vec![Asn(65001), Asn(65002)]),
+        Seq(Asn(23456)),
+        Set(vec![Asn(2), Asn(4_200_000_000)]),
+        Set(vec![Asn(3), Asn(4)]),
+        Seq(Asn(0)),
+    ]);
+    assert_eq!(
+        as_path,
+        vec![Seq(Asn(1)), Seq(Asn(2)), Set(vec![Asn(3), Asn(4)])]
+    );
+    assert_eq!(
+        normalizations,
+        vec![
+            Normalization::PrivateAsn(Asn(64512)),
+            Normalization::Prepending {
+                entry: Seq(Asn(1)),
+                removed: 1
+            },
+            Normalization::ConfedSeq(vec![Asn(65001), Asn(65002)]),
+            Normalization::AsTrans,
+            Normalization::PrivateAsn(Asn(4_200_000_000)),
+            Normalization::SingleSet(Asn(2)),
+            Normalization::ReservedAsn(Asn(0)),
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+use std::{env::temp_dir, fs::File, process};
+
+use polars::prelude::{CsvReader, ParquetReader, SerReader, TakeRandomUtf8};
+
+use crate::bgp::{record::*, *};
+
+use super::*;
+
+const LINE: &str = "TABLE_DUMP2|1687212000|B|147.28.7.1|3130|1.6.165.0/24|3130 1239 2914 9583|IGP|147.28.7.1|0|0|1239:321 1239:1000 1239:1010|NAG||";
+
+fn record() -> Result<ReportRecord> {
+    let mut line = Line::from_raw(LINE.into())?;
+    line.compare.verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        ..Verbosity::default()
+    };
+    line.check(&QueryDump::default());
+    ReportRecord::from_line(&line)
+}
+
+#[test]
+fn summarize() -> Result<()> {
+    let record = record()?;
+    assert_eq!(record.prefix.to_string(), "1.6.165.0/24");
+    assert_eq!(record.path, "3130 1239 2914 9583");
+    assert_eq!(record.collector_asn, Asn(3130));
+    assert_eq!(record.collector_ip, "147.28.7.1");
+    assert_eq!(record.timestamp, 1687212000);
+    assert_eq!(record.communities, ["1239:321", "1239:1000", "1239:1010"]);
+    assert_eq!(record.summary, Summary::Neutral);
+    assert_eq!(Summary::of(&[]), Summary::Good);
+    Ok(())
+}
+
+#[test]
+fn path_as_bgpdump() -> Result<()> {
+    let path = "3130 {1239,2914} (65001 65002) [65003,65004] 9583";
+    let line = Line::from_raw(LINE.replace("3130 1239 2914 9583", path))?;
+    assert_eq!(ReportRecord::from_line(&line)?.path, path);
+    Ok(())
+}
+
+#[test]
+fn write_formats() -> Result<()> {
+    let record = record()?;
+    for format in ["jsonl", "csv", "parquet"] {
+        let path = temp_dir().join(format!("records-{}.{format}", process::id()));
+        let mut writer = RecordWriter::create(&path, RecordFormat::from_path(&path)?)?;
+        writer.write_batch(&[record.clone(), record.clone()])?;
+        writer.write_batch(std::slice::from_ref(&record))?;
+        writer.finish()?;
+
+        match format {
+            "jsonl" => {
+                let content = std::fs::read_to_string(&path)?;
+                let records = content
+                    .lines()
+                    .map(serde_json::from_str)
+                    .collect::<serde_json::Result<Vec<ReportRecord>>>()?;
+                assert_eq!(records, vec![record.clone(); 3]);
+            }
+            "csv" => {
+                let df = CsvReader::new(File::open(&path)?).finish()?;
+                assert_eq!(df.shape(), (3, 11));
+            }
+            _ => {
+                let df = ParquetReader::new(File::open(&path)?).finish()?;
+                assert_eq!(df.shape(), (3, 11));
+                assert_eq!(
+                    df.column("communities")?.utf8()?.get(0),
+                    Some("1239:321 1239:1000 1239:1010")
+                );
+                assert_eq!(
+                    df.column("path")?.utf8()?.get(2),
+                    Some(record.path.as_str())
+                );
+            }
+        }
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
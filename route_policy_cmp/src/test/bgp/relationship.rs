@@ -0,0 +1,82 @@
+use crate::{
+    bgp::{Report::*, *},
+    parse::Asn,
+};
+
+use super::*;
+
+use LeakType::*;
+
+const AS_REL: &str = "# source:topology|BGP
+10|1|-1
+10|2|-1
+20|10|-1
+40|10|-1
+30|3|-1
+20|30|0
+50|20|0
+3|4|0
+60|99|-1|bgp
+";
+
+#[test]
+fn read_as_rel() -> Result<()> {
+    let rels = AsRelDb::from_caida(AS_REL.as_bytes())?;
+    assert_eq!(rels.len(), 9);
+    assert_eq!(
+        rels.get(Asn(1), Asn(10)),
+        Some(Relationship::CustomerToProvider)
+    );
+    assert_eq!(
+        rels.get(Asn(10), Asn(1)),
+        Some(Relationship::ProviderToCustomer)
+    );
+    assert_eq!(rels.get(Asn(30), Asn(20)), Some(Relationship::PeerToPeer));
+    assert_eq!(rels.get(Asn(1), Asn(2)), None);
+    assert!(AsRelDb::from_caida("1|2|1".as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn check_leaks() -> Result<()> {
+    let rels = AsRelDb::from_caida(AS_REL.as_bytes())?;
+    let verbosity = Verbosity {
+        show_skips: true,
+        ..Verbosity::default()
+    };
+    let leak = |from, leaker, to, leak_type| RouteLeak {
+        from: Asn(from),
+        leaker: Asn(leaker),
+        to: Asn(to),
+        leak_type,
+    };
+    for (line, expected) in [
+        ("30 20 10 1", vec![]),
+        ("3 30 20 10 1", vec![]),
+        ("40 10 20", vec![leak(20, 10, 40, HairpinTurn)]),
+        ("50 20 30", vec![leak(30, 20, 50, Lateral)]),
+        ("4 3 30", vec![leak(30, 3, 4, ProviderToPeer)]),
+        ("30 3 4", vec![leak(4, 3, 30, PeerToProvider)]),
+        (
+            "60 99 10 20",
+            vec![
+                RelationshipUnknown {
+                    from: Asn(10),
+                    to: Asn(99),
+                },
+                ValleyFreeViolation {
+                    from: Asn(99),
+                    to: Asn(60),
+                },
+            ],
+        ),
+    ] {
+        let as_path = line
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        let compare = Compare::new("10.0.0.0/8".parse()?, as_path).verbosity(verbosity);
+        assert_eq!(compare.check_leaks(&rels), expected, "{line}");
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::bgp::{map::AsPathEntry, *};
+
+use super::*;
+
+const JSON: &str = r#"{
+  "metadata": {"generated": 1687212000},
+  "roas": [
+    {"asn": "AS13335", "prefix": "1.0.0.0/24", "maxLength": 24, "ta": "apnic"},
+    {"asn": 2914, "prefix": "10.0.0.0/8", "maxLength": 16, "ta": "arin", "expires": 1687300000},
+    {"asn": "AS0", "prefix": "2001:db8::/32", "maxLength": 48, "ta": "ripe"}
+  ]
+}"#;
+
+const CSV: &str = "ASN,IP Prefix,Max Length,Trust Anchor
+AS13335,1.0.0.0/24,24,apnic
+AS2914,10.0.0.0/8,16,arin
+AS0,2001:db8::/32,48,ripe
+";
+
+#[test]
+fn read_vrps() -> Result<()> {
+    let json = Vrps::from_json(JSON.as_bytes())?;
+    let csv = Vrps::from_csv(CSV.as_bytes())?;
+    assert_eq!(json, csv);
+    assert_eq!(json.len(), 3);
+    assert!(Vrps::from_csv("AS1,10.0.0.0/8,7,arin".as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn validate() -> Result<()> {
+    use RpkiState::*;
+    let vrps = Vrps::from_csv(CSV.as_bytes())?;
+    for (prefix, as_path, expected) in [
+        ("1.0.0.0/24", "3130 13335", Valid),
+        ("1.0.0.0/24", "3130 1239", Invalid),
+        ("10.1.0.0/16", "3130 2914", Valid),
+        ("10.1.1.0/24", "3130 2914", Invalid),
+        ("10.1.0.0/16", "3130 {2914}", Valid),
+        ("10.1.0.0/16", "3130 {2914,3356}", Invalid),
+        ("2001:db8:1::/48", "3130 64496", Invalid),
+        ("2001:db9::/32", "3130 64496", NotFound),
+        ("8.8.8.0/24", "3130 15169", NotFound),
+    ] {
+        let as_path = as_path
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<AsPathEntry>>>()?;
+        let compare = Compare::new(prefix.parse()?, as_path);
+        assert_eq!(compare.rpki_state(&vrps), expected, "{prefix} {compare:?}");
+    }
+    Ok(())
+}
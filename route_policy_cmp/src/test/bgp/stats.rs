@@ -0,0 +1,145 @@
+use maplit::btreemap;
+
+use crate::{
+    bgp::{
+        map::{AsPathEntry, CollectorPeer},
+        *,
+    },
+    parse::*,
+    test::fixture::entry,
+};
+
+use super::*;
+
+fn query() -> QueryDump {
+    let mut as1 = AutNum {
+        body: "as-name: ONE\nsource: ripe\n".into(),
+        ..AutNum::default()
+    };
+    as1.imports.any.any.push(entry(3, Filter::Any));
+    let mut as2 = AutNum {
+        body: "as-name: TWO\nsource: ARIN\n".into(),
+        ..AutNum::default()
+    };
+    as2.exports.any.any.push(entry(
+        1,
+        Filter::AsSet("AS-MISSING".into(), RangeOperator::NoOp),
+    ));
+    QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => as1, Asn(2) => as2},
+        ..Dump::default()
+    })
+}
+
+#[test]
+fn aggregate() -> Result<()> {
+    let query = query();
+    let stats = Stats::new(&query);
+    let mut compare = Compare::new(
+        "10.0.0.0/8".parse()?,
+        vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))],
+    );
+    let verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        show_success: true,
+        ..Verbosity::default()
+    };
+    compare.verbosity = Stats::verbosity(verbosity)?;
+    assert!(compare.verbosity.all_err);
+    let reports = compare.check(&query);
+    stats.record(&compare, &reports);
+    stats.record(&compare, &reports);
+
+    let v4 = IpVersion::Ipv4;
+    let as1 = stats.per_as.get(&(Asn(1), v4)).unwrap().clone();
+    assert_eq!((as1.import.ok, as1.import.skip, as1.import.err), (0, 0, 2));
+    assert_eq!(as1.import.items, btreemap! {"RemoteAsNum" => 2});
+    assert_eq!(as1.export, Counts::default());
+    let as2 = stats.per_as.get(&(Asn(2), v4)).unwrap().clone();
+    assert_eq!((as2.export.ok, as2.export.skip, as2.export.err), (0, 2, 0));
+    assert_eq!(as2.export.items, btreemap! {"AsSetRouteUnrecorded" => 2});
+
+    let pair = stats.per_pair.get(&(Asn(2), Asn(1), v4)).unwrap().clone();
+    assert_eq!(pair.import, as1.import);
+    assert_eq!(pair.export, as2.export);
+
+    let ripe = stats
+        .per_registry
+        .get(&("RIPE".into(), v4))
+        .unwrap()
+        .clone();
+    assert_eq!(ripe, as1);
+    let as_set = stats
+        .per_as_set
+        .get(&("AS-MISSING".into(), v4))
+        .unwrap()
+        .clone();
+    assert_eq!((as_set.skip, as_set.items.len()), (2, 1));
+    assert!(stats.per_as.get(&(Asn(1), IpVersion::Ipv6)).is_none());
+
+    let df = stats.as_df()?;
+    assert_eq!(df.shape(), (4, 6 + ReportItem::NAMES.len()));
+    assert_eq!(stats.pair_df()?.height(), 2);
+    assert_eq!(stats.as_set_df()?.height(), 1);
+    assert_eq!(stats.registry_df()?.height(), 4);
+
+    let hide_success = Verbosity {
+        show_success: false,
+        ..verbosity
+    };
+    assert!(Stats::verbosity(hide_success).is_err());
+    Ok(())
+}
+
+#[test]
+fn vantage_points() -> Result<()> {
+    let query = query();
+    let stats = Stats::new(&query);
+    let bad = query_reports(&query, "10.0.0.0/8")?;
+    let good = vec![Report::GoodImport {
+        from: Asn(2),
+        to: Asn(1),
+    }];
+    let peer = |ip: &str, asn| -> Result<_> {
+        Ok(CollectorPeer {
+            asn: Asn(asn),
+            ip: ip.parse()?,
+        })
+    };
+    let prefix = "10.0.0.0/8".parse()?;
+    stats.record_vantage_point(peer("192.0.2.1", 1)?, &prefix, &bad);
+    stats.record_vantage_point(peer("192.0.2.2", 1)?, &prefix, &good);
+    stats.record_vantage_point(peer("192.0.2.2", 1)?, &prefix, &good);
+    stats.record_vantage_point(peer("192.0.2.3", 2)?, &"2001:db8::/32".parse()?, &good);
+
+    let v4 = IpVersion::Ipv4;
+    let as1 = stats.per_collector.get(&(Asn(1), v4)).unwrap().clone();
+    assert_eq!((as1.good, as1.neutral, as1.bad), (2, 0, 1));
+    let peer2 = stats
+        .per_peer
+        .get(&(peer("192.0.2.2", 1)?, v4))
+        .unwrap()
+        .clone();
+    assert_eq!((peer2.good, peer2.bad), (2, 0));
+    assert!(stats.per_collector.get(&(Asn(2), v4)).is_none());
+
+    let df = stats.collector_df()?;
+    assert_eq!(df.shape(), (2, 5));
+    assert_eq!(stats.peer_df()?.shape(), (3, 6));
+    Ok(())
+}
+
+fn query_reports(query: &QueryDump, prefix: &str) -> Result<Vec<Report>> {
+    let mut compare = Compare::new(
+        prefix.parse()?,
+        vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))],
+    );
+    compare.verbosity = Verbosity {
+        stop_at_first: false,
+        show_skips: true,
+        show_success: true,
+        ..Verbosity::default()
+    };
+    Ok(compare.check(query))
+}
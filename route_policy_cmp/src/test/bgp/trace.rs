@@ -0,0 +1,100 @@
+use maplit::btreemap;
+
+use crate::{
+    bgp::{map::AsPathEntry, report::RpslError, Report::*, *},
+    parse::*,
+    test::fixture::entry,
+};
+
+use super::*;
+
+#[test]
+fn trace_import() -> Result<()> {
+    let dump = Dump {
+        aut_nums: btreemap! {
+            Asn(1) => AutNum {
+                imports: serde_json::from_str(r#"{"any":{"any":[
+                    {"mp_peerings":[{"mp_peering":{"remote_as":{"Single":{"Num":3}}}}],"mp_filter":"Any"},
+                    {"mp_peerings":[{"mp_peering":{"remote_as":{"Or":{"left":{"Single":{"Num":2}},"right":{"Single":{"Set":"AS-MISSING"}}}}}}],"mp_filter":{"Or":{"left":{"AsNum":[3,"NoOp"]},"right":{"AsNum":[2,"NoOp"]}}}}
+                ]}}"#)?,
+                ..AutNum::default()
+            },
+        },
+        as_routes: btreemap! {Asn(2) => vec!["10.0.0.0/8".parse()?]},
+        ..Dump::default()
+    };
+    let query = QueryDump::from_dump(dump);
+    let compare = Compare::new(
+        "10.0.0.0/8".parse()?,
+        vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))],
+    )
+    .verbosity(Verbosity {
+        trace: true,
+        ..Verbosity::default()
+    });
+    let mut reports = compare.check(&query);
+    let Some(Trace { root }) = reports.pop() else {
+        panic!("Expected a trace at the end of {reports:?}");
+    };
+    assert!(reports.is_empty());
+    let expected = "route 10.0.0.0/8 with AS path 1 2: True
+  AS1 import from AS2: True
+    entry #0: False
+      peering AS3: False
+    entry #1: True
+      peering OR: True
+        peering AS2: True
+      filter OR: True
+        filter AS3: Unknown
+        filter AS2: True
+";
+    assert_eq!(root.render(), expected);
+
+    let json: TraceNode = serde_json::from_str(&root.to_json()?)?;
+    assert_eq!(json, root);
+    Ok(())
+}
+
+/// Failures carry no items without `all_err`, so tracing reports all errors
+/// to tell invalid RPSL apart from definite mismatches.
+#[test]
+fn trace_bad_rpsl() -> Result<()> {
+    let mut aut_num = AutNum::default();
+    aut_num
+        .imports
+        .any
+        .any
+        .push(entry(2, Filter::Invalid("bad".into())));
+    let query = QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => aut_num},
+        ..Dump::default()
+    });
+    let compare = Compare::new(
+        "10.0.0.0/8".parse()?,
+        vec![AsPathEntry::Seq(Asn(1)), AsPathEntry::Seq(Asn(2))],
+    )
+    .verbosity(Verbosity {
+        trace: true,
+        ..Verbosity::default()
+    });
+    let mut reports = compare.check(&query);
+    let Some(Trace { root }) = reports.pop() else {
+        panic!("Expected a trace at the end of {reports:?}");
+    };
+    assert_eq!(
+        reports,
+        [BadImport {
+            from: Asn(2),
+            to: Asn(1),
+            items: vec![ReportItem::BadRpsl(RpslError::InvalidFilter("bad".into()))],
+        }]
+    );
+    let expected = "route 10.0.0.0/8 with AS path 1 2: Unknown
+  AS1 import from AS2: Unknown
+    entry #0: Unknown
+      peering AS2: True
+      filter invalid filter bad: Unknown
+";
+    assert_eq!(root.render(), expected);
+    Ok(())
+}
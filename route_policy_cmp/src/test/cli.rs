@@ -0,0 +1,121 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    bgp::{AspaDirection, Normalizer, StatsFormat, Verbosity},
+    cli::{Cli, Command},
+};
+
+use super::*;
+
+#[test]
+fn command() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn report_args() -> Result<()> {
+    let cli = Cli::try_parse_from([
+        "route_policy_cmp",
+        "-j4",
+        "report",
+        "parsed_all",
+        "rib.bz2",
+        "-o",
+        "reports.parquet",
+        "-n",
+        "100",
+        "--stats-dir",
+        "stats",
+        "--per-entry-err",
+        "--trace",
+    ])?;
+    assert_eq!(cli.threads, Some(4));
+    let Command::Report(args) = cli.command else {
+        panic!("Expected the report command, got {:?}", cli.command);
+    };
+    assert_eq!(args.output.as_deref(), Some("reports.parquet"));
+    assert_eq!(args.sample_size, Some(100));
+    assert_eq!(args.stats_format, StatsFormat::Csv);
+    let verbosity: Verbosity = (&args.verbosity).into();
+    assert!(verbosity.show_success && verbosity.per_entry_err && verbosity.trace);
+    assert_eq!(
+        (args.aspa, args.aspa_direction),
+        (None, AspaDirection::Upstream)
+    );
+    Ok(())
+}
+
+#[test]
+fn report_aspa_args() -> Result<()> {
+    let cli = Cli::try_parse_from([
+        "route_policy_cmp",
+        "report",
+        "parsed_all",
+        "rib.bz2",
+        "--aspa",
+        "aspas.json",
+        "--aspa-direction",
+        "downstream",
+    ])?;
+    let Command::Report(args) = cli.command else {
+        panic!("Expected the report command, got {:?}", cli.command);
+    };
+    assert_eq!(args.aspa.as_deref(), Some("aspas.json"));
+    assert_eq!(args.aspa_direction, AspaDirection::Downstream);
+    Ok(())
+}
+
+#[test]
+fn parse_memory_ceiling() -> Result<()> {
+    let cli = Cli::try_parse_from([
+        "route_policy_cmp",
+        "parse-all",
+        "irrs",
+        "parsed_all",
+        "--memory-mib",
+        "256",
+    ])?;
+    let Command::ParseAll { memory, .. } = cli.command else {
+        panic!("Expected the parse-all command, got {:?}", cli.command);
+    };
+    assert_eq!(memory.ceiling(), 256 << 20);
+    Ok(())
+}
+
+#[test]
+fn report_normalize_args() -> Result<()> {
+    let report = |flags: &[&str]| -> Result<Normalizer> {
+        let args = ["route_policy_cmp", "report", "parsed_all", "rib.bz2"];
+        let cli = Cli::try_parse_from(args.iter().chain(flags))?;
+        let Command::Report(args) = cli.command else {
+            panic!("Expected the report command, got {:?}", cli.command);
+        };
+        Ok((&args.normalize).into())
+    };
+    assert_eq!(report(&[])?, Normalizer::default());
+    let normalizer = report(&["--remove-private", "--remove-confed", "--keep-single-sets"])?;
+    assert!(normalizer.remove_private && normalizer.remove_confed);
+    assert!(!normalizer.remove_reserved && !normalizer.expand_single_sets);
+    Ok(())
+}
+
+#[test]
+fn stats_need_every_check() {
+    for flag in ["--stop-at-first", "--hide-skips", "--hide-success"] {
+        let args = ["route_policy_cmp", "report", "parsed_all", "rib.bz2"];
+        let cli = Cli::try_parse_from(args.into_iter().chain(["--stats-dir", "stats", flag]));
+        assert!(cli.is_err(), "{flag} accepted with --stats-dir");
+    }
+}
+
+#[test]
+fn stats_format() -> Result<()> {
+    let args = ["route_policy_cmp", "report", "parsed_all", "rib.bz2"];
+    let parse = |format| Cli::try_parse_from(args.into_iter().chain(["--stats-format", format]));
+    let Command::Report(args) = parse("parquet")?.command else {
+        panic!("Expected the report command");
+    };
+    assert_eq!(args.stats_format, StatsFormat::Parquet);
+    assert!(parse("jsonl").is_err());
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! Objects shared by tests of different modules.
+use crate::parse::*;
+
+/// An entry peering with `remote_as` using `mp_filter`.
+pub fn entry_with(remote_as: AsExpr, mp_filter: Filter) -> Entry {
+    Entry {
+        mp_peerings: vec![PeeringAction {
+            mp_peering: Peering {
+                remote_as,
+                remote_router: None,
+                local_router: None,
+            },
+            actions: Actions::new(),
+        }],
+        mp_filter,
+        index: None,
+    }
+}
+
+/// An entry peering with `remote` using `mp_filter`.
+pub fn entry(remote: u32, mp_filter: Filter) -> Entry {
+    entry_with(AsExpr::Single(AsName::Num(Asn(remote))), mp_filter)
+}
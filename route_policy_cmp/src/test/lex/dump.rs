@@ -21,5 +21,5 @@ fn dump() -> Result<()> {
 }
 
 pub fn expected_dump() -> Dump {
-    Dump { aut_nums: vec![AutNum { name: "AS590".into(), body: "remarks:\nremarks: This aut-num has been transfered as part of the ERX.\nremarks: It was present in both the ARIN and RIPE databases, so\nremarks: the information from both databases has been merged.\nremarks: If you are the mntner of this object, please update it\nremarks: to reflect the correct information.\nremarks:\nremarks: Please see the FAQ for this process:\nremarks: http://www.ripe.net/db/erx/erx-asn/group3-faq.html\nremarks:\nremarks: **** INFORMATION FROM ARIN OBJECT ****\nremarks: as-name: EASINET-AS1\ndescr: EASInet Operations Center\n Riemenschneiderstrasse 11\n D-5300 Bonn 2\n DE\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nremarks: changed: hostmaster@arin.net 19900302\nremarks: changed: hostmaster@arin.net 19910416\nremarks:\nremarks: **** INFORMATION FROM RIPE OBJECT ****\nas-name: UNSPECIFIED\ndescr: EASInet\nimport: from AS690\n action pref=100;\n accept ANY\nimport: from AS513\n action pref=100;\n accept ANY\nimport: from AS559\n action pref=100;\n accept AS559\nimport: from AS697\n action pref=100;\n accept AS697\nexport: to AS690\n announce AS590\nexport: to AS513\n announce AS590\nexport: to AS559\n announce AS590\nexport: to AS697\n announce AS590\ndefault: to AS690\n action pref=100;\n networks ANY\ndefault: to AS513\n action pref=200;\n networks ANY\nstatus: LEGACY\nnotify: stf@easi.net\nmnt-by: RIPE-NCC-AN-MNT # WARNING: maintainer added to protect object\ncreated: 2002-09-19T15:23:42Z\nlast-modified: 2017-11-15T09:12:37Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), imports: Versions { any: Casts { any: vec![Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS690".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("ANY".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS513".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("ANY".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS559".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("AS559".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS697".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("AS697".into()) }], unicast: vec![], multicast: vec![] }, ipv4: Casts::default(), ipv6: Casts::default() }, exports: Versions { any: Casts { any: vec![Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS690".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS513".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS559".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()) }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS697".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()) }], unicast: vec![], multicast: vec![] }, ipv4: Casts::default(), ipv6: Casts::default() } }], as_sets: vec![AsOrRouteSet { name: "AS-RESTENA".into(), body: "descr: Reseau Teleinformatique de l'Education Nationale\ndescr: Educational and research network for Luxembourg\nmembers: AS2602\nmembers: AS42909\nmembers: AS51966\nmembers: AS-LXP\nmembers: AS-VDL\ntech-c: DUMY-RIPE\nadmin-c: DUMY-RIPE\nnotify: noc@restena.lu\nmnt-by: AS2602-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2022-09-08T09:11:41Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec!["AS2602".into(), "AS42909".into(), "AS51966".into(), "AS-LXP".into(), "AS-VDL".into()] }], route_sets: vec![AsOrRouteSet { name: "AS13646:RS-PEERLANS".into(), body: "descr: Internet Exchange Peering LAN Routes\nmembers: 195.66.224.0/23\nmembers: 194.68.129.0/24\nmembers: 217.29.66.0/23\nmembers: 193.149.1.0/25\nmembers: 193.149.1.128/25\nmembers: 193.148.15.0/24\nmembers: 194.31.232.0/24\nmembers: 194.42.48.0/25\nmembers: 194.53.172.0/26\nmembers: 193.203.0.0/24\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: ZIGGO-SERVICES-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2020-01-21T15:43:54Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec!["195.66.224.0/23".into(), "194.68.129.0/24".into(), "217.29.66.0/23".into(), "193.149.1.0/25".into(), "193.149.1.128/25".into(), "193.148.15.0/24".into(), "194.31.232.0/24".into(), "194.42.48.0/25".into(), "194.53.172.0/26".into(), "193.203.0.0/24".into()] }], peering_sets: vec![PeeringSet { name: "AS8785:prng-nyiix".into(), body: "descr: NYIIX Peering Partners\npeering: AS2516 at 198.32.160.25\npeering: AS3257 at 198.32.160.29\npeering: AS4323 at 198.32.160.35\npeering: AS4436 at 198.32.160.53\npeering: AS4513 at 198.32.160.32\npeering: AS5496 at 198.32.160.16\npeering: AS6427 at 198.32.160.12\npeering: AS6461 at 198.32.160.22\npeering: AS6660 at 198.32.160.13\npeering: AS6667 at 198.32.160.41\npeering: AS6939 at 198.32.160.61\npeering: AS8001 at 198.32.160.20\npeering: AS8002 at 198.32.160.33\npeering: AS8220 at 198.32.160.34\npeering: AS8647 at 198.32.160.14\npeering: AS8966 at 198.32.160.45\npeering: AS9156 at 198.32.160.24\npeering: AS13768 at 198.32.160.65\npeering: AS13945 at 198.32.160.37\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: MISTRALNOC\ncreated: 2001-10-20T18:41:03Z\nlast-modified: 2005-10-10T11:47:35Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), peerings: vec![Peering { as_expr: Field("AS2516".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.25".into())) }, Peering { as_expr: Field("AS3257".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.29".into())) }, Peering { as_expr: Field("AS4323".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.35".into())) }, Peering { as_expr: Field("AS4436".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.53".into())) }, Peering { as_expr: Field("AS4513".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.32".into())) }, Peering { as_expr: Field("AS5496".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.16".into())) }, Peering { as_expr: Field("AS6427".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.12".into())) }, Peering { as_expr: Field("AS6461".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.22".into())) }, Peering { as_expr: Field("AS6660".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.13".into())) }, Peering { as_expr: Field("AS6667".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.41".into())) }, Peering { as_expr: Field("AS6939".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.61".into())) }, Peering { as_expr: Field("AS8001".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.20".into())) }, Peering { as_expr: Field("AS8002".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.33".into())) }, Peering { as_expr: Field("AS8220".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.34".into())) }, Peering { as_expr: Field("AS8647".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.14".into())) }, Peering { as_expr: Field("AS8966".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.45".into())) }, Peering { as_expr: Field("AS9156".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.24".into())) }, Peering { as_expr: Field("AS13768".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.65".into())) }, Peering { as_expr: Field("AS13945".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.37".into())) }] }], filter_sets: vec![FilterSet { name: "FLTR-EUX".into(), body: "filter: AS8785 AND AS13285\ndescr: test filter set 1\nmnt-by: MISTRALNOC\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\ncreated: 2002-08-23T22:50:47Z\nlast-modified: 2005-10-10T11:47:30Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), filters: vec![And { left: Box::new(PathAttr("AS8785".into())), right: Box::new(PathAttr("AS13285".into())) }] }], as_routes: btreemap! {"AS10".into()=> vec!["45.143.208.0/22".into()]} }
+    Dump { aut_nums: vec![AutNum { name: "AS590".into(), body: "remarks:\nremarks: This aut-num has been transfered as part of the ERX.\nremarks: It was present in both the ARIN and RIPE databases, so\nremarks: the information from both databases has been merged.\nremarks: If you are the mntner of this object, please update it\nremarks: to reflect the correct information.\nremarks:\nremarks: Please see the FAQ for this process:\nremarks: http://www.ripe.net/db/erx/erx-asn/group3-faq.html\nremarks:\nremarks: **** INFORMATION FROM ARIN OBJECT ****\nremarks: as-name: EASINET-AS1\ndescr: EASInet Operations Center\n Riemenschneiderstrasse 11\n D-5300 Bonn 2\n DE\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nremarks: changed: hostmaster@arin.net 19900302\nremarks: changed: hostmaster@arin.net 19910416\nremarks:\nremarks: **** INFORMATION FROM RIPE OBJECT ****\nas-name: UNSPECIFIED\ndescr: EASInet\nimport: from AS690\n action pref=100;\n accept ANY\nimport: from AS513\n action pref=100;\n accept ANY\nimport: from AS559\n action pref=100;\n accept AS559\nimport: from AS697\n action pref=100;\n accept AS697\nexport: to AS690\n announce AS590\nexport: to AS513\n announce AS590\nexport: to AS559\n announce AS590\nexport: to AS697\n announce AS590\ndefault: to AS690\n action pref=100;\n networks ANY\ndefault: to AS513\n action pref=200;\n networks ANY\nstatus: LEGACY\nnotify: stf@easi.net\nmnt-by: RIPE-NCC-AN-MNT # WARNING: maintainer added to protect object\ncreated: 2002-09-19T15:23:42Z\nlast-modified: 2017-11-15T09:12:37Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), imports: Versions { any: Casts { any: vec![Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS690".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("ANY".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS513".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("ANY".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS559".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("AS559".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS697".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::from([("pref".into(), Assigned("100".into()))]) }], mp_filter: PathAttr("AS697".into()), index: None }], unicast: vec![], multicast: vec![] }, ipv4: Casts::default(), ipv6: Casts::default() }, exports: Versions { any: Casts { any: vec![Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS690".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS513".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS559".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()), index: None }, Entry { mp_peerings: vec![PeeringAction { mp_peering: Peering { as_expr: Field("AS697".into()), router_expr1: None, router_expr2: None }, actions: BTreeMap::new() }], mp_filter: PathAttr("AS590".into()), index: None }], unicast: vec![], multicast: vec![] }, ipv4: Casts::default(), ipv6: Casts::default() } }], as_sets: vec![AsOrRouteSet { name: "AS-RESTENA".into(), body: "descr: Reseau Teleinformatique de l'Education Nationale\ndescr: Educational and research network for Luxembourg\nmembers: AS2602\nmembers: AS42909\nmembers: AS51966\nmembers: AS-LXP\nmembers: AS-VDL\ntech-c: DUMY-RIPE\nadmin-c: DUMY-RIPE\nnotify: noc@restena.lu\nmnt-by: AS2602-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2022-09-08T09:11:41Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec!["AS2602".into(), "AS42909".into(), "AS51966".into(), "AS-LXP".into(), "AS-VDL".into()] }], route_sets: vec![AsOrRouteSet { name: "AS13646:RS-PEERLANS".into(), body: "descr: Internet Exchange Peering LAN Routes\nmembers: 195.66.224.0/23\nmembers: 194.68.129.0/24\nmembers: 217.29.66.0/23\nmembers: 193.149.1.0/25\nmembers: 193.149.1.128/25\nmembers: 193.148.15.0/24\nmembers: 194.31.232.0/24\nmembers: 194.42.48.0/25\nmembers: 194.53.172.0/26\nmembers: 193.203.0.0/24\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: ZIGGO-SERVICES-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2020-01-21T15:43:54Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec!["195.66.224.0/23".into(), "194.68.129.0/24".into(), "217.29.66.0/23".into(), "193.149.1.0/25".into(), "193.149.1.128/25".into(), "193.148.15.0/24".into(), "194.31.232.0/24".into(), "194.42.48.0/25".into(), "194.53.172.0/26".into(), "193.203.0.0/24".into()] }], peering_sets: vec![PeeringSet { name: "AS8785:prng-nyiix".into(), body: "descr: NYIIX Peering Partners\npeering: AS2516 at 198.32.160.25\npeering: AS3257 at 198.32.160.29\npeering: AS4323 at 198.32.160.35\npeering: AS4436 at 198.32.160.53\npeering: AS4513 at 198.32.160.32\npeering: AS5496 at 198.32.160.16\npeering: AS6427 at 198.32.160.12\npeering: AS6461 at 198.32.160.22\npeering: AS6660 at 198.32.160.13\npeering: AS6667 at 198.32.160.41\npeering: AS6939 at 198.32.160.61\npeering: AS8001 at 198.32.160.20\npeering: AS8002 at 198.32.160.33\npeering: AS8220 at 198.32.160.34\npeering: AS8647 at 198.32.160.14\npeering: AS8966 at 198.32.160.45\npeering: AS9156 at 198.32.160.24\npeering: AS13768 at 198.32.160.65\npeering: AS13945 at 198.32.160.37\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: MISTRALNOC\ncreated: 2001-10-20T18:41:03Z\nlast-modified: 2005-10-10T11:47:35Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), peerings: vec![Peering { as_expr: Field("AS2516".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.25".into())) }, Peering { as_expr: Field("AS3257".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.29".into())) }, Peering { as_expr: Field("AS4323".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.35".into())) }, Peering { as_expr: Field("AS4436".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.53".into())) }, Peering { as_expr: Field("AS4513".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.32".into())) }, Peering { as_expr: Field("AS5496".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.16".into())) }, Peering { as_expr: Field("AS6427".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.12".into())) }, Peering { as_expr: Field("AS6461".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.22".into())) }, Peering { as_expr: Field("AS6660".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.13".into())) }, Peering { as_expr: Field("AS6667".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.41".into())) }, Peering { as_expr: Field("AS6939".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.61".into())) }, Peering { as_expr: Field("AS8001".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.20".into())) }, Peering { as_expr: Field("AS8002".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.33".into())) }, Peering { as_expr: Field("AS8220".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.34".into())) }, Peering { as_expr: Field("AS8647".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.14".into())) }, Peering { as_expr: Field("AS8966".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.45".into())) }, Peering { as_expr: Field("AS9156".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.24".into())) }, Peering { as_expr: Field("AS13768".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.65".into())) }, Peering { as_expr: Field("AS13945".into()), router_expr1: None, router_expr2: Some(Field("198.32.160.37".into())) }] }], filter_sets: vec![FilterSet { name: "FLTR-EUX".into(), body: "filter: AS8785 AND AS13285\ndescr: test filter set 1\nmnt-by: MISTRALNOC\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\ncreated: 2002-08-23T22:50:47Z\nlast-modified: 2005-10-10T11:47:30Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), filters: vec![And { left: Box::new(PathAttr("AS8785".into())), right: Box::new(PathAttr("AS13285".into())) }] }], as_routes: btreemap! {"AS10".into()=> vec!["45.143.208.0/22".into()]} }
 }
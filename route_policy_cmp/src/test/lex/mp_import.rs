@@ -80,6 +80,7 @@ fn expected_mp_import() -> Versions {
                     left: Box::new(PathAttr("ANY".into())),
                     right: Box::new(Not(Box::new(PathAttr("AS3344:fltr-filterlist".into())))),
                 },
+                index: None,
             }],
             multicast: vec![],
         },
@@ -1,6 +1,12 @@
+mod analysis;
+mod api;
 mod bgp;
+mod cli;
+mod fixture;
 pub mod lex;
 pub mod notebook;
 mod parse;
+mod shell;
+mod whois;
 
 use super::*;
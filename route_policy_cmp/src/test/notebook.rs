@@ -21,7 +21,10 @@ use dashmap::DashMap;
 use itertools::multiunzip;
 use polars::prelude::*;
 use rayon::prelude::*;
-use route_policy_cmp::{bgp::*, parse::dump::Dump};
+use route_policy_cmp::{
+    bgp::*,
+    parse::{dump::Dump, Asn},
+};
 use std::{
     collections::BTreeMap,
     fs::File,
@@ -55,7 +58,7 @@ fn parse_bgp_lines() -> Result<()> {
     // ---
     // Generate statistics for each AS:
     let start = Instant::now();
-    let map: DashMap<usize, AsStats> = DashMap::new();
+    let map: DashMap<Asn, AsStats> = DashMap::new();
     bgp_lines.par_iter_mut().for_each(|l| {
         l.compare.as_stats(&query, &map);
     });
@@ -85,7 +88,7 @@ fn parse_bgp_lines() -> Result<()> {
             },
         )| {
             (
-                an as u64,
+                an.0 as u64,
                 import_ok,
                 export_ok,
                 import_skip,
@@ -1,5 +1,8 @@
+mod asn;
 mod lex;
 mod peering;
+mod set;
+mod shard;
 
 use crate::parse::*;
 
@@ -0,0 +1,39 @@
+use anyhow::Result;
+
+use crate::parse::{lex::parse_aut_num_name, *};
+
+#[test]
+fn parse_asn() -> Result<()> {
+    assert_eq!("2914".parse::<Asn>()?, Asn(2914));
+    assert_eq!("AS2914".parse::<Asn>()?, Asn(2914));
+    assert_eq!("as4294967295".parse::<Asn>()?, Asn(4_294_967_295));
+    assert_eq!("1.10".parse::<Asn>()?, Asn(65546));
+    assert_eq!("AS65535.65535".parse::<Asn>()?, Asn(4_294_967_295));
+    assert!("4294967296".parse::<Asn>().is_err());
+    assert!("AS65536.0".parse::<Asn>().is_err());
+    assert!("AS1.65536".parse::<Asn>().is_err());
+    assert!("AS-FOO".parse::<Asn>().is_err());
+
+    assert_eq!(parse_aut_num_name("AS1.10")?, Asn(65546));
+    assert!(parse_aut_num_name("1.10").is_err());
+    Ok(())
+}
+
+#[test]
+fn display_asn() {
+    assert_eq!(Asn(65546).to_string(), "65546");
+    assert_eq!(Asn(65546).to_asdot(), "1.10");
+    assert_eq!(Asn(2914).to_asdot(), "2914");
+}
+
+#[test]
+fn classify_asn() {
+    assert!(Asn(64512).is_private());
+    assert!(Asn(4_200_000_000).is_private());
+    assert!(!Asn(65535).is_private());
+    assert!(Asn(65535).is_reserved());
+    assert!(Asn(0).is_reserved());
+    assert!(Asn(64496).is_documentation());
+    assert!(AS_TRANS.is_as_trans());
+    assert!(Asn(65536).is_4_byte());
+}
@@ -5,9 +5,10 @@ use crate::{
     lex::action::Action::*,
     parse::{
         lex::parse_aut_num_name,
-        set::{AsSet, FilterSet, PeeringSet, RouteSet, RouteSetMember::*},
+        set::{AsSet, FilterSet, PeeringSet, RouteSet, RouteSetMember::Range},
         AsExpr::*,
         AsName::*,
+        Asn,
         Filter::*,
         RangeOperator::NoOp,
         RouterExpr::*,
@@ -19,7 +20,7 @@ pub use super::*;
 
 #[test]
 fn parse_name() {
-    assert_eq!(parse_aut_num_name("AS2340").unwrap(), 2340);
+    assert_eq!(parse_aut_num_name("AS2340").unwrap(), Asn(2340));
     assert!(parse_aut_num_name("AS2340 ").is_err());
     assert!(parse_aut_num_name("AS-2340").is_err());
     assert!(parse_aut_num_name("jfwoe").is_err());
@@ -44,7 +45,7 @@ fn parse_dump() {
     assert_eq!(as_routes, expected_as_routes());
 }
 
-fn expected_aut_nums() -> BTreeMap<usize, AutNum> {
+fn expected_aut_nums() -> BTreeMap<Asn, AutNum> {
     let body = "remarks:\nremarks: This aut-num has been transfered as part of the ERX.\nremarks: It was present in both the ARIN and RIPE databases, so\nremarks: the information from both databases has been merged.\nremarks: If you are the mntner of this object, please update it\nremarks: to reflect the correct information.\nremarks:\nremarks: Please see the FAQ for this process:\nremarks: http://www.ripe.net/db/erx/erx-asn/group3-faq.html\nremarks:\nremarks: **** INFORMATION FROM ARIN OBJECT ****\nremarks: as-name: EASINET-AS1\ndescr: EASInet Operations Center\n Riemenschneiderstrasse 11\n D-5300 Bonn 2\n DE\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nremarks: changed: hostmaster@arin.net 19900302\nremarks: changed: hostmaster@arin.net 19910416\nremarks:\nremarks: **** INFORMATION FROM RIPE OBJECT ****\nas-name: UNSPECIFIED\ndescr: EASInet\nimport: from AS690\n action pref=100;\n accept ANY\nimport: from AS513\n action pref=100;\n accept ANY\nimport: from AS559\n action pref=100;\n accept AS559\nimport: from AS697\n action pref=100;\n accept AS697\nexport: to AS690\n announce AS590\nexport: to AS513\n announce AS590\nexport: to AS559\n announce AS590\nexport: to AS697\n announce AS590\ndefault: to AS690\n action pref=100;\n networks ANY\ndefault: to AS513\n action pref=200;\n networks ANY\nstatus: LEGACY\nnotify: stf@easi.net\nmnt-by: RIPE-NCC-AN-MNT # WARNING: maintainer added to protect object\ncreated: 2002-09-19T15:23:42Z\nlast-modified: 2017-11-15T09:12:37Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into();
     let imports = Versions {
         any: Casts {
@@ -52,46 +53,50 @@ fn expected_aut_nums() -> BTreeMap<usize, AutNum> {
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(690)),
+                            remote_as: Single(Num(Asn(690))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {"pref".into() => Assigned("100".into())},
                     }],
                     mp_filter: Any,
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(513)),
+                            remote_as: Single(Num(Asn(513))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {"pref".into() => Assigned("100".into())},
                     }],
                     mp_filter: Any,
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(559)),
+                            remote_as: Single(Num(Asn(559))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {"pref".into() => Assigned("100".into())},
                     }],
-                    mp_filter: AsNum(559, NoOp),
+                    mp_filter: AsNum(Asn(559), NoOp),
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(697)),
+                            remote_as: Single(Num(Asn(697))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {"pref".into() => Assigned("100".into())},
                     }],
-                    mp_filter: AsNum(697, NoOp),
+                    mp_filter: AsNum(Asn(697), NoOp),
+                    index: None,
                 },
             ],
             unicast: vec![],
@@ -115,46 +120,50 @@ fn expected_aut_nums() -> BTreeMap<usize, AutNum> {
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(690)),
+                            remote_as: Single(Num(Asn(690))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {},
                     }],
-                    mp_filter: AsNum(590, NoOp),
+                    mp_filter: AsNum(Asn(590), NoOp),
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(513)),
+                            remote_as: Single(Num(Asn(513))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {},
                     }],
-                    mp_filter: AsNum(590, NoOp),
+                    mp_filter: AsNum(Asn(590), NoOp),
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(559)),
+                            remote_as: Single(Num(Asn(559))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {},
                     }],
-                    mp_filter: AsNum(590, NoOp),
+                    mp_filter: AsNum(Asn(590), NoOp),
+                    index: None,
                 },
                 Entry {
                     mp_peerings: vec![PeeringAction {
                         mp_peering: Peering {
-                            remote_as: Single(Num(697)),
+                            remote_as: Single(Num(Asn(697))),
                             remote_router: None,
                             local_router: None,
                         },
                         actions: btreemap! {},
                     }],
-                    mp_filter: AsNum(590, NoOp),
+                    mp_filter: AsNum(Asn(590), NoOp),
+                    index: None,
                 },
             ],
             unicast: vec![],
@@ -165,7 +174,7 @@ fn expected_aut_nums() -> BTreeMap<usize, AutNum> {
     };
 
     BTreeMap::from([(
-        590,
+        Asn(590),
         AutNum {
             body,
             imports,
@@ -175,7 +184,7 @@ fn expected_aut_nums() -> BTreeMap<usize, AutNum> {
 }
 
 fn expected_as_sets() -> BTreeMap<String, AsSet> {
-    btreemap! {"AS-RESTENA".into()=> AsSet { body: "descr: Reseau Teleinformatique de l'Education Nationale\ndescr: Educational and research network for Luxembourg\nmembers: AS2602\nmembers: AS42909\nmembers: AS51966\nmembers: AS-LXP\nmembers: AS-VDL\ntech-c: DUMY-RIPE\nadmin-c: DUMY-RIPE\nnotify: noc@restena.lu\nmnt-by: AS2602-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2022-09-08T09:11:41Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec![2602, 42909, 51966], set_members: vec!["AS-LXP".into(), "AS-VDL".into()] }}
+    btreemap! {"AS-RESTENA".into()=> AsSet { body: "descr: Reseau Teleinformatique de l'Education Nationale\ndescr: Educational and research network for Luxembourg\nmembers: AS2602\nmembers: AS42909\nmembers: AS51966\nmembers: AS-LXP\nmembers: AS-VDL\ntech-c: DUMY-RIPE\nadmin-c: DUMY-RIPE\nnotify: noc@restena.lu\nmnt-by: AS2602-MNT\ncreated: 1970-01-01T00:00:00Z\nlast-modified: 2022-09-08T09:11:41Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), members: vec![Asn(2602), Asn(42909), Asn(51966)], set_members: vec!["AS-LXP".into(), "AS-VDL".into()] }}
 }
 
 fn expected_route_sets() -> BTreeMap<String, RouteSet> {
@@ -187,15 +196,15 @@ fn ipn(s: &str) -> IpNet {
 }
 
 fn expected_peering_sets() -> BTreeMap<String, PeeringSet> {
-    btreemap! {"AS8785:prng-nyiix".into()=> PeeringSet { body: "descr: NYIIX Peering Partners\npeering: AS2516 at 198.32.160.25\npeering: AS3257 at 198.32.160.29\npeering: AS4323 at 198.32.160.35\npeering: AS4436 at 198.32.160.53\npeering: AS4513 at 198.32.160.32\npeering: AS5496 at 198.32.160.16\npeering: AS6427 at 198.32.160.12\npeering: AS6461 at 198.32.160.22\npeering: AS6660 at 198.32.160.13\npeering: AS6667 at 198.32.160.41\npeering: AS6939 at 198.32.160.61\npeering: AS8001 at 198.32.160.20\npeering: AS8002 at 198.32.160.33\npeering: AS8220 at 198.32.160.34\npeering: AS8647 at 198.32.160.14\npeering: AS8966 at 198.32.160.45\npeering: AS9156 at 198.32.160.24\npeering: AS13768 at 198.32.160.65\npeering: AS13945 at 198.32.160.37\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: MISTRALNOC\ncreated: 2001-10-20T18:41:03Z\nlast-modified: 2005-10-10T11:47:35Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), peerings: vec![Peering { remote_as: Single(Num(2516)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.25"))) }, Peering { remote_as: Single(Num(3257)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.29"))) }, Peering { remote_as: Single(Num(4323)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.35"))) }, Peering { remote_as: Single(Num(4436)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.53"))) }, Peering { remote_as: Single(Num(4513)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.32"))) }, Peering { remote_as: Single(Num(5496)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.16"))) }, Peering { remote_as: Single(Num(6427)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.12"))) }, Peering { remote_as: Single(Num(6461)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.22"))) }, Peering { remote_as: Single(Num(6660)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.13"))) }, Peering { remote_as: Single(Num(6667)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.41"))) }, Peering { remote_as: Single(Num(6939)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.61"))) }, Peering { remote_as: Single(Num(8001)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.20"))) }, Peering { remote_as: Single(Num(8002)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.33"))) }, Peering { remote_as: Single(Num(8220)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.34"))) }, Peering { remote_as: Single(Num(8647)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.14"))) }, Peering { remote_as: Single(Num(8966)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.45"))) }, Peering { remote_as: Single(Num(9156)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.24"))) }, Peering { remote_as: Single(Num(13768)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.65"))) }, Peering { remote_as: Single(Num(13945)), remote_router: None, local_router: Some(Ip(ip!("198.32.160.37"))) }] }}
+    btreemap! {"AS8785:prng-nyiix".into()=> PeeringSet { body: "descr: NYIIX Peering Partners\npeering: AS2516 at 198.32.160.25\npeering: AS3257 at 198.32.160.29\npeering: AS4323 at 198.32.160.35\npeering: AS4436 at 198.32.160.53\npeering: AS4513 at 198.32.160.32\npeering: AS5496 at 198.32.160.16\npeering: AS6427 at 198.32.160.12\npeering: AS6461 at 198.32.160.22\npeering: AS6660 at 198.32.160.13\npeering: AS6667 at 198.32.160.41\npeering: AS6939 at 198.32.160.61\npeering: AS8001 at 198.32.160.20\npeering: AS8002 at 198.32.160.33\npeering: AS8220 at 198.32.160.34\npeering: AS8647 at 198.32.160.14\npeering: AS8966 at 198.32.160.45\npeering: AS9156 at 198.32.160.24\npeering: AS13768 at 198.32.160.65\npeering: AS13945 at 198.32.160.37\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\nmnt-by: MISTRALNOC\ncreated: 2001-10-20T18:41:03Z\nlast-modified: 2005-10-10T11:47:35Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), peerings: vec![Peering { remote_as: Single(Num(Asn(2516))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.25"))) }, Peering { remote_as: Single(Num(Asn(3257))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.29"))) }, Peering { remote_as: Single(Num(Asn(4323))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.35"))) }, Peering { remote_as: Single(Num(Asn(4436))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.53"))) }, Peering { remote_as: Single(Num(Asn(4513))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.32"))) }, Peering { remote_as: Single(Num(Asn(5496))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.16"))) }, Peering { remote_as: Single(Num(Asn(6427))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.12"))) }, Peering { remote_as: Single(Num(Asn(6461))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.22"))) }, Peering { remote_as: Single(Num(Asn(6660))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.13"))) }, Peering { remote_as: Single(Num(Asn(6667))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.41"))) }, Peering { remote_as: Single(Num(Asn(6939))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.61"))) }, Peering { remote_as: Single(Num(Asn(8001))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.20"))) }, Peering { remote_as: Single(Num(Asn(8002))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.33"))) }, Peering { remote_as: Single(Num(Asn(8220))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.34"))) }, Peering { remote_as: Single(Num(Asn(8647))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.14"))) }, Peering { remote_as: Single(Num(Asn(8966))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.45"))) }, Peering { remote_as: Single(Num(Asn(9156))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.24"))) }, Peering { remote_as: Single(Num(Asn(13768))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.65"))) }, Peering { remote_as: Single(Num(Asn(13945))), remote_router: None, local_router: Some(Ip(ip!("198.32.160.37"))) }] }}
 }
 
 fn expected_filter_sets() -> BTreeMap<String, FilterSet> {
     use crate::parse::{Filter::*, FilterSet};
 
-    btreemap! {"FLTR-EUX".into()=> FilterSet { body: "filter: AS8785 AND AS13285\ndescr: test filter set 1\nmnt-by: MISTRALNOC\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\ncreated: 2002-08-23T22:50:47Z\nlast-modified: 2005-10-10T11:47:30Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), filters: vec![And { left: Box::new(AsNum(8785, NoOp)), right: Box::new(AsNum(13285, NoOp)) }] }}
+    btreemap! {"FLTR-EUX".into()=> FilterSet { body: "filter: AS8785 AND AS13285\ndescr: test filter set 1\nmnt-by: MISTRALNOC\nadmin-c: DUMY-RIPE\ntech-c: DUMY-RIPE\ncreated: 2002-08-23T22:50:47Z\nlast-modified: 2005-10-10T11:47:30Z\nsource: RIPE\nremarks: ****************************\nremarks: * THIS OBJECT IS MODIFIED\nremarks: * Please note that all data that is generally regarded as personal\nremarks: * data has been removed from this object.\nremarks: * To view the original object, please query the RIPE Database at:\nremarks: * http://www.ripe.net/whois\nremarks: ****************************\n".into(), filters: vec![And { left: Box::new(AsNum(Asn(8785), NoOp)), right: Box::new(AsNum(Asn(13285), NoOp)) }] }}
 }
 
-fn expected_as_routes() -> BTreeMap<usize, Vec<IpNet>> {
-    btreemap! {10=> vec![ipn("45.143.208.0/22")]}
+fn expected_as_routes() -> BTreeMap<Asn, Vec<IpNet>> {
+    btreemap! {Asn(10)=> vec![ipn("45.143.208.0/22")]}
 }
@@ -8,7 +8,7 @@ fn peering_set() {
     ] {
         let parsed = parse_mp_peering(peering_field(name.clone()));
         let expected = Peering {
-            remote_as: AsExpr::PeeringSet(name),
+            remote_as: AsExpr::PeeringSet(name.into()),
             remote_router: None,
             local_router: None,
         };
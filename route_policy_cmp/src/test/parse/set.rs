@@ -0,0 +1,112 @@
+use maplit::btreemap;
+
+use crate::parse::{
+    filter::{parse_path_attribute, try_parse_as_set},
+    *,
+};
+
+#[test]
+fn hierarchical_set_names() {
+    for name in [
+        "AS-FOO",
+        "AS1:AS-FOO",
+        "as1:as-foo",
+        "AS-FOO:AS-BAR",
+        "AS-FOO:AS-BAR:AS65000",
+        "AS1:AS-FOO:AS2",
+        "AS1.10:AS-FOO",
+        "AS-FOO:AS1.10",
+    ] {
+        assert!(is_as_set(name), "{name}");
+    }
+    for name in [
+        "AS1",
+        "AS1:AS2",
+        "1.10:AS-FOO",
+        "AS1.:AS-FOO",
+        "AS-",
+        "AS1:RS-FOO",
+        "AS-FOO:RS-BAR",
+        "AS-FOO::AS-BAR",
+        "AS-FOO^+",
+    ] {
+        assert!(!is_as_set(name), "{name}");
+    }
+
+    assert!(is_route_set_name("RS-FOO:AS1:RS-BAR"));
+    assert!(is_filter_set("AS1:FLTR-FOO:FLTR-BAR"));
+    assert!(is_peering_set("PRNG-FOO:AS2"));
+    assert!(!is_peering_set("AS1:AS-FOO"));
+}
+
+#[test]
+fn parent_names() {
+    assert_eq!(parent_set_name("AS-FOO"), None);
+    assert_eq!(parent_set_name("AS1:AS-FOO"), Some("AS1"));
+    assert_eq!(parent_set_name("AS-FOO:AS-BAR:AS2"), Some("AS-FOO:AS-BAR"));
+}
+
+#[test]
+fn hierarchical_filters() {
+    assert_eq!(
+        try_parse_as_set("AS-FOO:AS-BAR:AS65000^+"),
+        Some(Filter::AsSet(
+            "AS-FOO:AS-BAR:AS65000".into(),
+            RangeOperator::Plus
+        ))
+    );
+    assert_eq!(
+        parse_path_attribute("AS1:RS-FOO:RS-BAR".into(), &[]),
+        Filter::RouteSet("AS1:RS-FOO:RS-BAR".into(), RangeOperator::NoOp)
+    );
+}
+
+#[test]
+fn remove_orphan_sets() {
+    let mut dump = Dump {
+        aut_nums: btreemap! {Asn(1) => AutNum::default()},
+        as_sets: btreemap! {
+            "AS-FOO".into() => AsSet::default(),
+            "AS-FOO:AS-BAR".into() => AsSet::default(),
+            "AS1:AS-FOO".into() => AsSet::default(),
+            "AS2:AS-FOO".into() => AsSet::default(),
+            "AS2:AS-FOO:AS-BAR".into() => AsSet::default(),
+            "AS-BAZ:AS3".into() => AsSet::default(),
+        },
+        ..Dump::default()
+    };
+    dump.remove_orphan_sets();
+    assert_eq!(
+        dump.as_sets.into_keys().collect::<Vec<_>>(),
+        ["AS-FOO", "AS-FOO:AS-BAR", "AS1:AS-FOO"]
+    );
+}
+
+#[test]
+fn route_set_members() {
+    use RouteSetMember::*;
+    let members: Vec<RouteSetMember> = [
+        "1.0.0.0/8^+",
+        "2001:db8::/32^48",
+        "RS-FOO^-",
+        "AS1:RS-BAR",
+        "AS2",
+        "AS3^+",
+        "AS-FOO",
+        "AS1:AS-BAR^-",
+    ]
+    .into_iter()
+    .map(|member| member.to_string().into())
+    .collect();
+    let expected = vec![
+        Range("1.0.0.0/8^+".parse().unwrap()),
+        Range("2001:db8::/32^48".parse().unwrap()),
+        NameOp("RS-FOO".into(), RangeOperator::Minus),
+        NameOp("AS1:RS-BAR".into(), RangeOperator::NoOp),
+        AsNum(Asn(2), RangeOperator::NoOp),
+        AsNum(Asn(3), RangeOperator::Plus),
+        AsSet("AS-FOO".into(), RangeOperator::NoOp),
+        AsSet("AS1:AS-BAR".into(), RangeOperator::Minus),
+    ];
+    assert_eq!(members, expected);
+}
@@ -0,0 +1,95 @@
+use std::{
+    env::temp_dir,
+    fs::{read_dir, remove_dir_all},
+    process,
+};
+
+use maplit::btreemap;
+
+use crate::parse::*;
+
+use super::*;
+
+fn aut_num(body: &str) -> AutNum {
+    AutNum {
+        body: body.into(),
+        ..AutNum::default()
+    }
+}
+
+fn as_set(body: &str) -> AsSet {
+    AsSet {
+        body: body.into(),
+        ..AsSet::default()
+    }
+}
+
+#[test]
+fn shards_under_ceiling() -> Result<()> {
+    let directory = temp_dir().join(format!("shards-{}", process::id()));
+    let mut writer = ShardWriter::new(&directory, 16)?;
+    writer.push_aut_num(Asn(1), aut_num("aut-num: AS1\n"))?;
+    writer.push_as_set("AS1:AS-FOO".into(), as_set("as-set: AS1:AS-FOO\n"))?;
+    writer.push_as_set("AS2:AS-FOO".into(), as_set("as-set: AS2:AS-FOO\n"))?;
+    writer.push_as_set("AS-BAR".into(), as_set("as-set: AS-BAR\n"))?;
+    for route in ["10.1.0.0/16", "10.0.0.0/16", "10.0.0.0/16"] {
+        writer.push_route(Asn(1), route.parse()?)?;
+    }
+    writer.finish()?;
+
+    // The shard with only the orphan is removed, and each route gets its own,
+    // combined when read.
+    assert_eq!(read_dir(&directory)?.count(), 5);
+    let dump = Dump::pal_read(&directory)?;
+    remove_dir_all(directory)?;
+    assert_eq!(
+        dump,
+        Dump {
+            aut_nums: btreemap! {Asn(1) => aut_num("aut-num: AS1\n")},
+            as_sets: btreemap! {
+                "AS1:AS-FOO".into() => as_set("as-set: AS1:AS-FOO\n"),
+                "AS-BAR".into() => as_set("as-set: AS-BAR\n"),
+            },
+            as_routes: btreemap! {
+                Asn(1) => vec!["10.0.0.0/16".parse()?, "10.1.0.0/16".parse()?],
+            },
+            ..Dump::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn later_generation_overrides() -> Result<()> {
+    let directory = temp_dir().join(format!("generations-{}", process::id()));
+    let mut writer = ShardWriter::new(&directory, MEMORY_CEILING)?;
+    writer.push_aut_num(Asn(1), aut_num("backup"))?;
+    writer.push_aut_num(Asn(2), aut_num("backup"))?;
+    writer.push_as_set("AS2:AS-FOO".into(), as_set("backup"))?;
+    writer.push_route(Asn(1), "10.0.0.0/16".parse()?)?;
+    writer.push_route(Asn(2), "10.2.0.0/16".parse()?)?;
+    writer.next_generation()?;
+    writer.push_aut_num(Asn(1), aut_num("priority"))?;
+    writer.push_as_set("AS2:AS-FOO".into(), as_set("priority"))?;
+    writer.push_route(Asn(1), "10.1.0.0/16".parse()?)?;
+    writer.finish()?;
+
+    let dump = Dump::pal_read(&directory)?;
+    remove_dir_all(directory)?;
+    assert_eq!(
+        dump,
+        Dump {
+            aut_nums: btreemap! {
+                Asn(1) => aut_num("priority"),
+                Asn(2) => aut_num("backup"),
+            },
+            as_sets: btreemap! {"AS2:AS-FOO".into() => as_set("priority")},
+            as_routes: btreemap! {
+                Asn(1) => vec!["10.1.0.0/16".parse()?],
+                Asn(2) => vec!["10.2.0.0/16".parse()?],
+            },
+            ..Dump::default()
+        }
+    );
+    Ok(())
+}
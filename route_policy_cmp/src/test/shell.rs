@@ -0,0 +1,79 @@
+use maplit::btreemap;
+
+use crate::{
+    bgp::QueryDump,
+    parse::*,
+    shell::{Shell, ShellHelper, HELP},
+    test::fixture::entry,
+};
+
+use super::*;
+
+fn shell() -> Shell {
+    let mut as1 = AutNum {
+        body: "as-name: ONE\nimport: from AS2 accept ANY\n".into(),
+        ..AutNum::default()
+    };
+    as1.imports.any.any.push(Entry {
+        index: Some(1),
+        ..entry(2, Filter::Any)
+    });
+    Shell::new(QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => as1},
+        as_sets: btreemap! {
+            "AS-OUTER".into() => AsSet::new(
+                String::new(),
+                vec![Asn(2)],
+                vec!["AS-INNER".into(), "AS-MISSING".into()],
+            ),
+            "AS-INNER".into() => AsSet::new(String::new(), vec![Asn(3), Asn(2)], vec![]),
+        },
+        ..Dump::default()
+    }))
+}
+
+#[test]
+fn execute() -> Result<()> {
+    let shell = shell();
+    let output = |line| shell.execute(line).map(Option::unwrap);
+
+    assert_eq!(
+        output("aut-num AS1")?,
+        "aut-num: AS1\nas-name: ONE\nimport: from AS2 accept ANY"
+    );
+    assert!(output("aut-num AS9").is_err());
+    assert_eq!(
+        output("expand AS-OUTER")?,
+        "2 ASes: AS2 AS3\nUnrecorded sets: AS-MISSING"
+    );
+    assert_eq!(output("expand as-outer")?, output("expand AS-OUTER")?);
+    assert!(output("expand AS-NONE").is_err());
+    assert_eq!(output("members-of AS2")?, "AS-INNER\nAS-OUTER");
+    assert_eq!(output("members-of AS-INNER")?, "AS-OUTER");
+    assert_eq!(output("members-of as-inner")?, "AS-OUTER");
+    assert_eq!(
+        output("members-of AS9")?,
+        "No as-set directly contains AS9."
+    );
+    assert!(output("check 10.0.0.0/8 1 2")?.contains("GoodImport { from: 2, to: 1 }"));
+    assert!(output("explain 10.0.0.0/8 1 2")?.contains("entry #0 (attribute #2)"));
+    assert!(output("check 10.0.0.0/8").is_err());
+    assert_eq!(output("help")?, HELP);
+    assert_eq!(shell.execute("quit")?, None);
+    assert!(shell.execute("frobnicate").is_err());
+    Ok(())
+}
+
+#[test]
+fn complete() {
+    let helper = ShellHelper::new(&shell().dump);
+    assert_eq!(helper.candidates("ex", 0, "ex"), ["expand", "explain"]);
+    assert_eq!(
+        helper.candidates("expand as-", 7, "as-"),
+        ["AS-INNER", "AS-OUTER"]
+    );
+    assert_eq!(
+        helper.candidates("aut-num AS", 8, "AS"),
+        ["AS-INNER", "AS-OUTER", "AS1"]
+    );
+}
@@ -0,0 +1,114 @@
+use maplit::btreemap;
+
+use crate::{
+    bgp::QueryDump,
+    parse::*,
+    whois::{Answer, Whois},
+};
+
+use super::*;
+
+fn whois() -> Whois {
+    Whois::new(QueryDump::from_dump(Dump {
+        aut_nums: btreemap! {Asn(1) => AutNum {
+            body: "as-name: ONE\n".into(),
+            ..AutNum::default()
+        }},
+        as_sets: btreemap! {
+            "AS-OUTER".into() => AsSet::new(
+                "members: AS2, AS-INNER\n".into(),
+                vec![Asn(2)],
+                vec!["AS-INNER".into()],
+            ),
+            "AS-INNER".into() => AsSet::new(String::new(), vec![Asn(3)], vec![]),
+        },
+        route_sets: btreemap! {
+            "RS-ONE".into() => RouteSet {
+                body: String::new(),
+                members: vec!["10.0.0.0/8^+".to_string().into(), "AS2".to_string().into()],
+            },
+        },
+        as_routes: btreemap! {
+            Asn(1) => vec!["10.0.0.0/8".parse().unwrap(), "2001:db8::/32".parse().unwrap()],
+            Asn(2) => vec!["10.1.0.0/16".parse().unwrap()],
+            Asn(3) => vec!["10.1.0.0/16".parse().unwrap(), "10.1.2.0/24".parse().unwrap()],
+        },
+        ..Dump::default()
+    }))
+}
+
+fn data(data: &str) -> Answer {
+    Answer::Data(data.into())
+}
+
+#[test]
+fn irrd_queries() {
+    let whois = whois();
+    assert_eq!(whois.answer("!gAS1"), data("10.0.0.0/8"));
+    assert_eq!(whois.answer("!6AS1"), data("2001:db8::/32"));
+    assert_eq!(whois.answer("!6AS2"), Answer::NotFound);
+    assert!(matches!(whois.answer("!gASX"), Answer::Error(_)));
+
+    assert_eq!(whois.answer("!iAS-OUTER"), data("AS2 AS-INNER"));
+    assert_eq!(whois.answer("!ias-outer,1"), data("AS2 AS3"));
+    assert_eq!(whois.answer("!iRS-ONE"), data("10.0.0.0/8^+ AS2"));
+    assert_eq!(whois.answer("!iRS-ONE,1"), data("10.0.0.0/8^+ 10.1.0.0/16"));
+    assert_eq!(whois.answer("!iAS-NONE"), Answer::NotFound);
+
+    assert_eq!(
+        whois.answer("!r10.1.0.0/16"),
+        data("route: 10.1.0.0/16\norigin: AS2\n\nroute: 10.1.0.0/16\norigin: AS3")
+    );
+    assert_eq!(whois.answer("!r10.1.0.0/16,o"), data("AS2 AS3"));
+    assert_eq!(
+        whois.answer("!r10.1.2.0/24,l"),
+        data("route: 10.1.0.0/16\norigin: AS2\n\nroute: 10.1.0.0/16\norigin: AS3")
+    );
+    assert_eq!(
+        whois
+            .answer("!r10.1.2.0/24,L")
+            .encode()
+            .matches("route:")
+            .count(),
+        4
+    );
+    assert_eq!(
+        whois.answer("!r10.1.0.0/16,M"),
+        data("route: 10.1.2.0/24\norigin: AS3")
+    );
+    assert_eq!(whois.answer("!r11.0.0.0/8"), Answer::NotFound);
+
+    assert_eq!(
+        whois.answer("!maut-num,AS1"),
+        data("aut-num: AS1\nas-name: ONE")
+    );
+    assert_eq!(whois.answer("!mas-set,AS1"), Answer::NotFound);
+    assert!(matches!(whois.answer("!z"), Answer::Error(_)));
+}
+
+#[test]
+fn ripe_queries() {
+    let whois = whois();
+    assert_eq!(
+        whois.answer("-i origin AS2"),
+        Answer::Text("route: 10.1.0.0/16\norigin: AS2\n\n".into())
+    );
+    assert_eq!(
+        whois.answer("as-outer"),
+        Answer::Text("as-set: AS-OUTER\nmembers: AS2, AS-INNER\n\n".into())
+    );
+    assert!(whois.answer("AS9").encode().starts_with("%ERROR:101"));
+}
+
+#[test]
+fn connection() -> Result<()> {
+    let whois = whois();
+    let mut output = Vec::new();
+    whois.handle(&b"!gAS2\n!gAS1\n"[..], &mut output)?;
+    assert_eq!(output, b"A12\n10.1.0.0/16\nC\n");
+
+    let mut output = Vec::new();
+    whois.handle(&b"!!\n!gAS2\n!gAS9\n!q\n!gAS1\n"[..], &mut output)?;
+    assert_eq!(output, b"A12\n10.1.0.0/16\nC\nD\n");
+    Ok(())
+}
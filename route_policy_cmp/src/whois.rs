@@ -0,0 +1,345 @@
+//! Whois server answering IRRd-style queries from a [`QueryDump`],
+//! so tools speaking the whois protocol can run against a local snapshot.
+//!
+//! Supported queries:
+//! - `!gAS<n>`, `!6AS<n>`: IPv4 or IPv6 prefixes originated by the AS.
+//! - `!i<set>`, `!i<set>,1`: members of an as-set or route-set,
+//!   or all the AS numbers or prefixes in it recursively.
+//! - `!r<prefix>[,o|l|L|M]`: route objects exactly matching the prefix,
+//!   their origins, the one-level or all less specifics, or all more
+//!   specifics.
+//! - `!m<class>,<key>`: the object of `class` named `key`.
+//! - `!!` to keep the connection open, `!q` to close it, `!v` for the version.
+//! - `-i origin AS<n>`: route objects originated by the AS.
+//! - `<key>`: the aut-num or set named `key`.
+//!
+//! `!` queries are answered in the IRRd framing: `A<length>` followed by the
+//! data and `C` on success, `C` for success without data, `D` if the key is
+//! not found, and `F <message>` on errors. Other queries are answered with
+//! plain RPSL text, each object followed by a blank line.
+//!
+//! The parsed dump does not keep the text of route objects,
+//! so they are answered with only the `route`/`route6` and `origin` attributes.
+use std::{
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use crate::{
+    analysis::bilateral::expand_route_set_ranges,
+    bgp::{cmp::RECURSION_LIMIT, QueryDump},
+    parse::Asn,
+};
+
+use super::*;
+
+/// Answer to one query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Answer {
+    /// IRRd-style data, answered as `A<length>`, the data, and `C`.
+    Data(String),
+    /// IRRd-style success without data, answered as `C`.
+    Success,
+    /// IRRd-style key not found, answered as `D`.
+    NotFound,
+    /// IRRd-style error, answered as `F <message>`.
+    Error(String),
+    /// Plain RPSL text, answered as is.
+    Text(String),
+    /// Keep the connection open for more queries.
+    KeepOpen,
+    /// Close the connection.
+    Quit,
+}
+
+impl Answer {
+    /// Bytes to send back, empty for [`Answer::KeepOpen`] and [`Answer::Quit`].
+    pub fn encode(&self) -> String {
+        match self {
+            Self::Data(data) => format!("A{}\n{data}\nC\n", data.len() + 1),
+            Self::Success => "C\n".into(),
+            Self::NotFound => "D\n".into(),
+            Self::Error(message) => format!("F {message}\n"),
+            Self::Text(text) => text.clone(),
+            Self::KeepOpen | Self::Quit => String::new(),
+        }
+    }
+}
+
+/// Whois server over `dump`.
+pub struct Whois {
+    pub dump: QueryDump,
+    /// Origins of each prefix with route objects, for `!r` queries.
+    origins: BTreeMap<IpNet, Vec<Asn>>,
+}
+
+impl Whois {
+    pub fn new(dump: QueryDump) -> Self {
+        let mut origins: BTreeMap<IpNet, Vec<Asn>> = BTreeMap::new();
+        for (num, routes) in &dump.as_routes {
+            for route in routes {
+                origins.entry(*route).or_default().push(*num);
+            }
+        }
+        for nums in origins.values_mut() {
+            nums.sort_unstable();
+        }
+        Self { dump, origins }
+    }
+
+    /// Accept connections on `listener` forever,
+    /// each handled on its own thread.
+    pub fn serve(&self, listener: TcpListener) -> Result<()> {
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        warn!("Failed to accept a whois connection: {err}.");
+                        continue;
+                    }
+                };
+                scope.spawn(move || {
+                    if let Err(err) = self.handle_stream(stream) {
+                        debug!("Whois connection closed: {err:#}.");
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+
+    fn handle_stream(&self, stream: TcpStream) -> Result<()> {
+        let peer = stream.peer_addr()?;
+        debug!("Whois connection from {peer}.");
+        self.handle(BufReader::new(stream.try_clone()?), stream)
+    }
+
+    /// Answer the queries read from `reader`, one per line, to `writer`.
+    /// Only the first query is answered unless `!!` is sent.
+    pub fn handle<R: BufRead, W: Write>(&self, reader: R, mut writer: W) -> Result<()> {
+        let mut keep_open = false;
+        for line in reader.lines() {
+            let answer = self.answer(&line?);
+            writer.write_all(answer.encode().as_bytes())?;
+            writer.flush()?;
+            match answer {
+                Answer::KeepOpen => keep_open = true,
+                Answer::Quit => break,
+                _ if !keep_open => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer one `query`.
+    pub fn answer(&self, query: &str) -> Answer {
+        let query = query.trim();
+        if let Some(command) = query.strip_prefix('!') {
+            let (kind, arg) = match command.char_indices().nth(1) {
+                Some((index, _)) => command.split_at(index),
+                None => (command, ""),
+            };
+            let arg = arg.trim();
+            match kind {
+                "!" => Answer::KeepOpen,
+                "q" => Answer::Quit,
+                "v" => Answer::Data(format!("route_policy_cmp {}", env!("CARGO_PKG_VERSION"))),
+                "g" => self.origin_prefixes(arg, false),
+                "6" => self.origin_prefixes(arg, true),
+                "i" => self.set_members(arg),
+                "r" => self.routes(arg),
+                "m" => self.match_object(arg),
+                _ => Answer::Error(format!("Unrecognized command `!{kind}`")),
+            }
+        } else if query.is_empty() {
+            Answer::Error("Empty query".into())
+        } else {
+            let words: Vec<_> = query.split_whitespace().collect();
+            match words.as_slice() {
+                ["-i", attribute, num] if attribute.eq_ignore_ascii_case("origin") => {
+                    match num.parse() {
+                        Ok(num) => text(self.origin_routes(num)),
+                        Err(_) => text(Vec::new()),
+                    }
+                }
+                [key] => text(self.object(key).into_iter().collect()),
+                _ => Answer::Text(format!("%ERROR:111: unsupported query `{query}`\n\n")),
+            }
+        }
+    }
+
+    fn origin_prefixes(&self, arg: &str, v6: bool) -> Answer {
+        let Ok(num) = arg.parse::<Asn>() else {
+            return Answer::Error(format!("Invalid AS number `{arg}`"));
+        };
+        let prefixes: Vec<_> = self
+            .dump
+            .as_routes
+            .get(&num)
+            .into_iter()
+            .flatten()
+            .filter(|prefix| matches!(prefix, IpNet::V6(_)) == v6)
+            .map(ToString::to_string)
+            .collect();
+        data(prefixes.join(" "))
+    }
+
+    fn set_members(&self, arg: &str) -> Answer {
+        let (name, recursive) = match arg.split_once(',') {
+            Some((name, "1")) => (name, true),
+            Some((_, option)) => return Answer::Error(format!("Invalid option `{option}`")),
+            None => (arg, false),
+        };
+        let name = name.trim().to_uppercase();
+        let members: Vec<String> = if let Some(as_set) = self.dump.as_sets.get(&name) {
+            match recursive {
+                true => match self.dump.expand_as_set(&name) {
+                    Some((nums, _)) => nums.iter().map(|num| format!("AS{num}")).collect(),
+                    None => return Answer::NotFound,
+                },
+                false => as_set
+                    .members
+                    .iter()
+                    .map(|num| format!("AS{num}"))
+                    .chain(as_set.set_members.iter().cloned())
+                    .collect(),
+            }
+        } else if let Some(route_set) = self.dump.route_sets.get(&name) {
+            match recursive {
+                true => match expand_route_set_ranges(&self.dump, &name, RECURSION_LIMIT) {
+                    Some(ranges) => ranges.iter().map(ToString::to_string).collect(),
+                    None => return Answer::Error(format!("Cannot expand {arg}")),
+                },
+                false => route_set.members.iter().map(ToString::to_string).collect(),
+            }
+        } else {
+            return Answer::NotFound;
+        };
+        data(members.join(" "))
+    }
+
+    fn routes(&self, arg: &str) -> Answer {
+        let (prefix, option) = arg.split_once(',').unwrap_or((arg, ""));
+        let Ok(prefix) = prefix.trim().parse::<IpNet>() else {
+            return Answer::Error(format!("Invalid prefix `{prefix}`"));
+        };
+        let prefix = prefix.trunc();
+        let routes: Vec<(IpNet, &[Asn])> = match option {
+            "" | "o" => self.origins_of(prefix).into_iter().collect(),
+            "l" => self.less_specifics(prefix).take(1).collect(),
+            "L" => self
+                .origins_of(prefix)
+                .into_iter()
+                .chain(self.less_specifics(prefix))
+                .collect(),
+            "M" => self
+                .origins
+                .range(prefix..)
+                .take_while(|(route, _)| route.addr() <= prefix.broadcast())
+                .filter(|(route, _)| **route != prefix && prefix.contains(*route))
+                .map(|(route, nums)| (*route, nums.as_slice()))
+                .collect(),
+            other => return Answer::Error(format!("Invalid option `{other}`")),
+        };
+        if option == "o" {
+            let nums: Vec<_> = routes
+                .iter()
+                .flat_map(|(_, nums)| nums.iter().map(|num| format!("AS{num}")))
+                .collect();
+            return data(nums.join(" "));
+        }
+        let objects: Vec<_> = routes
+            .into_iter()
+            .flat_map(|(route, nums)| nums.iter().map(move |num| route_object(route, *num)))
+            .collect();
+        data(objects.join("\n").trim_end().into())
+    }
+
+    fn origins_of(&self, prefix: IpNet) -> Option<(IpNet, &[Asn])> {
+        self.origins
+            .get(&prefix)
+            .map(|nums| (prefix, nums.as_slice()))
+    }
+
+    /// Routes covering `prefix`, from the longest to the shortest.
+    fn less_specifics(&self, prefix: IpNet) -> impl Iterator<Item = (IpNet, &[Asn])> {
+        std::iter::successors(prefix.supernet(), IpNet::supernet)
+            .filter_map(|supernet| self.origins_of(supernet))
+    }
+
+    fn match_object(&self, arg: &str) -> Answer {
+        let Some((class, key)) = arg.split_once(',') else {
+            return Answer::Error(format!("Expected `<class>,<key>`, got `{arg}`"));
+        };
+        match self.object(key.trim()) {
+            Some(object) if object.starts_with(&format!("{}:", class.trim())) => {
+                data(object.trim_end().into())
+            }
+            _ => Answer::NotFound,
+        }
+    }
+
+    /// RPSL text of the aut-num or set named `key`.
+    fn object(&self, key: &str) -> Option<String> {
+        let key = key.to_uppercase();
+        let (class, body) = if let Ok(num) = key.parse::<Asn>() {
+            ("aut-num", &self.dump.aut_nums.get(&num)?.body)
+        } else if let Some(as_set) = self.dump.as_sets.get(&key) {
+            ("as-set", &as_set.body)
+        } else if let Some(route_set) = self.dump.route_sets.get(&key) {
+            ("route-set", &route_set.body)
+        } else if let Some(filter_set) = self.dump.filter_sets.get(&key) {
+            ("filter-set", &filter_set.body)
+        } else if let Some(peering_set) = self.dump.peering_sets.get(&key) {
+            ("peering-set", &peering_set.body)
+        } else {
+            return None;
+        };
+        let mut object = format!("{class}: {key}\n{body}");
+        if !object.ends_with('\n') {
+            object.push('\n');
+        }
+        Some(object)
+    }
+
+    fn origin_routes(&self, num: Asn) -> Vec<String> {
+        self.dump
+            .as_routes
+            .get(&num)
+            .into_iter()
+            .flatten()
+            .map(|route| route_object(*route, num))
+            .collect()
+    }
+}
+
+fn data(data: String) -> Answer {
+    match data.is_empty() {
+        true => Answer::NotFound,
+        false => Answer::Data(data),
+    }
+}
+
+/// RIPE-style answer of `objects`, each ending with a newline.
+fn text(objects: Vec<String>) -> Answer {
+    if objects.is_empty() {
+        return Answer::Text("%ERROR:101: no entries found\n\n".into());
+    }
+    let mut result = String::new();
+    for object in objects {
+        _ = writeln!(result, "{object}");
+    }
+    Answer::Text(result)
+}
+
+fn route_object(route: IpNet, num: Asn) -> String {
+    let class = match route {
+        IpNet::V4(_) => "route",
+        IpNet::V6(_) => "route6",
+    };
+    format!("{class}: {route}\norigin: AS{num}\n")
+}
@@ -9,11 +9,11 @@ from .rpsl_object import AutNum
 def parse_aut_num():
     imports: dict[str, dict[str, list[dict]]] = {}
     exports: dict[str, dict[str, list[dict]]] = {}
-    for key, expr in expressions(stdin_lines()):
+    for index, (key, expr) in enumerate(expressions(stdin_lines())):
         if key == "import" or key == "mp-import":
-            parse_mp_import(expr, imports)
+            parse_mp_import(expr, imports, index)
         elif key == "export" or key == "mp-export":
-            parse_mp_import(expr, exports)
+            parse_mp_import(expr, exports, index)
     return AutNum("", "", imports, exports).__dict__
 
 
@@ -21,10 +21,12 @@ def red(string: str) -> str:
     return f"\033[91m{string}\033[0m"
 
 
-def parse_mp_import(expr: str, imports: dict[str, dict[str, list[dict]]]):
+def parse_mp_import(
+    expr: str, imports: dict[str, dict[str, list[dict]]], index: int | None = None
+):
     try:
         lexed = lex_with(mp_import, expr)
-        import_export(lexed, imports)
+        import_export(lexed, imports, index)
     except Exception as err:
         tag = red("[parse_mp_import]")
         print(f"{tag} {err} parsing `{expr}`.", file=sys.stderr)
@@ -33,11 +35,12 @@ def parse_mp_import(expr: str, imports: dict[str, dict[str, list[dict]]]):
 def parse_aut_num(obj: RPSLObject):
     imports: dict[str, dict[str, list[dict]]] = {}
     exports: dict[str, dict[str, list[dict]]] = {}
-    for key, expr in expressions(lines_continued(obj.body.splitlines())):
+    lines = lines_continued(obj.body.splitlines())
+    for index, (key, expr) in enumerate(expressions(lines)):
         if key == "import" or key == "mp-import":
-            parse_mp_import(expr, imports)
+            parse_mp_import(expr, imports, index)
         elif key in ("export", "mp-export", "default", "mp-default"):
-            parse_mp_import(expr, exports)
+            parse_mp_import(expr, exports, index)
     aut_nums.append(AutNum(obj.name, obj.body, imports, exports).__dict__)
 
 
@@ -363,8 +363,12 @@ def parse_afi_import_expression(
     return []
 
 
-def import_export(lexed: dict, result: dict[str, dict[str, list]]):
-    """Parse lexed <mp-import> or <mp-export>."""
+def import_export(
+    lexed: dict, result: dict[str, dict[str, list]], index: int | None = None
+):
+    """Parse lexed <mp-import> or <mp-export>.
+    Tag each entry with `index`, the index of its attribute in the object body,
+    if given."""
     if protocol_1 := lexed.get("protocol-1"):
         print(f"Ignoring protocol-1: {protocol_1}.", file=stderr)
     if protocol_2 := lexed.get("protocol-2"):
@@ -372,6 +376,8 @@ def import_export(lexed: dict, result: dict[str, dict[str, list]]):
 
     parsed_list = parse_afi_import_expression(lexed, set([("any", "any")]))
     for afi_entries, parsed in parsed_list:
+        if index is not None:
+            parsed = [{**entry, "index": index} for entry in parsed]
         for version, cast in afi_entries:
             version_entry = result.get(version, {})
             cast_entry = version_entry.get(cast, [])